@@ -5,6 +5,18 @@ mod circuits {
     use arcis::*;
 
     const MAX_ORDERS: usize = 100;
+    const MAX_SEGMENTS: usize = 16;
+    const DEPTH_BANDS: usize = 10;
+    const MATCH_BATCH_SIZE: usize = 8;
+    // Matching rounds in a single-fill computation; each round before the
+    // fill may cancel an order that keeps the top of the book from trading
+    const MATCH_ATTEMPTS: usize = 4;
+
+    // Time in force
+    const TIF_GTC: u8 = 0; // good till cancelled
+    const TIF_IOC: u8 = 1; // immediate or cancel
+    const TIF_FOK: u8 = 2; // fill or kill
+    const TIF_GTD: u8 = 3; // good till expires_at
 
     #[derive(Copy, Clone)]
     pub struct Order {
@@ -14,6 +26,21 @@ mod circuits {
         pub order_type: u8, // 0 = market, 1 = limit
         pub user_id: u128,
         pub active: u8, // 0 = inactive, 1 = active
+        pub sequence: u64, // arrival order across the market's segments
+        pub expires_at: u64, // unix timestamp, used by GTD orders
+        pub time_in_force: u8,
+    }
+
+    // Order fields supplied by the trader
+    #[derive(Copy, Clone)]
+    pub struct OrderInput {
+        pub price: u64,
+        pub amount: u64,
+        pub side: u8,
+        pub order_type: u8,
+        pub user_id: u128,
+        pub expires_at: u64,
+        pub time_in_force: u8,
     }
 
     #[derive(Copy, Clone)]
@@ -31,24 +58,77 @@ mod circuits {
         pub sell_order_id: u64,
     }
 
-    // Add order to encrypted order book
+    // Create the empty order book stored in the order book account
+    #[instruction]
+    pub fn init_order_book(mxe: Mxe) -> Enc<Mxe, OrderBook> {
+        let empty = Order {
+            price: 0,
+            amount: 0,
+            side: 0,
+            order_type: 0,
+            user_id: 0,
+            active: 0,
+            sequence: 0,
+            expires_at: 0,
+            time_in_force: TIF_GTC,
+        };
+        let ob = OrderBook {
+            orders: [empty; MAX_ORDERS],
+            order_count: 0,
+        };
+
+        mxe.from_arcis(ob)
+    }
+
+    // Add order to one segment of the market's book. Orders that break the
+    // market's rules (limit price off tick, amount off lot or below the
+    // minimum), GTD orders already expired at `now`, and orders that find the
+    // segment full are dropped. Expired orders are removed first. Reveals whether the
+    // order was added and whether the segment still has a free slot, so the
+    // router can move on to another segment.
     #[instruction]
     pub fn add_order(
-        order_ctxt: Enc<Shared, Order>,
+        order_ctxt: Enc<Shared, OrderInput>,
         orderbook_ctxt: Enc<Mxe, OrderBook>,
-    ) -> Enc<Mxe, OrderBook> {
-        let order = order_ctxt.to_arcis();
-        let mut ob = orderbook_ctxt.to_arcis();
+        tick_size: u64,
+        lot_size: u64,
+        min_order_size: u64,
+        sequence: u64,
+        now: u64,
+    ) -> (Enc<Mxe, OrderBook>, bool, bool) {
+        let input = order_ctxt.to_arcis();
+        let mut ob = expire_orders(orderbook_ctxt.to_arcis(), now);
+
+        let order = Order {
+            price: input.price,
+            amount: input.amount,
+            side: input.side,
+            order_type: input.order_type,
+            user_id: input.user_id,
+            active: 1,
+            sequence,
+            expires_at: input.expires_at,
+            time_in_force: input.time_in_force,
+        };
+
+        let on_tick = order.order_type == 0 || (order.price > 0 && order.price % tick_size == 0);
+        let on_lot = order.amount >= min_order_size && order.amount % lot_size == 0;
+        let live = order.time_in_force != TIF_GTD || order.expires_at > now;
+        let valid = on_tick
+            && on_lot
+            && live
+            && order.side <= 1
+            && order.order_type <= 1
+            && order.time_in_force <= TIF_GTD;
 
         // Find empty slot and add order
         let mut added = 0u8;
         for i in 0..MAX_ORDERS {
             let is_empty = ob.orders[i].active == 0;
-            let should_add = is_empty && added == 0;
+            let should_add = valid && is_empty && added == 0;
             
             if should_add {
                 ob.orders[i] = order;
-                ob.orders[i].active = 1;
                 added = 1;
             }
         }
@@ -57,85 +137,523 @@ mod circuits {
         let count_increment = if added == 1 { 1u64 } else { 0u64 };
         ob.order_count = ob.order_count + count_increment;
 
-        orderbook_ctxt.owner.from_arcis(ob)
+        let mut has_free_slot = false;
+        for i in 0..MAX_ORDERS {
+            if ob.orders[i].active == 0 {
+                has_free_slot = true;
+            }
+        }
+
+        (
+            orderbook_ctxt.owner.from_arcis(ob),
+            (added == 1).reveal(),
+            has_free_slot.reveal(),
+        )
     }
 
-    // Match orders in encrypted order book
-    #[instruction]
-    pub fn match_orders(
-        orderbook_ctxt: Enc<Mxe, OrderBook>,
-    ) -> (Enc<Mxe, OrderBook>, Enc<Shared, MatchResult>) {
-        let mut ob = orderbook_ctxt.to_arcis();
-        
-        let mut result = MatchResult {
+    // Remove GTD orders whose expiry has passed
+    fn expire_orders(mut ob: OrderBook, now: u64) -> OrderBook {
+        for i in 0..MAX_ORDERS {
+            let order = ob.orders[i];
+            let expired = order.time_in_force == TIF_GTD && order.expires_at <= now;
+            if order.active == 1 && expired {
+                ob.orders[i].active = 0;
+                ob.order_count = ob.order_count - 1;
+            }
+        }
+        ob
+    }
+
+    // Remove every resting order with the given time in force
+    fn cancel_time_in_force(mut ob: OrderBook, time_in_force: u8) -> OrderBook {
+        for i in 0..MAX_ORDERS {
+            let order = ob.orders[i];
+            if order.active == 1 && order.time_in_force == time_in_force {
+                ob.orders[i].active = 0;
+                ob.order_count = ob.order_count - 1;
+            }
+        }
+        ob
+    }
+
+    // Best resting order on one side seen so far. Orders rank by price
+    // (highest bid, lowest ask), market orders first; equal prices rank by
+    // lowest sequence.
+    #[derive(Copy, Clone)]
+    pub struct Top {
+        pub found: bool,
+        pub book: u8, // which of the books being matched
+        pub slot: u64,
+        pub rank: u64, // higher is better
+        pub sequence: u64,
+        pub market: bool,
+    }
+
+    fn no_top() -> Top {
+        Top {
+            found: false,
+            book: 0,
+            slot: 0,
+            rank: 0,
+            sequence: 0,
+            market: false,
+        }
+    }
+
+    // `limit_only` skips market orders
+    fn scan_top(ob: OrderBook, book: u8, side: u8, limit_only: bool, mut top: Top) -> Top {
+        for i in 0..MAX_ORDERS {
+            let order = ob.orders[i];
+            let is_market = order.order_type == 0;
+
+            // Market orders take the best possible rank
+            let rank = if is_market {
+                u64::MAX
+            } else if side == 0 {
+                order.price
+            } else {
+                u64::MAX - order.price
+            };
+            let better = !top.found
+                || rank > top.rank
+                || (rank == top.rank && order.sequence < top.sequence);
+            let eligible = order.active == 1 && order.side == side && !(limit_only && is_market);
+            if eligible && better {
+                top.found = true;
+                top.book = book;
+                top.slot = i as u64;
+                top.rank = rank;
+                top.sequence = order.sequence;
+                top.market = is_market;
+            }
+        }
+        top
+    }
+
+    // Best bid and ask, and best limit bid and ask, seen so far
+    #[derive(Copy, Clone)]
+    pub struct Tops {
+        pub bid: Top,
+        pub ask: Top,
+        pub limit_bid: Top,
+        pub limit_ask: Top,
+    }
+
+    fn no_tops() -> Tops {
+        Tops {
+            bid: no_top(),
+            ask: no_top(),
+            limit_bid: no_top(),
+            limit_ask: no_top(),
+        }
+    }
+
+    fn scan_tops(ob: OrderBook, book: u8, tops: Tops) -> Tops {
+        Tops {
+            bid: scan_top(ob, book, 0, false, tops.bid),
+            ask: scan_top(ob, book, 1, false, tops.ask),
+            limit_bid: scan_top(ob, book, 0, true, tops.limit_bid),
+            limit_ask: scan_top(ob, book, 1, true, tops.limit_ask),
+        }
+    }
+
+    // Bid and ask to cross next: the best of each side. Two market orders
+    // have no price to trade at, so then the earlier of them meets the best
+    // limit order on the other side (the later one does when the earlier
+    // finds no limit order).
+    fn pick_pair(tops: Tops) -> (Top, Top) {
+        let market_pair = tops.bid.found && tops.ask.found && tops.bid.market && tops.ask.market;
+        let bid_first = tops.limit_ask.found
+            && (tops.bid.sequence < tops.ask.sequence || !tops.limit_bid.found);
+
+        let mut bid = tops.bid;
+        let mut ask = tops.ask;
+        if market_pair && bid_first {
+            ask = tops.limit_ask;
+        }
+        if market_pair && !bid_first {
+            bid = tops.limit_bid;
+        }
+        (bid, ask)
+    }
+
+    // Whether some order on the other side of `tops` crosses `order` in
+    // price. A market order needs a limit order to trade with.
+    fn marketable(order: Order, tops: Tops) -> bool {
+        let is_market = order.order_type == 0;
+        // Bids rank by price and asks by u64::MAX - price
+        let crosses_limit = if order.side == 0 {
+            tops.ask.found && order.price >= u64::MAX - tops.ask.rank
+        } else {
+            tops.bid.found && tops.bid.rank >= order.price
+        };
+        let crosses_market = if order.side == 0 {
+            tops.limit_ask.found
+        } else {
+            tops.limit_bid.found
+        };
+        if is_market {
+            crosses_market
+        } else {
+            crosses_limit
+        }
+    }
+
+    // IOC and FOK orders stay in the book while the matching computations
+    // that follow them can still trade them. Once nothing on the other side
+    // of `tops` (the book after matching) crosses them, what is left of
+    // them is cancelled.
+    fn end_immediate_orders(mut ob: OrderBook, tops: Tops) -> OrderBook {
+        for i in 0..MAX_ORDERS {
+            let order = ob.orders[i];
+            let immediate = order.time_in_force == TIF_IOC || order.time_in_force == TIF_FOK;
+            if order.active == 1 && immediate && !marketable(order, tops) {
+                ob.orders[i].active = 0;
+                ob.order_count = ob.order_count - 1;
+            }
+        }
+        ob
+    }
+
+    fn order_at(ob: OrderBook, slot: u64) -> Order {
+        let mut order = ob.orders[0];
+        for i in 0..MAX_ORDERS {
+            if (i as u64) == slot {
+                order = ob.orders[i];
+            }
+        }
+        order
+    }
+
+    fn order_in(book_a: OrderBook, book_b: OrderBook, top: Top) -> Order {
+        if top.book == 0 {
+            order_at(book_a, top.slot)
+        } else {
+            order_at(book_b, top.slot)
+        }
+    }
+
+    // Reduce the order in `slot` by `amount` (when `apply`), deactivating it
+    // once fully filled
+    fn fill_slot(mut ob: OrderBook, slot: u64, amount: u64, apply: bool) -> OrderBook {
+        for i in 0..MAX_ORDERS {
+            if apply && (i as u64) == slot {
+                ob.orders[i].amount = ob.orders[i].amount - amount;
+                if ob.orders[i].amount == 0 {
+                    ob.orders[i].active = 0;
+                    ob.order_count = ob.order_count - 1;
+                }
+            }
+        }
+        ob
+    }
+
+    // Deactivate the order in `slot` (when `apply`)
+    fn cancel_slot(mut ob: OrderBook, slot: u64, apply: bool) -> OrderBook {
+        for i in 0..MAX_ORDERS {
+            if apply && (i as u64) == slot && ob.orders[i].active == 1 {
+                ob.orders[i].active = 0;
+                ob.order_count = ob.order_count - 1;
+            }
+        }
+        ob
+    }
+
+    // Outcome of crossing a bid with an ask
+    #[derive(Copy, Clone)]
+    pub struct Cross {
+        pub fill: bool,
+        pub price: u64,
+        pub amount: u64,
+        pub cancel_buy: bool,
+        pub cancel_sell: bool,
+    }
+
+    fn prices_cross(buy_order: Order, sell_order: Order) -> bool {
+        let buy_market = buy_order.order_type == 0;
+        let sell_market = sell_order.order_type == 0;
+        !(buy_market && sell_market)
+            && (buy_market || sell_market || buy_order.price >= sell_order.price)
+    }
+
+    // Size of the other users' orders in `ob` that cross `order` in price
+    fn crossing_liquidity(ob: OrderBook, order: Order) -> u64 {
+        let mut liquidity = 0u64;
+        for i in 0..MAX_ORDERS {
+            let other = ob.orders[i];
+            let crosses = if order.side == 0 {
+                prices_cross(order, other)
+            } else {
+                prices_cross(other, order)
+            };
+            let counter = other.active == 1
+                && other.side != order.side
+                && other.user_id != order.user_id;
+            if counter && crosses {
+                liquidity = liquidity + other.amount;
+            }
+        }
+        liquidity
+    }
+
+    // Price and size at which a bid and an ask cross. When they cross in
+    // price but can't trade, the order in the way is cancelled so the next
+    // candidate can rank: the newer order of a user trading with themselves,
+    // and a FOK order that the crossing liquidity on the other side
+    // (`buy_liquidity` for the buy order, `sell_liquidity` for the sell
+    // order) can't complete. A FOK order that it can complete fills against
+    // one order per round.
+    fn cross(
+        buy_order: Order,
+        sell_order: Order,
+        buy_liquidity: u64,
+        sell_liquidity: u64,
+    ) -> Cross {
+        let buy_market = buy_order.order_type == 0;
+        let sell_market = sell_order.order_type == 0;
+        let price_match = prices_cross(buy_order, sell_order);
+        let same_user = buy_order.user_id == sell_order.user_id;
+
+        // Midpoint for limit orders, limit price against a market order
+        let match_price = if buy_market {
+            sell_order.price
+        } else if sell_market {
+            buy_order.price
+        } else {
+            (buy_order.price + sell_order.price) / 2
+        };
+
+        // Minimum of both orders
+        let match_amount = if buy_order.amount < sell_order.amount {
+            buy_order.amount
+        } else {
+            sell_order.amount
+        };
+
+        let buy_fok_short = buy_order.time_in_force == TIF_FOK && buy_liquidity < buy_order.amount;
+        let sell_fok_short = sell_order.time_in_force == TIF_FOK && sell_liquidity < sell_order.amount;
+        let buy_newer = buy_order.sequence > sell_order.sequence;
+
+        Cross {
+            fill: price_match && !same_user && !buy_fok_short && !sell_fok_short,
+            price: match_price,
+            amount: match_amount,
+            cancel_buy: price_match && ((same_user && buy_newer) || (!same_user && buy_fok_short)),
+            cancel_sell: price_match && ((same_user && !buy_newer) || (!same_user && sell_fok_short)),
+        }
+    }
+
+    fn no_match() -> MatchResult {
+        MatchResult {
             matched: 0,
             match_price: 0,
             match_amount: 0,
             buy_order_id: 0,
             sell_order_id: 0,
-        };
+        }
+    }
 
-        // Find matching buy and sell orders
-        for i in 0..MAX_ORDERS {
-            for j in 0..MAX_ORDERS {
-                let buy_order = ob.orders[i];
-                let sell_order = ob.orders[j];
-
-                // Check if orders can match
-                let is_buy = buy_order.side == 0;
-                let is_sell = sell_order.side == 1;
-                let both_active = buy_order.active == 1 && sell_order.active == 1;
-                let not_same_user = buy_order.user_id != sell_order.user_id;
-                
-                // Price matching logic
-                let price_match = if buy_order.order_type == 0 || sell_order.order_type == 0 {
-                    // Market order - always matches
-                    1u8
-                } else {
-                    // Limit order - buy price >= sell price
-                    if buy_order.price >= sell_order.price { 1u8 } else { 0u8 }
-                };
-
-                let can_match = is_buy && is_sell && both_active && not_same_user && price_match == 1 && result.matched == 0;
-
-                if can_match {
-                    // Calculate match price (midpoint for limit orders, limit price for market)
-                    let match_price = if buy_order.order_type == 0 {
-                        sell_order.price
-                    } else if sell_order.order_type == 0 {
-                        buy_order.price
-                    } else {
-                        (buy_order.price + sell_order.price) / 2
-                    };
-
-                    // Calculate match amount (minimum of both orders)
-                    let match_amount = if buy_order.amount < sell_order.amount {
-                        buy_order.amount
-                    } else {
-                        sell_order.amount
-                    };
-
-                    // Update result
-                    result.matched = 1;
-                    result.match_price = match_price;
-                    result.match_amount = match_amount;
-                    result.buy_order_id = i as u64;
-                    result.sell_order_id = j as u64;
-
-                    // Update order amounts
-                    ob.orders[i].amount = ob.orders[i].amount - match_amount;
-                    ob.orders[j].amount = ob.orders[j].amount - match_amount;
-
-                    // Deactivate fully filled orders
-                    if ob.orders[i].amount == 0 {
-                        ob.orders[i].active = 0;
-                    }
-                    if ob.orders[j].amount == 0 {
-                        ob.orders[j].active = 0;
-                    }
-                }
+    // One matching round (when `live`): cross the pair picked from the top
+    // of the book (price-time priority), or cancel the order keeping it from
+    // trading. Also returns whether an order was cancelled, in which case
+    // another round can rank the next candidate.
+    fn match_round(ob: OrderBook, live: bool) -> (OrderBook, MatchResult, bool) {
+        let mut result = no_match();
+
+        let (bid, ask) = pick_pair(scan_tops(ob, 0, no_tops()));
+        let buy_order = order_at(ob, bid.slot);
+        let sell_order = order_at(ob, ask.slot);
+        let c = cross(
+            buy_order,
+            sell_order,
+            crossing_liquidity(ob, buy_order),
+            crossing_liquidity(ob, sell_order),
+        );
+        let found = live && bid.found && ask.found;
+        let fill = found && c.fill;
+        let cancel_buy = found && c.cancel_buy;
+        let cancel_sell = found && c.cancel_sell;
+
+        if fill {
+            result.matched = 1;
+            result.match_price = c.price;
+            result.match_amount = c.amount;
+            result.buy_order_id = bid.slot;
+            result.sell_order_id = ask.slot;
+        }
+
+        let ob = fill_slot(ob, bid.slot, c.amount, fill);
+        let ob = fill_slot(ob, ask.slot, c.amount, fill);
+        let ob = cancel_slot(ob, bid.slot, cancel_buy);
+        let ob = cancel_slot(ob, ask.slot, cancel_sell);
+
+        (ob, result, cancel_buy || cancel_sell)
+    }
+
+    // Single fill: up to MATCH_ATTEMPTS rounds, stopping at the first round
+    // that doesn't cancel an order
+    fn match_best(mut ob: OrderBook) -> (OrderBook, MatchResult) {
+        let mut result = no_match();
+        let mut live = true;
+
+        for _ in 0..MATCH_ATTEMPTS {
+            let (next_ob, round, cancelled) = match_round(ob, live);
+            ob = next_ob;
+            if round.matched == 1 {
+                result = round;
+            }
+            live = live && cancelled;
+        }
+
+        (ob, result)
+    }
+
+    // Best levels of one segment (its tops without the slot), kept in the
+    // market's levels record so match_segments can tell whether another
+    // segment holds a better order
+    #[derive(Copy, Clone)]
+    pub struct Level {
+        pub found: bool,
+        pub rank: u64,
+        pub sequence: u64,
+        pub market: bool,
+    }
+
+    #[derive(Copy, Clone)]
+    pub struct SegmentLevels {
+        pub bid: Level,
+        pub ask: Level,
+        pub limit_bid: Level,
+        pub limit_ask: Level,
+    }
+
+    #[derive(Copy, Clone)]
+    pub struct MarketLevels {
+        pub segments: [SegmentLevels; MAX_SEGMENTS],
+    }
+
+    fn level(top: Top) -> Level {
+        Level {
+            found: top.found,
+            rank: top.rank,
+            sequence: top.sequence,
+            market: top.market,
+        }
+    }
+
+    fn no_level() -> Level {
+        level(no_top())
+    }
+
+    // Fold a stored level into `top` the way scan_top ranks orders
+    fn fold_level(level: Level, book: u8, mut top: Top) -> Top {
+        let better = !top.found
+            || level.rank > top.rank
+            || (level.rank == top.rank && level.sequence < top.sequence);
+        if level.found && better {
+            top.found = true;
+            top.book = book;
+            top.slot = 0;
+            top.rank = level.rank;
+            top.sequence = level.sequence;
+            top.market = level.market;
+        }
+        top
+    }
+
+    fn fold_levels(levels: SegmentLevels, book: u8, tops: Tops) -> Tops {
+        Tops {
+            bid: fold_level(levels.bid, book, tops.bid),
+            ask: fold_level(levels.ask, book, tops.ask),
+            limit_bid: fold_level(levels.limit_bid, book, tops.limit_bid),
+            limit_ask: fold_level(levels.limit_ask, book, tops.limit_ask),
+        }
+    }
+
+    // Replace the levels of `segment` with those of its current book
+    fn set_levels(mut levels: MarketLevels, segment: u64, ob: OrderBook) -> MarketLevels {
+        let tops = scan_tops(ob, 0, no_tops());
+        let fresh = SegmentLevels {
+            bid: level(tops.bid),
+            ask: level(tops.ask),
+            limit_bid: level(tops.limit_bid),
+            limit_ask: level(tops.limit_ask),
+        };
+        for s in 0..MAX_SEGMENTS {
+            if (s as u64) == segment {
+                levels.segments[s] = fresh;
             }
         }
+        levels
+    }
+
+    // Segments holding the market-wide pair match_segments should cross
+    // next, revealed so the caller knows which segments to pass. Both are
+    // MAX_SEGMENTS unless the pair crosses in price.
+    fn next_segments(levels: MarketLevels) -> (u8, u8) {
+        let mut tops = no_tops();
+        for s in 0..MAX_SEGMENTS {
+            tops = fold_levels(levels.segments[s], s as u8, tops);
+        }
+        let (bid, ask) = pick_pair(tops);
+
+        // Bids rank by price and asks by u64::MAX - price
+        let crosses = bid.found
+            && ask.found
+            && (bid.market || ask.market || bid.rank >= u64::MAX - ask.rank);
+        let none = MAX_SEGMENTS as u8;
+        let bid_segment = if crosses { bid.book } else { none };
+        let ask_segment = if crosses { ask.book } else { none };
+
+        (bid_segment.reveal(), ask_segment.reveal())
+    }
+
+    // Create the market's levels record, with every segment empty
+    #[instruction]
+    pub fn init_market_levels(mxe: Mxe) -> Enc<Mxe, MarketLevels> {
+        let empty = SegmentLevels {
+            bid: no_level(),
+            ask: no_level(),
+            limit_bid: no_level(),
+            limit_ask: no_level(),
+        };
+        let levels = MarketLevels {
+            segments: [empty; MAX_SEGMENTS],
+        };
+
+        mxe.from_arcis(levels)
+    }
+
+    // Record one segment's best levels in the market's levels record,
+    // removing expired orders from the segment first. Reveals the segments
+    // match_segments should cross next.
+    #[instruction]
+    pub fn collect_levels(
+        segment: u64,
+        book_ctxt: Enc<Mxe, OrderBook>,
+        levels_ctxt: Enc<Mxe, MarketLevels>,
+        now: u64,
+    ) -> (Enc<Mxe, OrderBook>, Enc<Mxe, MarketLevels>, u8, u8) {
+        let book = expire_orders(book_ctxt.to_arcis(), now);
+        let levels = set_levels(levels_ctxt.to_arcis(), segment, book);
+        let (bid_segment, ask_segment) = next_segments(levels);
+
+        (
+            book_ctxt.owner.from_arcis(book),
+            levels_ctxt.owner.from_arcis(levels),
+            bid_segment,
+            ask_segment,
+        )
+    }
+
+    // Match orders in encrypted order book (single fill)
+    #[instruction]
+    pub fn match_orders(
+        orderbook_ctxt: Enc<Mxe, OrderBook>,
+        now: u64,
+    ) -> (Enc<Mxe, OrderBook>, Enc<Shared, MatchResult>) {
+        let (ob, result) = match_best(expire_orders(orderbook_ctxt.to_arcis(), now));
+        let ob = end_immediate_orders(ob, scan_tops(ob, 0, no_tops()));
 
         let updated_ob = orderbook_ctxt.owner.from_arcis(ob);
         let match_result = orderbook_ctxt.owner.from_arcis(result);
@@ -143,6 +661,241 @@ mod circuits {
         (updated_ob, match_result)
     }
 
+    // Run MATCH_BATCH_SIZE matching rounds in one computation. A round that
+    // cancels an order in the way has matched = 0 but lets the next round
+    // rank the next candidate; once the book stops crossing the remaining
+    // results have matched = 0.
+    #[instruction]
+    pub fn match_orders_batch(
+        orderbook_ctxt: Enc<Mxe, OrderBook>,
+        now: u64,
+    ) -> (Enc<Mxe, OrderBook>, Enc<Shared, [MatchResult; MATCH_BATCH_SIZE]>) {
+        let mut ob = expire_orders(orderbook_ctxt.to_arcis(), now);
+        let mut results = [no_match(); MATCH_BATCH_SIZE];
+
+        for k in 0..MATCH_BATCH_SIZE {
+            let (next_ob, result, _) = match_round(ob, true);
+            ob = next_ob;
+            results[k] = result;
+        }
+        let ob = end_immediate_orders(ob, scan_tops(ob, 0, no_tops()));
+
+        let updated_ob = orderbook_ctxt.owner.from_arcis(ob);
+        let match_results = orderbook_ctxt.owner.from_arcis(results);
+
+        (updated_ob, match_results)
+    }
+
+    // Cross the best bid and best ask across two segments of the same
+    // market, the same way match_orders does within one. Only crosses a
+    // pair the whole market would pick: the other segments' orders are
+    // ranked from the levels record (as book 2), and a round whose pair
+    // isn't in the two books matches nothing. Updates both segments' levels
+    // and reveals the segments to cross next. Order ids in the result are
+    // segment * MAX_ORDERS + slot.
+    #[instruction]
+    pub fn match_segments(
+        segment_a: u64,
+        book_a_ctxt: Enc<Mxe, OrderBook>,
+        segment_b: u64,
+        book_b_ctxt: Enc<Mxe, OrderBook>,
+        levels_ctxt: Enc<Mxe, MarketLevels>,
+        now: u64,
+    ) -> (
+        Enc<Mxe, OrderBook>,
+        Enc<Mxe, OrderBook>,
+        Enc<Mxe, MarketLevels>,
+        Enc<Shared, MatchResult>,
+        u8,
+        u8,
+    ) {
+        let mut book_a = expire_orders(book_a_ctxt.to_arcis(), now);
+        let mut book_b = expire_orders(book_b_ctxt.to_arcis(), now);
+        let levels = levels_ctxt.to_arcis();
+        let mut result = no_match();
+        let mut live = true;
+
+        let mut others = no_tops();
+        for s in 0..MAX_SEGMENTS {
+            if (s as u64) != segment_a && (s as u64) != segment_b {
+                others = fold_levels(levels.segments[s], 2, others);
+            }
+        }
+
+        for _ in 0..MATCH_ATTEMPTS {
+            let (bid, ask) = pick_pair(scan_tops(book_b, 1, scan_tops(book_a, 0, others)));
+            let buy_order = order_in(book_a, book_b, bid);
+            let sell_order = order_in(book_a, book_b, ask);
+            let c = cross(
+                buy_order,
+                sell_order,
+                crossing_liquidity(book_a, buy_order) + crossing_liquidity(book_b, buy_order),
+                crossing_liquidity(book_a, sell_order) + crossing_liquidity(book_b, sell_order),
+            );
+            let in_books = bid.book != 2 && ask.book != 2;
+            let found = live && in_books && bid.found && ask.found;
+            let fill = found && c.fill;
+            let cancel_buy = found && c.cancel_buy;
+            let cancel_sell = found && c.cancel_sell;
+
+            if fill {
+                let bid_segment = if bid.book == 0 { segment_a } else { segment_b };
+                let ask_segment = if ask.book == 0 { segment_a } else { segment_b };
+                result.matched = 1;
+                result.match_price = c.price;
+                result.match_amount = c.amount;
+                result.buy_order_id = bid_segment * (MAX_ORDERS as u64) + bid.slot;
+                result.sell_order_id = ask_segment * (MAX_ORDERS as u64) + ask.slot;
+            }
+
+            book_a = fill_slot(book_a, bid.slot, c.amount, fill && bid.book == 0);
+            book_a = fill_slot(book_a, ask.slot, c.amount, fill && ask.book == 0);
+            book_b = fill_slot(book_b, bid.slot, c.amount, fill && bid.book == 1);
+            book_b = fill_slot(book_b, ask.slot, c.amount, fill && ask.book == 1);
+            book_a = cancel_slot(book_a, bid.slot, cancel_buy && bid.book == 0);
+            book_a = cancel_slot(book_a, ask.slot, cancel_sell && ask.book == 0);
+            book_b = cancel_slot(book_b, bid.slot, cancel_buy && bid.book == 1);
+            book_b = cancel_slot(book_b, ask.slot, cancel_sell && ask.book == 1);
+            live = live && (cancel_buy || cancel_sell);
+        }
+        // Orders in the other segments count too: the market's best pair may
+        // have been there
+        let tops = scan_tops(book_b, 1, scan_tops(book_a, 0, others));
+        let book_a = end_immediate_orders(book_a, tops);
+        let book_b = end_immediate_orders(book_b, tops);
+        let levels = set_levels(set_levels(levels, segment_a, book_a), segment_b, book_b);
+        let (bid_segment, ask_segment) = next_segments(levels);
+
+        (
+            book_a_ctxt.owner.from_arcis(book_a),
+            book_b_ctxt.owner.from_arcis(book_b),
+            levels_ctxt.owner.from_arcis(levels),
+            book_a_ctxt.owner.from_arcis(result),
+            bid_segment,
+            ask_segment,
+        )
+    }
+
+    // Pro-rata share of `volume` for one side: order i gets
+    // amount * volume / side_total rounded down, and the rounding remainder
+    // goes one unit at a time to eligible orders in slot order.
+    fn allocate_fills(
+        amounts: [u64; MAX_ORDERS],
+        eligible: [bool; MAX_ORDERS],
+        side_total: u64,
+        volume: u64,
+    ) -> [u64; MAX_ORDERS] {
+        let mut fills = [0u64; MAX_ORDERS];
+        let mut allocated = 0u64;
+        for i in 0..MAX_ORDERS {
+            if eligible[i] && side_total > 0 {
+                let share = (amounts[i] as u128 * volume as u128) / side_total as u128;
+                fills[i] = share as u64;
+                allocated = allocated + fills[i];
+            }
+        }
+
+        let mut remainder = volume - allocated;
+        for i in 0..MAX_ORDERS {
+            if eligible[i] && remainder > 0 && fills[i] < amounts[i] {
+                fills[i] = fills[i] + 1;
+                remainder = remainder - 1;
+            }
+        }
+
+        fills
+    }
+
+    // Uniform-price batch auction over every active order. The clearing
+    // price is the limit price that maximizes matched volume (ties: smallest
+    // buy/sell imbalance, then lowest price). Every crossing order fills at
+    // that price; the side with excess size is filled pro-rata. Only the
+    // clearing price and total volume are revealed. A pro-rata fill can't
+    // promise FOK orders a complete fill, so they are cancelled unfilled.
+    #[instruction]
+    pub fn clear_batch_auction(
+        orderbook_ctxt: Enc<Mxe, OrderBook>,
+        now: u64,
+    ) -> (Enc<Mxe, OrderBook>, u64, u64) {
+        let ob = expire_orders(orderbook_ctxt.to_arcis(), now);
+        let mut ob = cancel_time_in_force(ob, TIF_FOK);
+
+        // Candidate prices are the limit prices in the book
+        let mut best_price = 0u64;
+        let mut best_volume = 0u64;
+        let mut best_imbalance = 0u64;
+        for c in 0..MAX_ORDERS {
+            let candidate = ob.orders[c];
+            let is_candidate = candidate.active == 1 && candidate.order_type == 1;
+            let price = candidate.price;
+
+            let mut demand = 0u64;
+            let mut supply = 0u64;
+            for i in 0..MAX_ORDERS {
+                let order = ob.orders[i];
+                let is_market = order.order_type == 0;
+                if order.active == 1 && order.side == 0 && (is_market || order.price >= price) {
+                    demand = demand + order.amount;
+                }
+                if order.active == 1 && order.side == 1 && (is_market || order.price <= price) {
+                    supply = supply + order.amount;
+                }
+            }
+
+            let volume = if demand < supply { demand } else { supply };
+            let imbalance = if demand > supply { demand - supply } else { supply - demand };
+            let better = volume > best_volume
+                || (volume == best_volume && volume > 0
+                    && (imbalance < best_imbalance
+                        || (imbalance == best_imbalance && price < best_price)));
+            if is_candidate && better {
+                best_price = price;
+                best_volume = volume;
+                best_imbalance = imbalance;
+            }
+        }
+
+        // Orders crossing the clearing price
+        let mut amounts = [0u64; MAX_ORDERS];
+        let mut buys = [false; MAX_ORDERS];
+        let mut sells = [false; MAX_ORDERS];
+        let mut demand = 0u64;
+        let mut supply = 0u64;
+        for i in 0..MAX_ORDERS {
+            let order = ob.orders[i];
+            let is_market = order.order_type == 0;
+            amounts[i] = order.amount;
+            buys[i] = order.active == 1 && order.side == 0 && (is_market || order.price >= best_price);
+            sells[i] = order.active == 1 && order.side == 1 && (is_market || order.price <= best_price);
+            if buys[i] {
+                demand = demand + order.amount;
+            }
+            if sells[i] {
+                supply = supply + order.amount;
+            }
+        }
+
+        let buy_fills = allocate_fills(amounts, buys, demand, best_volume);
+        let sell_fills = allocate_fills(amounts, sells, supply, best_volume);
+
+        for i in 0..MAX_ORDERS {
+            let fill = buy_fills[i] + sell_fills[i];
+            let was_active = ob.orders[i].active == 1;
+            ob.orders[i].amount = ob.orders[i].amount - fill;
+            if was_active && ob.orders[i].amount == 0 {
+                ob.orders[i].active = 0;
+                ob.order_count = ob.order_count - 1;
+            }
+        }
+        let ob = cancel_time_in_force(ob, TIF_IOC);
+
+        (
+            orderbook_ctxt.owner.from_arcis(ob),
+            best_price.reveal(),
+            best_volume.reveal(),
+        )
+    }
+
     // Cancel order from encrypted order book
     #[instruction]
     pub fn cancel_order(
@@ -170,45 +923,59 @@ mod circuits {
         orderbook_ctxt.owner.from_arcis(ob)
     }
 
-    // Get order book depth (privacy-preserving aggregation)
+    // Get order book depth (privacy-preserving aggregation), encrypted to the viewer.
+    // Limit orders are summed into DEPTH_BANDS bands of `tick_size` per side:
+    // buy band k holds prices in (reference - (k+1) * tick, reference - k * tick],
+    // sell band k holds prices in [reference + k * tick, reference + (k+1) * tick).
+    // Band 0 also takes any order through the reference price; orders beyond the
+    // last band are left out. With a non-zero `noise_scale` every band gets
+    // symmetric noise in (-noise_scale, noise_scale), clamped at zero.
     #[instruction]
     pub fn get_orderbook_depth(
+        viewer: Shared,
         orderbook_ctxt: Enc<Mxe, OrderBook>,
-        price_levels: u64,
+        reference_price: u64,
+        tick_size: u64,
+        noise_scale: u64,
     ) -> Enc<Shared, [u64; 20]> {
         let ob = orderbook_ctxt.to_arcis();
         let mut depth: [u64; 20] = [0; 20];
 
-        // Aggregate buy orders by price level (simplified)
-        for i in 0..10 {
-            let mut level_volume = 0u64;
+        for k in 0..DEPTH_BANDS {
+            let offset = (k as u64) * tick_size;
+            let buy_upper = reference_price - offset;
+            let buy_lower = buy_upper - tick_size;
+            let sell_lower = reference_price + offset;
+            let sell_upper = sell_lower + tick_size;
+
+            let mut buy_volume = 0u64;
+            let mut sell_volume = 0u64;
             for j in 0..MAX_ORDERS {
                 let order = ob.orders[j];
-                let is_buy = order.side == 0;
-                let is_active = order.active == 1;
-                
-                if is_buy && is_active {
-                    level_volume = level_volume + order.amount;
+                let is_limit = order.active == 1 && order.order_type == 1;
+
+                let in_buy_band = order.price > buy_lower && (k == 0 || order.price <= buy_upper);
+                if is_limit && order.side == 0 && in_buy_band {
+                    buy_volume = buy_volume + order.amount;
                 }
-            }
-            depth[i] = level_volume;
-        }
 
-        // Aggregate sell orders by price level
-        for i in 10..20 {
-            let mut level_volume = 0u64;
-            for j in 0..MAX_ORDERS {
-                let order = ob.orders[j];
-                let is_sell = order.side == 1;
-                let is_active = order.active == 1;
-                
-                if is_sell && is_active {
-                    level_volume = level_volume + order.amount;
+                let in_sell_band = order.price < sell_upper && (k == 0 || order.price >= sell_lower);
+                if is_limit && order.side == 1 && in_sell_band {
+                    sell_volume = sell_volume + order.amount;
                 }
             }
-            depth[i] = level_volume;
+            depth[k] = buy_volume;
+            depth[DEPTH_BANDS + k] = sell_volume;
+        }
+
+        // Difference of two uniform draws in [0, noise_scale)
+        for i in 0..(2 * DEPTH_BANDS) {
+            let up = (ArcisRNG::gen_integer_from_width(32) as u64 * noise_scale) >> 32;
+            let down = (ArcisRNG::gen_integer_from_width(32) as u64 * noise_scale) >> 32;
+            let raised = depth[i] + up;
+            depth[i] = if raised > down { raised - down } else { 0 };
         }
 
-        orderbook_ctxt.owner.from_arcis(depth)
+        viewer.from_arcis(depth)
     }
 }
@@ -1,11 +1,52 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
 use arcium_anchor::prelude::*;
 
 // Computation definition offsets for encrypted instructions
+const COMP_DEF_OFFSET_INIT_ORDER_BOOK: u32 = comp_def_offset("init_order_book");
 const COMP_DEF_OFFSET_ADD_ORDER: u32 = comp_def_offset("add_order");
 const COMP_DEF_OFFSET_MATCH_ORDERS: u32 = comp_def_offset("match_orders");
+const COMP_DEF_OFFSET_MATCH_ORDERS_BATCH: u32 = comp_def_offset("match_orders_batch");
+const COMP_DEF_OFFSET_MATCH_SEGMENTS: u32 = comp_def_offset("match_segments");
+const COMP_DEF_OFFSET_CLEAR_BATCH_AUCTION: u32 = comp_def_offset("clear_batch_auction");
 const COMP_DEF_OFFSET_CANCEL_ORDER: u32 = comp_def_offset("cancel_order");
 const COMP_DEF_OFFSET_GET_ORDERBOOK_DEPTH: u32 = comp_def_offset("get_orderbook_depth");
+const COMP_DEF_OFFSET_INIT_MARKET_LEVELS: u32 = comp_def_offset("init_market_levels");
+const COMP_DEF_OFFSET_COLLECT_LEVELS: u32 = comp_def_offset("collect_levels");
+
+// Order book layout; must match `OrderBook` in encrypted-ixs/match_orders.rs
+const MAX_ORDERS: usize = 100;
+// price, amount, side, order_type, user_id, active, sequence, expires_at, time_in_force
+const ORDER_FIELDS: usize = 9;
+const ORDER_BOOK_CIPHERTEXTS: usize = MAX_ORDERS * ORDER_FIELDS + 1; // + order_count
+
+// A market's book is split into up to MAX_SEGMENTS segments of MAX_ORDERS each
+const MAX_SEGMENTS: u8 = 16;
+
+// Levels record layout; must match `MarketLevels` in encrypted-ixs/match_orders.rs
+const LEVEL_FIELDS: usize = 4; // found, rank, sequence, market
+const SEGMENT_LEVELS: usize = 4; // bid, ask, limit bid, limit ask
+const MARKET_LEVELS_CIPHERTEXTS: usize = MAX_SEGMENTS as usize * SEGMENT_LEVELS * LEVEL_FIELDS;
+
+// Fills per match_orders_batch computation; must match MATCH_BATCH_SIZE in the circuit
+const MATCH_BATCH_SIZE: usize = 8;
+const MATCH_RESULT_FIELDS: usize = 5; // matched, match_price, match_amount, buy_order_id, sell_order_id
+
+const ORDER_BOOK_SEED: &[u8] = b"order_book";
+const MARKET_SEED: &[u8] = b"market";
+const MARKET_LEVELS_SEED: &[u8] = b"market_levels";
+const CONFIG_SEED: &[u8] = b"config";
+const DEPTH_VIEWER_SEED: &[u8] = b"depth_viewer";
+
+// Price bands per side; the depth result holds buy bands then sell bands
+const DEPTH_BANDS: u64 = 10;
+const DEPTH_LEVELS: usize = 2 * DEPTH_BANDS as usize;
+// Noise draws are 32-bit fractions of the scale
+const MAX_DEPTH_NOISE_SCALE: u64 = u32::MAX as u64;
+
+// A computation that hasn't called back by then no longer blocks the book
+const ORDER_BOOK_LOCK_TIMEOUT: i64 = 600;
 
 declare_id!("DarkPoo1111111111111111111111111111111111111");
 
@@ -14,6 +55,11 @@ pub mod darkpool {
     use super::*;
 
     // Initialize computation definitions
+    pub fn init_order_book_comp_def(ctx: Context<InitOrderBookCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
     pub fn init_add_order_comp_def(ctx: Context<InitAddOrderCompDef>) -> Result<()> {
         init_comp_def(ctx.accounts, None, None)?;
         Ok(())
@@ -24,6 +70,21 @@ pub mod darkpool {
         Ok(())
     }
 
+    pub fn init_match_orders_batch_comp_def(ctx: Context<InitMatchOrdersBatchCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_match_segments_comp_def(ctx: Context<InitMatchSegmentsCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_clear_batch_auction_comp_def(ctx: Context<InitClearBatchAuctionCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
     pub fn init_cancel_order_comp_def(ctx: Context<InitCancelOrderCompDef>) -> Result<()> {
         init_comp_def(ctx.accounts, None, None)?;
         Ok(())
@@ -34,18 +95,379 @@ pub mod darkpool {
         Ok(())
     }
 
-    // Add order to encrypted order book
+    pub fn init_market_levels_comp_def(ctx: Context<InitMarketLevelsCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_collect_levels_comp_def(ctx: Context<InitCollectLevelsCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    // Set the authority that manages depth viewers
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.bump = ctx.bumps.config;
+
+        msg!("Darkpool config initialized. Authority: {}", config.authority);
+        Ok(())
+    }
+
+    // Allow a market maker to request order book depth
+    pub fn add_depth_viewer(ctx: Context<AddDepthViewer>, viewer: Pubkey) -> Result<()> {
+        let record = &mut ctx.accounts.depth_viewer;
+        record.viewer = viewer;
+        record.added_at = Clock::get()?.unix_timestamp;
+        record.bump = ctx.bumps.depth_viewer;
+
+        emit!(DepthViewerAddedEvent { viewer });
+        Ok(())
+    }
+
+    pub fn remove_depth_viewer(_ctx: Context<RemoveDepthViewer>, viewer: Pubkey) -> Result<()> {
+        emit!(DepthViewerRemovedEvent { viewer });
+        Ok(())
+    }
+
+    // Register a trading pair; each market gets its own order book
+    pub fn create_market(
+        ctx: Context<CreateMarket>,
+        market_id: u64,
+        base_mint: Pubkey,
+        quote_mint: Pubkey,
+        tick_size: u64,
+        lot_size: u64,
+        min_order_size: u64,
+    ) -> Result<()> {
+        require!(
+            base_mint != quote_mint
+                && tick_size > 0
+                && lot_size > 0
+                && min_order_size >= lot_size
+                && min_order_size % lot_size == 0,
+            ErrorCode::InvalidMarketParams
+        );
+
+        let market = &mut ctx.accounts.market;
+        market.market_id = market_id;
+        market.base_mint = base_mint;
+        market.quote_mint = quote_mint;
+        market.tick_size = tick_size;
+        market.lot_size = lot_size;
+        market.min_order_size = min_order_size;
+        market.bump = ctx.bumps.market;
+
+        emit!(MarketCreatedEvent {
+            market_id,
+            base_mint,
+            quote_mint,
+            tick_size,
+            lot_size,
+            min_order_size,
+        });
+        Ok(())
+    }
+
+    // Allocate the next order book segment of a market; it is larger than
+    // one instruction can allocate, so grow_order_book is called until it
+    // reaches full size
+    pub fn create_order_book(
+        ctx: Context<CreateOrderBook>,
+        market_id: u64,
+        segment: u8,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(
+            segment == market.segment_count && segment < MAX_SEGMENTS,
+            ErrorCode::InvalidSegment
+        );
+        market.segment_count += 1;
+
+        msg!("Order book segment {} created for market {}", segment, market_id);
+        Ok(())
+    }
+
+    pub fn grow_order_book(ctx: Context<GrowOrderBook>, market_id: u64, segment: u8) -> Result<()> {
+        msg!(
+            "Order book {}/{} size: {}/{}",
+            market_id,
+            segment,
+            ctx.accounts.order_book.to_account_info().data_len(),
+            OrderBookAccount::LEN
+        );
+        Ok(())
+    }
+
+    // Encrypt an empty order book under the MXE key
+    pub fn init_order_book(
+        ctx: Context<InitOrderBook>,
+        computation_offset: u64,
+        market_id: u64,
+        segment: u8,
+        nonce: u128,
+    ) -> Result<()> {
+        {
+            let mut book = ctx.accounts.order_book.load_mut()?;
+            require!(book.initialized == 0, ErrorCode::OrderBookAlreadyInitialized);
+            book.market_id = market_id;
+            book.segment = segment;
+        }
+        lock_order_book(
+            &ctx.accounts.order_book,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+
+        let args = ArgBuilder::new().plaintext_u128(nonce).build();
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![InitOrderBookCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[order_book_callback_account(&ctx.accounts.order_book)]
+            )?],
+            1,
+            0,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "init_order_book")]
+    pub fn init_order_book_callback(
+        ctx: Context<InitOrderBookCallback>,
+        output: SignedComputationOutputs<InitOrderBookOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account
+        ) {
+            Ok(InitOrderBookOutput { field_0 }) => field_0,
+            Err(e) => {
+                msg!("Error: {}", e);
+                return Err(ErrorCode::AbortedComputation.into())
+            },
+        };
+
+        let (computation_offset, market_id, segment) = store_order_book(
+            &ctx.accounts.order_book,
+            ctx.accounts.computation_account.key(),
+            &o,
+        )?;
+
+        emit!(OrderBookInitializedEvent {
+            market_id,
+            segment,
+            computation_offset,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Create the market's levels record (each segment's best bid and ask,
+    // encrypted under the MXE key) that match_segments ranks other segments by
+    pub fn init_market_levels(
+        ctx: Context<InitMarketLevels>,
+        computation_offset: u64,
+        market_id: u64,
+        nonce: u128,
+    ) -> Result<()> {
+        {
+            let mut levels = ctx.accounts.market_levels.load_init()?;
+            levels.market_id = market_id;
+            lock_market_levels(
+                &mut levels,
+                computation_offset,
+                ctx.accounts.computation_account.key(),
+            )?;
+        }
+
+        let args = ArgBuilder::new().plaintext_u128(nonce).build();
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![InitMarketLevelsCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[market_levels_callback_account(&ctx.accounts.market_levels)]
+            )?],
+            1,
+            0,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "init_market_levels")]
+    pub fn init_market_levels_callback(
+        ctx: Context<InitMarketLevelsCallback>,
+        output: SignedComputationOutputs<InitMarketLevelsOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account
+        ) {
+            Ok(InitMarketLevelsOutput { field_0 }) => field_0,
+            Err(e) => {
+                msg!("Error: {}", e);
+                return Err(ErrorCode::AbortedComputation.into())
+            },
+        };
+
+        let market_id = store_market_levels(
+            &ctx.accounts.market_levels,
+            ctx.accounts.computation_account.key(),
+            &o,
+        )?;
+
+        msg!("Levels record initialized for market {}", market_id);
+        Ok(())
+    }
+
+    // Record one segment's best levels in the market's levels record (and
+    // drop its expired orders). Call it for every segment changed since its
+    // levels were last recorded before match_segments.
+    pub fn collect_levels(
+        ctx: Context<CollectLevels>,
+        computation_offset: u64,
+        market_id: u64,
+        segment: u8,
+    ) -> Result<()> {
+        let book_nonce = lock_initialized_order_book(
+            &ctx.accounts.order_book,
+            market_id,
+            segment,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+        let levels_nonce = lock_initialized_market_levels(
+            &ctx.accounts.market_levels,
+            market_id,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+
+        let args = ArgBuilder::new()
+            .plaintext_u64(segment as u64)
+            .plaintext_u128(book_nonce)
+            .account(
+                ctx.accounts.order_book.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u128(levels_nonce)
+            .account(
+                ctx.accounts.market_levels.key(),
+                MarketLevelsAccount::CIPHERTEXTS_OFFSET,
+                MarketLevelsAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(Clock::get()?.unix_timestamp as u64)
+            .build();
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CollectLevelsCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    order_book_callback_account(&ctx.accounts.order_book),
+                    market_levels_callback_account(&ctx.accounts.market_levels),
+                ]
+            )?],
+            1,
+            0,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "collect_levels")]
+    pub fn collect_levels_callback(
+        ctx: Context<CollectLevelsCallback>,
+        output: SignedComputationOutputs<CollectLevelsOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account
+        ) {
+            Ok(CollectLevelsOutput { field_0, field_1, field_2, field_3 }) => {
+                (field_0, field_1, field_2, field_3)
+            },
+            Err(e) => {
+                msg!("Error: {}", e);
+                return Err(ErrorCode::AbortedComputation.into())
+            },
+        };
+
+        let (computation_offset, market_id, segment) = store_order_book(
+            &ctx.accounts.order_book,
+            ctx.accounts.computation_account.key(),
+            &o.0,
+        )?;
+        store_market_levels(
+            &ctx.accounts.market_levels,
+            ctx.accounts.computation_account.key(),
+            &o.1,
+        )?;
+        record_levels_version(&ctx.accounts.market_levels, &ctx.accounts.order_book)?;
+
+        emit!(LevelsCollectedEvent {
+            market_id,
+            segment,
+            computation_offset,
+            next_bid_segment: o.2,
+            next_ask_segment: o.3,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Add order to one segment of the market's encrypted order book. Orders
+    // off the market's tick or lot size, or that find the segment full, are
+    // dropped inside the circuit; the callback reports which.
     pub fn add_order(
         ctx: Context<AddOrder>,
         computation_offset: u64,
+        market_id: u64,
+        segment: u8,
         order_price: [u8; 32],
         order_amount: [u8; 32],
         order_side: [u8; 32],
         order_type: [u8; 32],
         user_id: [u8; 32],
+        order_expires_at: [u8; 32],
+        order_time_in_force: [u8; 32],
         pub_key: [u8; 32],
         nonce: u128,
     ) -> Result<()> {
+        require!(ctx.accounts.order_book.load()?.full == 0, ErrorCode::SegmentFull);
+        let book_nonce = lock_initialized_order_book(
+            &ctx.accounts.order_book,
+            market_id,
+            segment,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        let sequence = market.next_sequence;
+        market.next_sequence += 1;
+
         let args = ArgBuilder::new()
             .x25519_pubkey(pub_key)
             .plaintext_u128(nonce)
@@ -54,6 +476,19 @@ pub mod darkpool {
             .encrypted_u8(order_side)
             .encrypted_u8(order_type)
             .encrypted_u128(user_id)
+            .encrypted_u64(order_expires_at)
+            .encrypted_u8(order_time_in_force)
+            .plaintext_u128(book_nonce)
+            .account(
+                ctx.accounts.order_book.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(market.tick_size)
+            .plaintext_u64(market.lot_size)
+            .plaintext_u64(market.min_order_size)
+            .plaintext_u64(sequence)
+            .plaintext_u64(Clock::get()?.unix_timestamp as u64)
             .build();
 
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
@@ -66,7 +501,7 @@ pub mod darkpool {
             vec![AddOrderCallback::callback_ix(
                 computation_offset,
                 &ctx.accounts.mxe_account,
-                &[]
+                &[order_book_callback_account(&ctx.accounts.order_book)]
             )?],
             1,
             0,
@@ -79,19 +514,29 @@ pub mod darkpool {
         ctx: Context<AddOrderCallback>,
         output: SignedComputationOutputs<AddOrderOutput>,
     ) -> Result<()> {
-        let _o = match output.verify_output(
+        let o = match output.verify_output(
             &ctx.accounts.cluster_account,
             &ctx.accounts.computation_account
         ) {
-            Ok(AddOrderOutput { field_0 }) => field_0,
+            Ok(AddOrderOutput { field_0, field_1, field_2 }) => (field_0, field_1, field_2),
             Err(e) => {
                 msg!("Error: {}", e);
                 return Err(ErrorCode::AbortedComputation.into())
             },
         };
 
+        let (computation_offset, market_id, segment) = store_order_book(
+            &ctx.accounts.order_book,
+            ctx.accounts.computation_account.key(),
+            &o.0,
+        )?;
+        ctx.accounts.order_book.load_mut()?.full = u8::from(!o.2);
+
         emit!(OrderAddedEvent {
-            computation_offset: ctx.accounts.computation_account.computation_offset,
+            market_id,
+            segment,
+            computation_offset,
+            accepted: o.1,
             timestamp: Clock::get()?.unix_timestamp,
         });
         Ok(())
@@ -101,8 +546,26 @@ pub mod darkpool {
     pub fn match_orders(
         ctx: Context<MatchOrders>,
         computation_offset: u64,
+        market_id: u64,
+        segment: u8,
     ) -> Result<()> {
-        let args = ArgBuilder::new().build();
+        let book_nonce = lock_initialized_order_book(
+            &ctx.accounts.order_book,
+            market_id,
+            segment,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+
+        let args = ArgBuilder::new()
+            .plaintext_u128(book_nonce)
+            .account(
+                ctx.accounts.order_book.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(Clock::get()?.unix_timestamp as u64)
+            .build();
 
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
@@ -114,7 +577,7 @@ pub mod darkpool {
             vec![MatchOrdersCallback::callback_ix(
                 computation_offset,
                 &ctx.accounts.mxe_account,
-                &[]
+                &[order_book_callback_account(&ctx.accounts.order_book)]
             )?],
             1,
             0,
@@ -138,8 +601,16 @@ pub mod darkpool {
             },
         };
 
+        let (computation_offset, market_id, segment) = store_order_book(
+            &ctx.accounts.order_book,
+            ctx.accounts.computation_account.key(),
+            &o.0,
+        )?;
+
         emit!(OrdersMatchedEvent {
-            computation_offset: ctx.accounts.computation_account.computation_offset,
+            market_id,
+            segment,
+            computation_offset,
             match_result: o.1.ciphertexts[0],
             nonce: o.1.nonce.to_le_bytes(),
             timestamp: Clock::get()?.unix_timestamp,
@@ -147,20 +618,29 @@ pub mod darkpool {
         Ok(())
     }
 
-    // Cancel order
-    pub fn cancel_order(
-        ctx: Context<CancelOrder>,
+    // Match up to MATCH_BATCH_SIZE fills in one computation
+    pub fn match_orders_batch(
+        ctx: Context<MatchOrdersBatch>,
         computation_offset: u64,
-        order_id: u64,
-        user_id: [u8; 32],
-        pub_key: [u8; 32],
-        nonce: u128,
+        market_id: u64,
+        segment: u8,
     ) -> Result<()> {
+        let book_nonce = lock_initialized_order_book(
+            &ctx.accounts.order_book,
+            market_id,
+            segment,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+
         let args = ArgBuilder::new()
-            .plaintext_u64(order_id)
-            .x25519_pubkey(pub_key)
-            .plaintext_u128(nonce)
-            .encrypted_u128(user_id)
+            .plaintext_u128(book_nonce)
+            .account(
+                ctx.accounts.order_book.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(Clock::get()?.unix_timestamp as u64)
             .build();
 
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
@@ -170,10 +650,10 @@ pub mod darkpool {
             computation_offset,
             args,
             None,
-            vec![CancelOrderCallback::callback_ix(
+            vec![MatchOrdersBatchCallback::callback_ix(
                 computation_offset,
                 &ctx.accounts.mxe_account,
-                &[]
+                &[order_book_callback_account(&ctx.accounts.order_book)]
             )?],
             1,
             0,
@@ -181,125 +661,2091 @@ pub mod darkpool {
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "cancel_order")]
-    pub fn cancel_order_callback(
-        ctx: Context<CancelOrderCallback>,
-        output: SignedComputationOutputs<CancelOrderOutput>,
+    #[arcium_callback(encrypted_ix = "match_orders_batch")]
+    pub fn match_orders_batch_callback(
+        ctx: Context<MatchOrdersBatchCallback>,
+        output: SignedComputationOutputs<MatchOrdersBatchOutput>,
     ) -> Result<()> {
-        let _o = match output.verify_output(
+        let o = match output.verify_output(
             &ctx.accounts.cluster_account,
             &ctx.accounts.computation_account
         ) {
-            Ok(CancelOrderOutput { field_0 }) => field_0,
+            Ok(MatchOrdersBatchOutput { field_0, field_1 }) => (field_0, field_1),
             Err(e) => {
                 msg!("Error: {}", e);
                 return Err(ErrorCode::AbortedComputation.into())
             },
         };
 
-        emit!(OrderCancelledEvent {
-            computation_offset: ctx.accounts.computation_account.computation_offset,
+        let (computation_offset, market_id, segment) = store_order_book(
+            &ctx.accounts.order_book,
+            ctx.accounts.computation_account.key(),
+            &o.0,
+        )?;
+
+        emit!(OrdersBatchMatchedEvent {
+            market_id,
+            segment,
+            computation_offset,
+            match_results: o.1.ciphertexts,
+            nonce: o.1.nonce.to_le_bytes(),
             timestamp: Clock::get()?.unix_timestamp,
         });
         Ok(())
     }
-}
 
-// Events
-#[event]
-pub struct OrderAddedEvent {
-    pub computation_offset: u64,
-    pub timestamp: i64,
-}
+    // Cross the best bid and ask across two segments of a market. The
+    // market's other segments are passed as remaining accounts, in segment
+    // order; each must be idle with its levels collected since its last
+    // update, so the circuit can rank their orders from the levels record.
+    pub fn match_segments<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MatchSegments<'info>>,
+        computation_offset: u64,
+        market_id: u64,
+        segment_a: u8,
+        segment_b: u8,
+    ) -> Result<()> {
+        require!(segment_a != segment_b, ErrorCode::InvalidSegmentPair);
+        check_other_segments(
+            ctx.remaining_accounts,
+            &ctx.accounts.market_levels,
+            market_id,
+            ctx.accounts.market.segment_count,
+            segment_a,
+            segment_b,
+        )?;
+        let nonce_a = lock_initialized_order_book(
+            &ctx.accounts.order_book_a,
+            market_id,
+            segment_a,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+        let nonce_b = lock_initialized_order_book(
+            &ctx.accounts.order_book_b,
+            market_id,
+            segment_b,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+        let levels_nonce = lock_initialized_market_levels(
+            &ctx.accounts.market_levels,
+            market_id,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
 
-#[event]
-pub struct OrdersMatchedEvent {
-    pub computation_offset: u64,
-    pub match_result: [u8; 32],
-    pub nonce: [u8; 16],
-    pub timestamp: i64,
-}
+        let args = ArgBuilder::new()
+            .plaintext_u64(segment_a as u64)
+            .plaintext_u128(nonce_a)
+            .account(
+                ctx.accounts.order_book_a.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(segment_b as u64)
+            .plaintext_u128(nonce_b)
+            .account(
+                ctx.accounts.order_book_b.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u128(levels_nonce)
+            .account(
+                ctx.accounts.market_levels.key(),
+                MarketLevelsAccount::CIPHERTEXTS_OFFSET,
+                MarketLevelsAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(Clock::get()?.unix_timestamp as u64)
+            .build();
 
-#[event]
-pub struct OrderCancelledEvent {
-    pub computation_offset: u64,
-    pub timestamp: i64,
-}
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![MatchSegmentsCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[
+                    order_book_callback_account(&ctx.accounts.order_book_a),
+                    order_book_callback_account(&ctx.accounts.order_book_b),
+                    market_levels_callback_account(&ctx.accounts.market_levels),
+                ]
+            )?],
+            1,
+            0,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "match_segments")]
+    pub fn match_segments_callback(
+        ctx: Context<MatchSegmentsCallback>,
+        output: SignedComputationOutputs<MatchSegmentsOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account
+        ) {
+            Ok(MatchSegmentsOutput { field_0, field_1, field_2, field_3, field_4, field_5 }) => {
+                (field_0, field_1, field_2, field_3, field_4, field_5)
+            },
+            Err(e) => {
+                msg!("Error: {}", e);
+                return Err(ErrorCode::AbortedComputation.into())
+            },
+        };
+
+        let (computation_offset, market_id, segment_a) = store_order_book(
+            &ctx.accounts.order_book_a,
+            ctx.accounts.computation_account.key(),
+            &o.0,
+        )?;
+        let (_, _, segment_b) = store_order_book(
+            &ctx.accounts.order_book_b,
+            ctx.accounts.computation_account.key(),
+            &o.1,
+        )?;
+        store_market_levels(
+            &ctx.accounts.market_levels,
+            ctx.accounts.computation_account.key(),
+            &o.2,
+        )?;
+        record_levels_version(&ctx.accounts.market_levels, &ctx.accounts.order_book_a)?;
+        record_levels_version(&ctx.accounts.market_levels, &ctx.accounts.order_book_b)?;
+
+        emit!(SegmentsMatchedEvent {
+            market_id,
+            segment_a,
+            segment_b,
+            computation_offset,
+            match_result: o.3.ciphertexts,
+            nonce: o.3.nonce.to_le_bytes(),
+            next_bid_segment: o.4,
+            next_ask_segment: o.5,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Clear the whole book in a uniform-price batch auction. The clearing
+    // price is computed over one segment, so only single-segment markets can
+    // use it; a market with more would clear at a different price per segment
+    pub fn clear_batch_auction(
+        ctx: Context<ClearBatchAuction>,
+        computation_offset: u64,
+        market_id: u64,
+        segment: u8,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.market.segment_count == 1,
+            ErrorCode::SegmentedAuction
+        );
+        let book_nonce = lock_initialized_order_book(
+            &ctx.accounts.order_book,
+            market_id,
+            segment,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+
+        let args = ArgBuilder::new()
+            .plaintext_u128(book_nonce)
+            .account(
+                ctx.accounts.order_book.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(Clock::get()?.unix_timestamp as u64)
+            .build();
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ClearBatchAuctionCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[order_book_callback_account(&ctx.accounts.order_book)]
+            )?],
+            1,
+            0,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "clear_batch_auction")]
+    pub fn clear_batch_auction_callback(
+        ctx: Context<ClearBatchAuctionCallback>,
+        output: SignedComputationOutputs<ClearBatchAuctionOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account
+        ) {
+            Ok(ClearBatchAuctionOutput { field_0, field_1, field_2 }) => (field_0, field_1, field_2),
+            Err(e) => {
+                msg!("Error: {}", e);
+                return Err(ErrorCode::AbortedComputation.into())
+            },
+        };
+
+        let (computation_offset, market_id, segment) = store_order_book(
+            &ctx.accounts.order_book,
+            ctx.accounts.computation_account.key(),
+            &o.0,
+        )?;
+
+        emit!(BatchAuctionClearedEvent {
+            market_id,
+            segment,
+            computation_offset,
+            clearing_price: o.1,
+            volume: o.2,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Aggregate depth of the book in price bands of `tick_size` around
+    // `reference_price`, encrypted to the viewer's x25519 key. A non-zero
+    // `noise_scale` adds noise to every band. Reads the book without locking
+    // it: depth reflects the last stored book.
+    pub fn get_orderbook_depth(
+        ctx: Context<GetOrderbookDepth>,
+        computation_offset: u64,
+        market_id: u64,
+        segment: u8,
+        pub_key: [u8; 32],
+        nonce: u128,
+        reference_price: u64,
+        tick_size: u64,
+        noise_scale: u64,
+    ) -> Result<()> {
+        // Band edges are computed in the circuit and must not wrap
+        let span = tick_size
+            .checked_mul(DEPTH_BANDS)
+            .ok_or(ErrorCode::InvalidDepthParams)?;
+        require!(
+            tick_size > 0
+                && reference_price >= span
+                && reference_price.checked_add(span).is_some(),
+            ErrorCode::InvalidDepthParams
+        );
+        require!(noise_scale <= MAX_DEPTH_NOISE_SCALE, ErrorCode::InvalidDepthParams);
+
+        let book = ctx.accounts.order_book.load()?;
+        require!(book.initialized == 1, ErrorCode::OrderBookNotInitialized);
+        require!(book.market_id == market_id, ErrorCode::MarketMismatch);
+        require!(book.segment == segment, ErrorCode::SegmentMismatch);
+        let book_nonce = u128::from_le_bytes(book.nonce);
+        drop(book);
+
+        let args = ArgBuilder::new()
+            .x25519_pubkey(pub_key)
+            .plaintext_u128(nonce)
+            .plaintext_u128(book_nonce)
+            .account(
+                ctx.accounts.order_book.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .plaintext_u64(reference_price)
+            .plaintext_u64(tick_size)
+            .plaintext_u64(noise_scale)
+            .build();
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![GetOrderbookDepthCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[CallbackAccount {
+                    pubkey: ctx.accounts.order_book.key(),
+                    is_writable: false,
+                }]
+            )?],
+            1,
+            0,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "get_orderbook_depth")]
+    pub fn get_orderbook_depth_callback(
+        ctx: Context<GetOrderbookDepthCallback>,
+        output: SignedComputationOutputs<GetOrderbookDepthOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account
+        ) {
+            Ok(GetOrderbookDepthOutput { field_0 }) => field_0,
+            Err(e) => {
+                msg!("Error: {}", e);
+                return Err(ErrorCode::AbortedComputation.into())
+            },
+        };
+
+        emit!(OrderbookDepthEvent {
+            market_id: ctx.accounts.order_book.load()?.market_id,
+            segment: ctx.accounts.order_book.load()?.segment,
+            computation_account: ctx.accounts.computation_account.key(),
+            encryption_key: o.encryption_key,
+            depth: o.ciphertexts,
+            nonce: o.nonce.to_le_bytes(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Cancel order
+    pub fn cancel_order(
+        ctx: Context<CancelOrder>,
+        computation_offset: u64,
+        market_id: u64,
+        segment: u8,
+        order_id: u64,
+        user_id: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        let book_nonce = lock_initialized_order_book(
+            &ctx.accounts.order_book,
+            market_id,
+            segment,
+            computation_offset,
+            ctx.accounts.computation_account.key(),
+        )?;
+
+        let args = ArgBuilder::new()
+            .plaintext_u64(order_id)
+            .x25519_pubkey(pub_key)
+            .plaintext_u128(nonce)
+            .encrypted_u128(user_id)
+            .plaintext_u128(book_nonce)
+            .account(
+                ctx.accounts.order_book.key(),
+                OrderBookAccount::CIPHERTEXTS_OFFSET,
+                OrderBookAccount::CIPHERTEXTS_LEN,
+            )
+            .build();
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CancelOrderCallback::callback_ix(
+                computation_offset,
+                &ctx.accounts.mxe_account,
+                &[order_book_callback_account(&ctx.accounts.order_book)]
+            )?],
+            1,
+            0,
+        )?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "cancel_order")]
+    pub fn cancel_order_callback(
+        ctx: Context<CancelOrderCallback>,
+        output: SignedComputationOutputs<CancelOrderOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account
+        ) {
+            Ok(CancelOrderOutput { field_0 }) => field_0,
+            Err(e) => {
+                msg!("Error: {}", e);
+                return Err(ErrorCode::AbortedComputation.into())
+            },
+        };
+
+        let (computation_offset, market_id, segment) = store_order_book(
+            &ctx.accounts.order_book,
+            ctx.accounts.computation_account.key(),
+            &o,
+        )?;
+
+        emit!(OrderCancelledEvent {
+            market_id,
+            segment,
+            computation_offset,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+// Order book helpers
+
+// Every computation that rewrites the book takes this lock, so two queued
+// computations never both start from the same ciphertexts and lose an update
+fn lock_order_book(
+    order_book: &AccountLoader<OrderBookAccount>,
+    computation_offset: u64,
+    computation_account: Pubkey,
+) -> Result<u128> {
+    let now = Clock::get()?.unix_timestamp;
+    let mut book = order_book.load_mut()?;
+    require!(
+        book.locked == 0 || now > book.locked_at + ORDER_BOOK_LOCK_TIMEOUT,
+        ErrorCode::OrderBookBusy
+    );
+    book.locked = 1;
+    book.locked_at = now;
+    book.pending_computation = computation_account;
+    book.pending_offset = computation_offset;
+    Ok(u128::from_le_bytes(book.nonce))
+}
+
+fn lock_initialized_order_book(
+    order_book: &AccountLoader<OrderBookAccount>,
+    market_id: u64,
+    segment: u8,
+    computation_offset: u64,
+    computation_account: Pubkey,
+) -> Result<u128> {
+    {
+        let book = order_book.load()?;
+        require!(book.initialized == 1, ErrorCode::OrderBookNotInitialized);
+        require!(book.market_id == market_id, ErrorCode::MarketMismatch);
+        require!(book.segment == segment, ErrorCode::SegmentMismatch);
+    }
+    lock_order_book(order_book, computation_offset, computation_account)
+}
+
+// Store the MXE's updated book if this computation still holds the lock;
+// returns the computation offset and the book's market and segment
+fn store_order_book(
+    order_book: &AccountLoader<OrderBookAccount>,
+    computation_account: Pubkey,
+    output: &MXEEncryptedStruct<ORDER_BOOK_CIPHERTEXTS>,
+) -> Result<(u64, u64, u8)> {
+    let mut book = order_book.load_mut()?;
+    require!(
+        book.locked == 1 && book.pending_computation == computation_account,
+        ErrorCode::StaleComputation
+    );
+    book.ciphertexts = output.ciphertexts;
+    book.nonce = output.nonce.to_le_bytes();
+    book.initialized = 1;
+    book.locked = 0;
+    // Any update may free slots; add_order's callback sets it again
+    book.full = 0;
+    book.version += 1;
+    Ok((book.pending_offset, book.market_id, book.segment))
+}
+
+fn order_book_callback_account(order_book: &AccountLoader<OrderBookAccount>) -> CallbackAccount {
+    CallbackAccount {
+        pubkey: order_book.key(),
+        is_writable: true,
+    }
+}
+
+// Levels record helpers
+
+// Same lock as the order books take
+fn lock_market_levels(
+    levels: &mut MarketLevelsAccount,
+    computation_offset: u64,
+    computation_account: Pubkey,
+) -> Result<u128> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        levels.locked == 0 || now > levels.locked_at + ORDER_BOOK_LOCK_TIMEOUT,
+        ErrorCode::LevelsBusy
+    );
+    levels.locked = 1;
+    levels.locked_at = now;
+    levels.pending_computation = computation_account;
+    levels.pending_offset = computation_offset;
+    Ok(u128::from_le_bytes(levels.nonce))
+}
+
+fn lock_initialized_market_levels(
+    market_levels: &AccountLoader<MarketLevelsAccount>,
+    market_id: u64,
+    computation_offset: u64,
+    computation_account: Pubkey,
+) -> Result<u128> {
+    let mut levels = market_levels.load_mut()?;
+    require!(levels.initialized == 1, ErrorCode::LevelsNotInitialized);
+    require!(levels.market_id == market_id, ErrorCode::MarketMismatch);
+    lock_market_levels(&mut levels, computation_offset, computation_account)
+}
+
+// Store the MXE's updated levels if this computation still holds the lock;
+// returns the market they belong to
+fn store_market_levels(
+    market_levels: &AccountLoader<MarketLevelsAccount>,
+    computation_account: Pubkey,
+    output: &MXEEncryptedStruct<MARKET_LEVELS_CIPHERTEXTS>,
+) -> Result<u64> {
+    let mut levels = market_levels.load_mut()?;
+    require!(
+        levels.locked == 1 && levels.pending_computation == computation_account,
+        ErrorCode::StaleComputation
+    );
+    levels.ciphertexts = output.ciphertexts;
+    levels.nonce = output.nonce.to_le_bytes();
+    levels.initialized = 1;
+    levels.locked = 0;
+    Ok(levels.market_id)
+}
+
+// Note the book version the stored levels of its segment reflect
+fn record_levels_version(
+    market_levels: &AccountLoader<MarketLevelsAccount>,
+    order_book: &AccountLoader<OrderBookAccount>,
+) -> Result<()> {
+    let book = order_book.load()?;
+    market_levels.load_mut()?.versions[book.segment as usize] = book.version;
+    Ok(())
+}
+
+// The levels record must reflect every other segment of the market as it
+// is now: each is idle and unchanged since its levels were collected.
+// Segments that were never initialized hold no orders and are skipped.
+fn check_other_segments<'info>(
+    others: &'info [AccountInfo<'info>],
+    market_levels: &AccountLoader<MarketLevelsAccount>,
+    market_id: u64,
+    segment_count: u8,
+    segment_a: u8,
+    segment_b: u8,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let levels = market_levels.load()?;
+    let mut others = others.iter();
+
+    for segment in 0..segment_count {
+        if segment == segment_a || segment == segment_b {
+            continue;
+        }
+        let info = others.next().ok_or(ErrorCode::MissingSegment)?;
+        let (address, _) = Pubkey::find_program_address(
+            &[ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]],
+            &crate::ID,
+        );
+        require_keys_eq!(info.key(), address, ErrorCode::SegmentMismatch);
+        if info.data_len() < OrderBookAccount::LEN {
+            continue;
+        }
+
+        let loader = AccountLoader::<OrderBookAccount>::try_from(info)?;
+        let book = loader.load()?;
+        require!(
+            book.locked == 0 || now > book.locked_at + ORDER_BOOK_LOCK_TIMEOUT,
+            ErrorCode::OrderBookBusy
+        );
+        require!(
+            book.initialized == 0 || levels.versions[segment as usize] == book.version,
+            ErrorCode::StaleLevels
+        );
+    }
+    Ok(())
+}
+
+fn market_levels_callback_account(market_levels: &AccountLoader<MarketLevelsAccount>) -> CallbackAccount {
+    CallbackAccount {
+        pubkey: market_levels.key(),
+        is_writable: true,
+    }
+}
+
+// One encrypted order book segment of a market (~29 KB)
+#[account(zero_copy)]
+pub struct OrderBookAccount {
+    // MXE ciphertexts of every order field (order by order), then order_count
+    pub ciphertexts: [[u8; 32]; ORDER_BOOK_CIPHERTEXTS],
+    // Nonce the ciphertexts were encrypted under (u128, little-endian)
+    pub nonce: [u8; 16],
+    // Computation account that holds the lock, and its offset
+    pub pending_computation: Pubkey,
+    pub pending_offset: u64,
+    // When the lock was taken
+    pub locked_at: i64,
+    // Market and segment the book belongs to (set by init_order_book)
+    pub market_id: u64,
+    pub initialized: u8,
+    pub locked: u8,
+    pub segment: u8,
+    // Set when add_order reports no free slot; cleared by any other update
+    pub full: u8,
+    pub _padding: [u8; 4],
+    // Bumped by every stored update (see MarketLevelsAccount::versions)
+    pub version: u64,
+}
+
+impl OrderBookAccount {
+    pub const LEN: usize = 8 + std::mem::size_of::<OrderBookAccount>();
+
+    // Byte range of the ciphertexts, passed to computations by reference
+    pub const CIPHERTEXTS_OFFSET: u32 = 8;
+    pub const CIPHERTEXTS_LEN: u32 = (ORDER_BOOK_CIPHERTEXTS * 32) as u32;
+}
+
+// Each segment's best bid and ask for one market (~8 KB)
+#[account(zero_copy)]
+pub struct MarketLevelsAccount {
+    // MXE ciphertexts of every segment's levels (segment by segment)
+    pub ciphertexts: [[u8; 32]; MARKET_LEVELS_CIPHERTEXTS],
+    // Nonce the ciphertexts were encrypted under (u128, little-endian)
+    pub nonce: [u8; 16],
+    // Computation account that holds the lock, and its offset
+    pub pending_computation: Pubkey,
+    pub pending_offset: u64,
+    // When the lock was taken
+    pub locked_at: i64,
+    // Book version each segment's levels were collected at
+    pub versions: [u64; MAX_SEGMENTS as usize],
+    pub market_id: u64,
+    pub initialized: u8,
+    pub locked: u8,
+    pub _padding: [u8; 6],
+}
+
+impl MarketLevelsAccount {
+    pub const LEN: usize = 8 + std::mem::size_of::<MarketLevelsAccount>();
+
+    // Byte range of the ciphertexts, passed to computations by reference
+    pub const CIPHERTEXTS_OFFSET: u32 = 8;
+    pub const CIPHERTEXTS_LEN: u32 = (MARKET_LEVELS_CIPHERTEXTS * 32) as u32;
+}
+
+// Darkpool administration
+#[account]
+pub struct DarkpoolConfig {
+    pub authority: Pubkey,
+    pub bump: u8,
+}
 
-// Account structures (auto-generated by Arcium)
+impl DarkpoolConfig {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+// Trading pair with its own segmented encrypted order book
+#[account]
+pub struct Market {
+    pub market_id: u64,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    // Limit prices must be a multiple of tick_size
+    pub tick_size: u64,
+    // Amounts must be a multiple of lot_size and at least min_order_size
+    pub lot_size: u64,
+    pub min_order_size: u64,
+    // Order book segments created so far
+    pub segment_count: u8,
+    // Sequence number for the next add_order, shared by all segments
+    pub next_sequence: u64,
+    pub bump: u8,
+}
+
+impl Market {
+    pub const LEN: usize = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 1 + 8 + 1;
+}
+
+// Market maker allowed to request order book depth (exists only while allowed)
+#[account]
+pub struct DepthViewer {
+    pub viewer: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+impl DepthViewer {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+}
+
+// Errors
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The computation was aborted")]
+    AbortedComputation,
+    #[msg("Cluster not set")]
+    ClusterNotSet,
+    #[msg("Order book account has not been grown to full size")]
+    OrderBookNotAllocated,
+    #[msg("Order book has not been initialized")]
+    OrderBookNotInitialized,
+    #[msg("Order book is already initialized")]
+    OrderBookAlreadyInitialized,
+    #[msg("Another computation is updating the order book")]
+    OrderBookBusy,
+    #[msg("Computation no longer holds the order book lock")]
+    StaleComputation,
+    #[msg("Depth bands must have a non-zero tick and fit in the u64 price range")]
+    InvalidDepthParams,
+    #[msg("Order book belongs to a different market")]
+    MarketMismatch,
+    #[msg("Order book is a different segment")]
+    SegmentMismatch,
+    #[msg("Segments must be created in order, up to the maximum")]
+    InvalidSegment,
+    #[msg("Order book segment is full")]
+    SegmentFull,
+    #[msg("Cross-segment matching needs two different segments")]
+    InvalidSegmentPair,
+    #[msg("Market needs distinct mints, non-zero tick and lot sizes, and a minimum order of whole lots")]
+    InvalidMarketParams,
+    #[msg("Market levels record has not been initialized")]
+    LevelsNotInitialized,
+    #[msg("Another computation is updating the market levels")]
+    LevelsBusy,
+    #[msg("Every other segment of the market must be passed, in segment order")]
+    MissingSegment,
+    #[msg("A segment changed since its levels were collected")]
+    StaleLevels,
+    #[msg("Signer is not the darkpool program's upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("Batch auctions need a market with a single segment")]
+    SegmentedAuction,
+}
+
+// Events
+#[event]
+pub struct MarketCreatedEvent {
+    pub market_id: u64,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub tick_size: u64,
+    pub lot_size: u64,
+    pub min_order_size: u64,
+}
+
+#[event]
+pub struct OrderBookInitializedEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderAddedEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_offset: u64,
+    // False when the order broke the market's rules or the segment was full
+    pub accepted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrdersMatchedEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_offset: u64,
+    pub match_result: [u8; 32],
+    pub nonce: [u8; 16],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrdersBatchMatchedEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_offset: u64,
+    // MATCH_BATCH_SIZE results, MATCH_RESULT_FIELDS ciphertexts each
+    pub match_results: [[u8; 32]; MATCH_BATCH_SIZE * MATCH_RESULT_FIELDS],
+    pub nonce: [u8; 16],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SegmentsMatchedEvent {
+    pub market_id: u64,
+    pub segment_a: u8,
+    pub segment_b: u8,
+    pub computation_offset: u64,
+    // Order ids are segment * MAX_ORDERS + slot
+    pub match_result: [[u8; 32]; MATCH_RESULT_FIELDS],
+    pub nonce: [u8; 16],
+    // Segments holding the market's next crossing pair; MAX_SEGMENTS when
+    // the market doesn't cross
+    pub next_bid_segment: u8,
+    pub next_ask_segment: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LevelsCollectedEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_offset: u64,
+    // As in SegmentsMatchedEvent
+    pub next_bid_segment: u8,
+    pub next_ask_segment: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchAuctionClearedEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_offset: u64,
+    // Zero when nothing crossed
+    pub clearing_price: u64,
+    pub volume: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderCancelledEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_offset: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderbookDepthEvent {
+    pub market_id: u64,
+    pub segment: u8,
+    pub computation_account: Pubkey,
+    // Viewer x25519 key the depth is encrypted to
+    pub encryption_key: [u8; 32],
+    pub depth: [[u8; 32]; DEPTH_LEVELS],
+    pub nonce: [u8; 16],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepthViewerAddedEvent {
+    pub viewer: Pubkey,
+}
+
+#[event]
+pub struct DepthViewerRemovedEvent {
+    pub viewer: Pubkey,
+}
+
+// Account structures (macros from arcium_anchor add the Arcium checks)
+
+#[init_computation_definition_accounts("init_order_book", payer)]
+#[derive(Accounts)]
+pub struct InitOrderBookCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("add_order", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
 pub struct InitAddOrderCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
 
+#[init_computation_definition_accounts("match_orders", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
 pub struct InitMatchOrdersCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
 
+#[init_computation_definition_accounts("match_segments", payer)]
+#[derive(Accounts)]
+pub struct InitMatchSegmentsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("match_orders_batch", payer)]
+#[derive(Accounts)]
+pub struct InitMatchOrdersBatchCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("clear_batch_auction", payer)]
+#[derive(Accounts)]
+pub struct InitClearBatchAuctionCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("cancel_order", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
 pub struct InitCancelOrderCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
 
+#[init_computation_definition_accounts("get_orderbook_depth", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
 pub struct InitGetOrderbookDepthCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("init_market_levels", payer)]
+#[derive(Accounts)]
+pub struct InitMarketLevelsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
 
+#[init_computation_definition_accounts("collect_levels", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
+pub struct InitCollectLevelsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by the arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = DarkpoolConfig::LEN,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, DarkpoolConfig>,
+    // Only the program's upgrade authority can claim the config, so nobody
+    // can front-run the deployment and become its authority
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(viewer: Pubkey)]
+pub struct AddDepthViewer<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = authority)]
+    pub config: Account<'info, DarkpoolConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = DepthViewer::LEN,
+        seeds = [DEPTH_VIEWER_SEED, viewer.as_ref()],
+        bump
+    )]
+    pub depth_viewer: Account<'info, DepthViewer>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(viewer: Pubkey)]
+pub struct RemoveDepthViewer<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = authority)]
+    pub config: Account<'info, DarkpoolConfig>,
+    #[account(
+        mut,
+        seeds = [DEPTH_VIEWER_SEED, viewer.as_ref()],
+        bump = depth_viewer.bump,
+        close = authority
+    )]
+    pub depth_viewer: Account<'info, DepthViewer>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct CreateMarket<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = authority)]
+    pub config: Account<'info, DarkpoolConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = Market::LEN,
+        seeds = [MARKET_SEED, &market_id.to_le_bytes()],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_id: u64, segment: u8)]
+pub struct CreateOrderBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [MARKET_SEED, &market_id.to_le_bytes()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        payer = payer,
+        space = MAX_PERMITTED_DATA_INCREASE,
+        seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]],
+        bump
+    )]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_id: u64, segment: u8)]
+pub struct GrowOrderBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]],
+        bump,
+        realloc = (order_book.to_account_info().data_len() + MAX_PERMITTED_DATA_INCREASE)
+            .min(OrderBookAccount::LEN),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("init_order_book", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
+pub struct InitOrderBook<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_ORDER_BOOK)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(
+        mut,
+        seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]],
+        bump,
+        constraint = order_book.to_account_info().data_len() == OrderBookAccount::LEN
+            @ ErrorCode::OrderBookNotAllocated
+    )]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("init_order_book")]
+#[derive(Accounts)]
+pub struct InitOrderBookCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_ORDER_BOOK)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as a callback account by the queueing instruction; only the book
+    // locked by this computation accepts the result (see store_order_book)
+    #[account(mut)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+}
+
+#[queue_computation_accounts("init_market_levels", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64)]
+pub struct InitMarketLevels<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MARKET_LEVELS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(seeds = [MARKET_SEED, &market_id.to_le_bytes()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        payer = payer,
+        space = MarketLevelsAccount::LEN,
+        seeds = [MARKET_LEVELS_SEED, &market_id.to_le_bytes()],
+        bump
+    )]
+    pub market_levels: AccountLoader<'info, MarketLevelsAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("init_market_levels")]
+#[derive(Accounts)]
+pub struct InitMarketLevelsCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MARKET_LEVELS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as a callback account by the queueing instruction; only the
+    // record locked by this computation accepts the result (see store_market_levels)
+    #[account(mut)]
+    pub market_levels: AccountLoader<'info, MarketLevelsAccount>,
+}
+
+#[queue_computation_accounts("collect_levels", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
+pub struct CollectLevels<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_COLLECT_LEVELS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]], bump)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    #[account(mut, seeds = [MARKET_LEVELS_SEED, &market_id.to_le_bytes()], bump)]
+    pub market_levels: AccountLoader<'info, MarketLevelsAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("collect_levels")]
+#[derive(Accounts)]
+pub struct CollectLevelsCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_COLLECT_LEVELS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as callback accounts by the queueing instruction; only accounts
+    // locked by this computation accept the result (see store_order_book)
+    #[account(mut)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    #[account(mut)]
+    pub market_levels: AccountLoader<'info, MarketLevelsAccount>,
+}
+
+#[queue_computation_accounts("add_order", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
 pub struct AddOrder<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_ORDER)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(mut, seeds = [MARKET_SEED, &market_id.to_le_bytes()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]], bump)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
     pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
 }
 
+#[callback_accounts("add_order")]
 #[derive(Accounts)]
 pub struct AddOrderCallback<'info> {
-    pub cluster_account: AccountInfo<'info>,
-    pub computation_account: AccountInfo<'info>,
-    pub mxe_account: AccountInfo<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_ORDER)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as a callback account by the queueing instruction; only the book
+    // locked by this computation accepts the result (see store_order_book)
+    #[account(mut)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
 }
 
+#[queue_computation_accounts("match_orders", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
 pub struct MatchOrders<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_ORDERS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]], bump)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("match_segments", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment_a: u8, segment_b: u8)]
+pub struct MatchSegments<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_SEGMENTS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment_a]], bump)]
+    pub order_book_a: AccountLoader<'info, OrderBookAccount>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment_b]], bump)]
+    pub order_book_b: AccountLoader<'info, OrderBookAccount>,
+    #[account(seeds = [MARKET_SEED, &market_id.to_le_bytes()], bump = market.bump)]
+    pub market: Account<'info, Market>,
+    #[account(mut, seeds = [MARKET_LEVELS_SEED, &market_id.to_le_bytes()], bump)]
+    pub market_levels: AccountLoader<'info, MarketLevelsAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("match_orders_batch", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
+pub struct MatchOrdersBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_ORDERS_BATCH)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]], bump)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("clear_batch_auction", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
+pub struct ClearBatchAuction<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CLEAR_BATCH_AUCTION)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]], bump)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    #[account(seeds = [MARKET_SEED, &market_id.to_le_bytes()], bump = market.bump)]
+    pub market: Account<'info, Market>,
     pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
 }
 
+#[callback_accounts("match_orders")]
 #[derive(Accounts)]
 pub struct MatchOrdersCallback<'info> {
-    pub cluster_account: AccountInfo<'info>,
-    pub computation_account: AccountInfo<'info>,
-    pub mxe_account: AccountInfo<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_ORDERS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as a callback account by the queueing instruction; only the book
+    // locked by this computation accepts the result (see store_order_book)
+    #[account(mut)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+}
+
+#[callback_accounts("match_segments")]
+#[derive(Accounts)]
+pub struct MatchSegmentsCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_SEGMENTS)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as callback accounts by the queueing instruction; only accounts
+    // locked by this computation accept the result (see store_order_book)
+    #[account(mut)]
+    pub order_book_a: AccountLoader<'info, OrderBookAccount>,
+    #[account(mut)]
+    pub order_book_b: AccountLoader<'info, OrderBookAccount>,
+    #[account(mut)]
+    pub market_levels: AccountLoader<'info, MarketLevelsAccount>,
+}
+
+#[callback_accounts("match_orders_batch")]
+#[derive(Accounts)]
+pub struct MatchOrdersBatchCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_ORDERS_BATCH)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as a callback account by the queueing instruction; only the book
+    // locked by this computation accepts the result (see store_order_book)
+    #[account(mut)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
 }
 
+#[callback_accounts("clear_batch_auction")]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
+pub struct ClearBatchAuctionCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CLEAR_BATCH_AUCTION)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as a callback account by the queueing instruction; only the book
+    // locked by this computation accepts the result (see store_order_book)
+    #[account(mut)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+}
+
+#[queue_computation_accounts("cancel_order", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
 pub struct CancelOrder<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CANCEL_ORDER)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(mut, seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]], bump)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
     pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
 }
 
+#[callback_accounts("cancel_order")]
 #[derive(Accounts)]
 pub struct CancelOrderCallback<'info> {
-    pub cluster_account: AccountInfo<'info>,
-    pub computation_account: AccountInfo<'info>,
-    pub mxe_account: AccountInfo<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CANCEL_ORDER)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed as a callback account by the queueing instruction; only the book
+    // locked by this computation accepts the result (see store_order_book)
+    #[account(mut)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+}
+
+#[queue_computation_accounts("get_orderbook_depth", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, market_id: u64, segment: u8)]
+pub struct GetOrderbookDepth<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // Market maker the depth is requested for
+    pub viewer: Signer<'info>,
+    #[account(
+        seeds = [DEPTH_VIEWER_SEED, viewer.key().as_ref()],
+        bump = depth_viewer.bump
+    )]
+    pub depth_viewer: Account<'info, DepthViewer>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_GET_ORDERBOOK_DEPTH)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Box<Account<'info, FeePool>>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Box<Account<'info, ClockAccount>>,
+    #[account(seeds = [ORDER_BOOK_SEED, &market_id.to_le_bytes(), &[segment]], bump)]
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("get_orderbook_depth")]
+#[derive(Accounts)]
+pub struct GetOrderbookDepthCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_GET_ORDERBOOK_DEPTH)
+    )]
+    pub comp_def_account: Box<Account<'info, ComputationDefinitionAccount>>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    /// CHECK: computation_account, checked by the arcium program via constraints in the callback context.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet)
+    )]
+    pub cluster_account: Box<Account<'info, Cluster>>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    // Passed read-only by get_orderbook_depth, for the market id
+    pub order_book: AccountLoader<'info, OrderBookAccount>,
 }
@@ -0,0 +1,229 @@
+import * as anchor from '@coral-xyz/anchor';
+import {
+  RescueCipher,
+  awaitComputationFinalization,
+  deserializeLE,
+  getArciumEnv,
+  getClusterAccAddress,
+  getCompDefAccAddress,
+  getCompDefAccOffset,
+  getComputationAccAddress,
+  getExecutingPoolAccAddress,
+  getMXEAccAddress,
+  getMXEPublicKey,
+  getMempoolAccAddress,
+  uploadCircuit
+} from '@arcium-hq/client';
+import { x25519 } from '@noble/curves/ed25519';
+import { randomBytes } from 'crypto';
+import { readFileSync, writeFileSync } from 'fs';
+
+// Times every order book circuit on the `arcium test` localnet, from queueing
+// the computation until its callback lands. Run by measureCircuitCost.js in a
+// workspace built with the book size under test; not meant to be run directly.
+//
+//   CIRCUIT_TIMINGS_OUT   JSON file the timings are written to
+//   CIRCUIT_TIMINGS_RUNS  executions per circuit (default: 5)
+
+const MARKET_ID = 1;
+// Batch auctions only run on single-segment markets
+const AUCTION_MARKET_ID = 2;
+const COMP_DEFS = [
+  'init_order_book',
+  'add_order',
+  'match_orders',
+  'match_orders_batch',
+  'match_segments',
+  'init_market_levels',
+  'collect_levels',
+  'clear_batch_auction',
+  'cancel_order',
+  'get_orderbook_depth'
+];
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+const idl = JSON.parse(readFileSync('target/idl/darkpool.json', 'utf-8'));
+const program = new anchor.Program(idl, provider);
+const arciumEnv = getArciumEnv();
+const payer = provider.wallet.publicKey;
+
+function camelCase(name) {
+  return name.replace(/_([a-z])/g, (_, c) => c.toUpperCase());
+}
+
+function compDefAddress(name) {
+  return getCompDefAccAddress(
+    program.programId,
+    Buffer.from(getCompDefAccOffset(name)).readUInt32LE()
+  );
+}
+
+function randomOffset() {
+  return new anchor.BN(randomBytes(8), 'hex');
+}
+
+function randomNonce() {
+  const nonce = randomBytes(16);
+  return { nonce, value: new anchor.BN(deserializeLE(nonce).toString()) };
+}
+
+async function initCompDefs() {
+  for (const name of COMP_DEFS) {
+    const method = camelCase(name.startsWith('init_') ? `${name}_comp_def` : `init_${name}_comp_def`);
+    await program.methods[method]()
+      .accounts({
+        compDefAccount: compDefAddress(name),
+        payer,
+        mxeAccount: getMXEAccAddress(program.programId)
+      })
+      .rpc({ commitment: 'confirmed' });
+    await uploadCircuit(provider, name, program.programId, readFileSync(`build/${name}.arcis`), false);
+  }
+}
+
+async function mxePublicKey() {
+  for (let attempt = 0; attempt < 20; attempt++) {
+    const key = await getMXEPublicKey(provider, program.programId).catch(() => null);
+    if (key) {
+      return key;
+    }
+    await new Promise((r) => setTimeout(r, 1000));
+  }
+  throw new Error('MXE public key not set');
+}
+
+// Queues `name` and waits for its callback; returns the elapsed milliseconds
+async function run(name, method, args) {
+  const offset = randomOffset();
+  const start = performance.now();
+  await program.methods[method](offset, ...args)
+    .accountsPartial({
+      computationAccount: getComputationAccAddress(arciumEnv.arciumClusterOffset, offset),
+      clusterAccount: getClusterAccAddress(arciumEnv.arciumClusterOffset),
+      mxeAccount: getMXEAccAddress(program.programId),
+      mempoolAccount: getMempoolAccAddress(arciumEnv.arciumClusterOffset),
+      executingPool: getExecutingPoolAccAddress(arciumEnv.arciumClusterOffset),
+      compDefAccount: compDefAddress(name)
+    })
+    .rpc({ commitment: 'confirmed' });
+  await awaitComputationFinalization(provider, offset, program.programId, 'confirmed');
+  return performance.now() - start;
+}
+
+async function createMarket(marketId) {
+  await program.methods
+    .createMarket(
+      new anchor.BN(marketId),
+      anchor.web3.Keypair.generate().publicKey,
+      anchor.web3.Keypair.generate().publicKey,
+      new anchor.BN(1),
+      new anchor.BN(1),
+      new anchor.BN(1)
+    )
+    .rpc();
+}
+
+async function createSegment(marketId, segment) {
+  const market = new anchor.BN(marketId);
+  await program.methods.createOrderBook(market, segment).rpc();
+
+  // Grow until the account stops getting larger
+  const [address] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('order_book'), market.toArrayLike(Buffer, 'le', 8), Buffer.from([segment])],
+    program.programId
+  );
+  let size = 0;
+  for (;;) {
+    await program.methods.growOrderBook(market, segment).rpc({ commitment: 'confirmed' });
+    const grown = (await provider.connection.getAccountInfo(address, 'confirmed')).data.length;
+    if (grown === size) {
+      break;
+    }
+    size = grown;
+  }
+
+  const { value } = randomNonce();
+  await run('init_order_book', 'initOrderBook', [market, segment, value]);
+}
+
+async function setup() {
+  await initCompDefs();
+  await program.methods.initializeConfig().rpc();
+  await program.methods.addDepthViewer(payer).rpc();
+  await createMarket(MARKET_ID);
+  await run('init_market_levels', 'initMarketLevels', [new anchor.BN(MARKET_ID), randomNonce().value]);
+  await createSegment(MARKET_ID, 0);
+  await createSegment(MARKET_ID, 1);
+  await createMarket(AUCTION_MARKET_ID);
+  await createSegment(AUCTION_MARKET_ID, 0);
+}
+
+async function main() {
+  const runs = Number(process.env.CIRCUIT_TIMINGS_RUNS || 5);
+  const out = process.env.CIRCUIT_TIMINGS_OUT;
+  if (!out) {
+    throw new Error('CIRCUIT_TIMINGS_OUT is not set');
+  }
+
+  await setup();
+
+  const privateKey = x25519.utils.randomPrivateKey();
+  const publicKey = Array.from(x25519.getPublicKey(privateKey));
+  const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, await mxePublicKey()));
+  const userId = 42n;
+  const market = new anchor.BN(MARKET_ID);
+
+  const encryptOrder = (side) => {
+    const { nonce, value } = randomNonce();
+    // price, amount, side, order_type (limit), user_id, expires_at, GTC;
+    // bids and asks never cross, so the book fills up across runs
+    const price = side === 0 ? 900n : 1100n;
+    const fields = cipher.encrypt([price, 10n, BigInt(side), 1n, userId, 0n, 0n], nonce);
+    return [...fields.map((field) => Array.from(field)), publicKey, value];
+  };
+  const encryptUser = () => {
+    const { nonce, value } = randomNonce();
+    return [Array.from(cipher.encrypt([userId], nonce)[0]), publicKey, value];
+  };
+
+  const timings = {};
+  const record = (name, ms) => {
+    (timings[name] ||= []).push(ms);
+  };
+
+  for (let i = 0; i < runs; i++) {
+    console.log(`Run ${i + 1}/${runs}`);
+    record('add_order', await run('add_order', 'addOrder', [market, 0, ...encryptOrder(i % 2)]));
+    await run('add_order', 'addOrder', [market, 1, ...encryptOrder((i + 1) % 2)]);
+    record('cancel_order', await run('cancel_order', 'cancelOrder', [market, 0, new anchor.BN(0), ...encryptUser()]));
+    record('match_orders', await run('match_orders', 'matchOrders', [market, 0]));
+    record('match_orders_batch', await run('match_orders_batch', 'matchOrdersBatch', [market, 0]));
+    record(
+      'clear_batch_auction',
+      await run('clear_batch_auction', 'clearBatchAuction', [new anchor.BN(AUCTION_MARKET_ID), 0])
+    );
+    record(
+      'get_orderbook_depth',
+      await run('get_orderbook_depth', 'getOrderbookDepth', [
+        market,
+        0,
+        publicKey,
+        randomNonce().value,
+        new anchor.BN(1000),
+        new anchor.BN(10),
+        new anchor.BN(0)
+      ])
+    );
+    record('collect_levels', await run('collect_levels', 'collectLevels', [market, 0]));
+    await run('collect_levels', 'collectLevels', [market, 1]);
+    record('match_segments', await run('match_segments', 'matchSegments', [market, 0, 1]));
+  }
+
+  writeFileSync(out, JSON.stringify({ runs, timings }));
+}
+
+main().catch((error) => {
+  console.error(error);
+  process.exit(1);
+});
@@ -35,8 +35,14 @@ async function initComputationDefs() {
     // const program = new anchor.Program(idl, programId, provider);
 
     const compDefs = [
+      'init_order_book',
       'add_order',
       'match_orders',
+      'match_orders_batch',
+      'match_segments',
+      'init_market_levels',
+      'collect_levels',
+      'clear_batch_auction',
       'cancel_order',
       'get_orderbook_depth'
     ];
@@ -0,0 +1,150 @@
+import { execSync } from 'child_process';
+import { cpSync, existsSync, mkdtempSync, readFileSync, rmSync, symlinkSync, writeFileSync } from 'fs';
+import { tmpdir } from 'os';
+import { join, resolve } from 'path';
+
+// Time every order book circuit at several book sizes (MAX_ORDERS). Each size
+// is built in a scratch copy of the workspace and run on the `arcium test`
+// localnet (see scripts/circuitTimings.js), which reports the time from
+// queueing each computation until its callback lands.
+//
+//   node scripts/measureCircuitCost.js [--runs N] [--write-docs] [size ...]
+//
+// Defaults: sizes 25 50 100 200, 5 runs per circuit. --write-docs replaces the
+// timings table in docs/DEPLOYMENT.md. Needs the Arcium CLI, Docker and the
+// npm dependencies installed.
+
+const ROOT = resolve(new URL('..', import.meta.url).pathname);
+const CIRCUIT_FILE = 'encrypted-ixs/match_orders.rs';
+const PROGRAM_FILE = 'programs/darkpool/src/lib.rs';
+const DOCS_FILE = 'docs/DEPLOYMENT.md';
+const DOCS_START = '<!-- circuit-timings:start -->';
+const DOCS_END = '<!-- circuit-timings:end -->';
+const SKIP = new Set(['node_modules', 'target', 'build', '.git', '.anchor']);
+
+function parseArgs(argv) {
+  const options = { runs: 5, writeDocs: false, sizes: [] };
+  for (let i = 0; i < argv.length; i++) {
+    if (argv[i] === '--runs') {
+      options.runs = Number(argv[++i]);
+    } else if (argv[i] === '--write-docs') {
+      options.writeDocs = true;
+    } else {
+      options.sizes.push(Number(argv[i]));
+    }
+  }
+  if (options.sizes.length === 0) {
+    options.sizes = [25, 50, 100, 200];
+  }
+  if (!(options.runs > 0) || options.sizes.some((size) => !(size > 0))) {
+    throw new Error('Usage: measureCircuitCost.js [--runs N] [--write-docs] [size ...]');
+  }
+  return options;
+}
+
+function prepareWorkspace(workspace, size) {
+  cpSync(ROOT, workspace, {
+    recursive: true,
+    filter: (src) => !SKIP.has(src.split('/').pop())
+  });
+  symlinkSync(join(ROOT, 'node_modules'), join(workspace, 'node_modules'));
+
+  for (const file of [CIRCUIT_FILE, PROGRAM_FILE]) {
+    const path = join(workspace, file);
+    const source = readFileSync(path, 'utf-8');
+    writeFileSync(path, source.replace(/const MAX_ORDERS: usize = \d+;/, `const MAX_ORDERS: usize = ${size};`));
+  }
+
+  // `arcium test` starts the localnet and runs the timing script against it
+  const configPath = join(workspace, 'Arcium.toml');
+  const config = readFileSync(configPath, 'utf-8');
+  writeFileSync(configPath, config.replace(/^test = .*$/m, 'test = "node scripts/circuitTimings.js"'));
+}
+
+function measure(size, runs) {
+  const workspace = mkdtempSync(join(tmpdir(), `darkpool-circuits-${size}-`));
+  const out = join(workspace, 'timings.json');
+  try {
+    prepareWorkspace(workspace, size);
+    execSync('arcium build', { cwd: workspace, stdio: 'inherit' });
+    execSync('arcium test', {
+      cwd: workspace,
+      stdio: 'inherit',
+      env: { ...process.env, CIRCUIT_TIMINGS_OUT: out, CIRCUIT_TIMINGS_RUNS: String(runs) }
+    });
+    return JSON.parse(readFileSync(out, 'utf-8')).timings;
+  } finally {
+    rmSync(workspace, { recursive: true, force: true });
+  }
+}
+
+function percentile(values, p) {
+  const sorted = [...values].sort((a, b) => a - b);
+  return sorted[Math.min(sorted.length - 1, Math.ceil((p / 100) * sorted.length) - 1)];
+}
+
+function formatTable(results, sizes) {
+  const circuits = [...new Set(Object.values(results).flatMap((r) => Object.keys(r)))].sort();
+  const lines = [
+    `| Circuit | ${sizes.map((size) => `n = ${size}`).join(' | ')} |`,
+    `|---------|${sizes.map(() => '------').join('|')}|`
+  ];
+  for (const circuit of circuits) {
+    const cells = sizes.map((size) => {
+      const times = results[size][circuit];
+      if (!times) {
+        return '-';
+      }
+      const median = (percentile(times, 50) / 1000).toFixed(1);
+      const p95 = (percentile(times, 95) / 1000).toFixed(1);
+      return `${median} s / ${p95} s`;
+    });
+    lines.push(`| \`${circuit}\` | ${cells.join(' | ')} |`);
+  }
+  return lines.join('\n');
+}
+
+function writeDocs(table, runs) {
+  const path = join(ROOT, DOCS_FILE);
+  const docs = readFileSync(path, 'utf-8');
+  const start = docs.indexOf(DOCS_START);
+  const end = docs.indexOf(DOCS_END);
+  if (start === -1 || end < start) {
+    throw new Error(`Timings markers not found in ${DOCS_FILE}`);
+  }
+
+  const date = new Date().toISOString().slice(0, 10);
+  const section = [
+    DOCS_START,
+    `Measured ${date} on the \`arcium test\` localnet, ${runs} runs per circuit`,
+    '(median / p95, queue to callback):',
+    '',
+    table,
+    DOCS_END
+  ].join('\n');
+  writeFileSync(path, docs.slice(0, start) + section + docs.slice(end + DOCS_END.length));
+}
+
+function main() {
+  const { runs, writeDocs: updateDocs, sizes } = parseArgs(process.argv.slice(2));
+  if (!existsSync(join(ROOT, 'node_modules'))) {
+    throw new Error('Install the npm dependencies first');
+  }
+
+  const results = {};
+  for (const size of sizes) {
+    console.log(`Timing circuits with MAX_ORDERS = ${size}...`);
+    results[size] = measure(size, runs);
+  }
+
+  const table = formatTable(results, sizes);
+  console.log('');
+  console.log(table);
+
+  if (updateDocs) {
+    writeDocs(table, runs);
+    console.log(`\nUpdated ${DOCS_FILE}`);
+  }
+}
+
+main();
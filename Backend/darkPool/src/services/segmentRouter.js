@@ -0,0 +1,137 @@
+import { PublicKey } from '@solana/web3.js';
+import config from '../config/index.js';
+import logger from '../utils/logger.js';
+import { getConnection } from '../utils/solana.js';
+
+// Account layouts; must match `Market` and `OrderBookAccount` in
+// programs/darkpool/src/lib.rs
+const MARKET_SEGMENT_COUNT_OFFSET = 8 + 8 + 32 + 32 + 8 + 8 + 8;
+
+const ORDER_BOOK_CIPHERTEXTS = 100 * 9 + 1;
+const ORDER_BOOK_FLAGS_OFFSET = 8 + ORDER_BOOK_CIPHERTEXTS * 32 + 16 + 32 + 8 + 8 + 8;
+const INITIALIZED_OFFSET = ORDER_BOOK_FLAGS_OFFSET;
+const LOCKED_OFFSET = ORDER_BOOK_FLAGS_OFFSET + 1;
+const FULL_OFFSET = ORDER_BOOK_FLAGS_OFFSET + 3;
+const LOCKED_AT_OFFSET = ORDER_BOOK_FLAGS_OFFSET - 16;
+
+// Must match ORDER_BOOK_LOCK_TIMEOUT in the program
+const LOCK_TIMEOUT_SECONDS = 600;
+
+function marketIdBytes(marketId) {
+  const bytes = Buffer.alloc(8);
+  bytes.writeBigUInt64LE(BigInt(marketId));
+  return bytes;
+}
+
+// Picks the order book segment an add_order should go to: the lowest
+// initialized segment that isn't full and isn't locked by a pending
+// computation. Segments are flagged full only once add_order reports no free
+// slot, so a chosen segment can still drop the order; check `accepted` on
+// OrderAddedEvent and route again if needed.
+class SegmentRouter {
+  constructor() {
+    this.programId = null;
+  }
+
+  getProgramId() {
+    if (!this.programId) {
+      this.programId = new PublicKey(config.arcium.mxeProgramId);
+    }
+    return this.programId;
+  }
+
+  getMarketAddress(marketId) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('market'), marketIdBytes(marketId)],
+      this.getProgramId()
+    )[0];
+  }
+
+  getSegmentAddress(marketId, segment) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('order_book'), marketIdBytes(marketId), Buffer.from([segment])],
+      this.getProgramId()
+    )[0];
+  }
+
+  async getSegmentCount(marketId) {
+    const connection = getConnection();
+    const market = await connection.getAccountInfo(this.getMarketAddress(marketId));
+    if (!market) {
+      throw new Error(`Market ${marketId} not found`);
+    }
+    return market.data[MARKET_SEGMENT_COUNT_OFFSET];
+  }
+
+  async getSegments(marketId) {
+    const connection = getConnection();
+    const count = await this.getSegmentCount(marketId);
+    const addresses = [];
+    for (let segment = 0; segment < count; segment++) {
+      addresses.push(this.getSegmentAddress(marketId, segment));
+    }
+
+    const accounts = await connection.getMultipleAccountsInfo(addresses);
+    const now = Math.floor(Date.now() / 1000);
+
+    return accounts.map((account, segment) => {
+      if (!account || account.data.length <= ORDER_BOOK_FLAGS_OFFSET) {
+        return { segment, address: addresses[segment], available: false };
+      }
+
+      const data = account.data;
+      const lockedAt = Number(data.readBigInt64LE(LOCKED_AT_OFFSET));
+      const locked = data[LOCKED_OFFSET] === 1 && now <= lockedAt + LOCK_TIMEOUT_SECONDS;
+      const initialized = data[INITIALIZED_OFFSET] === 1;
+      const full = data[FULL_OFFSET] === 1;
+
+      return {
+        segment,
+        address: addresses[segment],
+        initialized,
+        locked,
+        full,
+        available: initialized && !locked && !full
+      };
+    });
+  }
+
+  // Remaining accounts for match_segments: every other segment of the
+  // market, in segment order
+  async getOtherSegmentAccounts(marketId, segmentA, segmentB) {
+    const count = await this.getSegmentCount(marketId);
+    const accounts = [];
+    for (let segment = 0; segment < count; segment++) {
+      if (segment !== segmentA && segment !== segmentB) {
+        accounts.push({
+          pubkey: this.getSegmentAddress(marketId, segment),
+          isWritable: false,
+          isSigner: false
+        });
+      }
+    }
+    return accounts;
+  }
+
+  // Returns the segment index to route to, or null when every segment is
+  // full or busy (create another segment or retry later)
+  async route(marketId) {
+    const segments = await this.getSegments(marketId);
+    const target = segments.find((s) => s.available);
+
+    if (!target) {
+      logger.warn('No order book segment available', {
+        marketId,
+        segments: segments.length,
+        full: segments.filter((s) => s.full).length,
+        locked: segments.filter((s) => s.locked).length
+      });
+      return null;
+    }
+
+    logger.debug('Routing order to segment', { marketId, segment: target.segment });
+    return target.segment;
+  }
+}
+
+export default new SegmentRouter();
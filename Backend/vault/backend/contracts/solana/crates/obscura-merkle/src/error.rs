@@ -0,0 +1,45 @@
+//! Library errors
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MerkleError {
+    /// Depth outside 1..=MAX_DEPTH
+    InvalidDepth,
+
+    /// Proof length does not match the tree depth
+    ProofLengthMismatch,
+
+    /// Leaf index does not fit in the tree
+    IndexOutOfRange,
+
+    /// No free leaf positions left
+    TreeFull,
+
+    /// Multiproof indices are empty, unsorted or duplicated
+    InvalidIndices,
+
+    /// Multiproof has too few or too many siblings
+    InvalidMultiproof,
+
+    /// Input is not a valid field element for the hasher
+    InvalidInput,
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            MerkleError::InvalidDepth => "invalid tree depth: must be between 1 and 32",
+            MerkleError::ProofLengthMismatch => "proof length does not match the tree depth",
+            MerkleError::IndexOutOfRange => "leaf index out of range",
+            MerkleError::TreeFull => "tree is full",
+            MerkleError::InvalidIndices => "multiproof indices must be non-empty, sorted and unique",
+            MerkleError::InvalidMultiproof => "multiproof sibling count does not match its indices",
+            MerkleError::InvalidInput => "input is not a valid field element",
+        };
+        f.write_str(msg)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleError {}
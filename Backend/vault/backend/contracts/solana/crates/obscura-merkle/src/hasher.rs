@@ -0,0 +1,119 @@
+//! Hash backends
+
+use crate::{Hash, MerkleError, EMPTY_LEAF, MAX_DEPTH};
+
+/// Node hashing for a tree
+///
+/// Leaves and internal nodes must be hashed with distinct domains so that
+/// an internal node can never be presented as a leaf.
+pub trait Hasher {
+    /// Hash a leaf value into a leaf node
+    fn hash_leaf(value: &Hash) -> Result<Hash, MerkleError>;
+
+    /// Hash a leaf value bound to a deployment domain separator
+    fn hash_domain_leaf(domain: &Hash, value: &Hash) -> Result<Hash, MerkleError>;
+
+    /// Hash two child nodes into their parent
+    fn hash_nodes(left: &Hash, right: &Hash) -> Result<Hash, MerkleError>;
+}
+
+/// Roots of empty subtrees: `zeros[0] = EMPTY_LEAF`, `zeros[i + 1] = H(zeros[i], zeros[i])`
+///
+/// Entries above `depth` are left as `EMPTY_LEAF`.
+pub fn zero_hashes<H: Hasher>(depth: u8) -> Result<[Hash; MAX_DEPTH + 1], MerkleError> {
+    if depth as usize > MAX_DEPTH {
+        return Err(MerkleError::InvalidDepth);
+    }
+    let mut zeros = [EMPTY_LEAF; MAX_DEPTH + 1];
+    for level in 0..depth as usize {
+        zeros[level + 1] = H::hash_nodes(&zeros[level], &zeros[level])?;
+    }
+    Ok(zeros)
+}
+
+/// Keccak-256 with a 0x00 leaf prefix and 0x01 node prefix
+///
+/// Matches the EVM MerkleVerifier and the sip-settlement program. Domain
+/// leaves are keccak256(0x00 || domain || leaf).
+#[cfg(any(feature = "keccak", feature = "solana"))]
+pub struct Keccak;
+
+#[cfg(any(feature = "keccak", feature = "solana"))]
+impl Keccak {
+    pub(crate) fn hashv(parts: &[&[u8]]) -> Hash {
+        #[cfg(feature = "solana")]
+        {
+            solana_program::keccak::hashv(parts).to_bytes()
+        }
+        #[cfg(not(feature = "solana"))]
+        {
+            use sha3::{Digest, Keccak256};
+            let mut hasher = Keccak256::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().into()
+        }
+    }
+}
+
+#[cfg(any(feature = "keccak", feature = "solana"))]
+impl Hasher for Keccak {
+    fn hash_leaf(value: &Hash) -> Result<Hash, MerkleError> {
+        Ok(Self::hashv(&[&[0x00], value]))
+    }
+
+    fn hash_domain_leaf(domain: &Hash, value: &Hash) -> Result<Hash, MerkleError> {
+        Ok(Self::hashv(&[&[0x00], domain, value]))
+    }
+
+    fn hash_nodes(left: &Hash, right: &Hash) -> Result<Hash, MerkleError> {
+        Ok(Self::hashv(&[&[0x01], left, right]))
+    }
+}
+
+/// Poseidon over BN254 (x^5, circom parameters, big-endian)
+///
+/// Leaves hash with one input and nodes with two; the different widths use
+/// different round constants, which separates the two domains. Domain leaves
+/// hash the separator's two halves and the value (three inputs). Leaf values
+/// must be canonical field elements.
+#[cfg(any(feature = "poseidon", feature = "solana"))]
+pub struct Poseidon;
+
+#[cfg(any(feature = "poseidon", feature = "solana"))]
+impl Poseidon {
+    fn hashv(inputs: &[&[u8]]) -> Result<Hash, MerkleError> {
+        #[cfg(feature = "solana")]
+        {
+            use solana_program::poseidon::{hashv, Endianness, Parameters};
+            hashv(Parameters::Bn254X5, Endianness::BigEndian, inputs)
+                .map(|hash| hash.to_bytes())
+                .map_err(|_| MerkleError::InvalidInput)
+        }
+        #[cfg(not(feature = "solana"))]
+        {
+            use ark_bn254::Fr;
+            use light_poseidon::{Poseidon as PoseidonHasher, PoseidonBytesHasher};
+            PoseidonHasher::<Fr>::new_circom(inputs.len())
+                .and_then(|mut hasher| hasher.hash_bytes_be(inputs))
+                .map_err(|_| MerkleError::InvalidInput)
+        }
+    }
+}
+
+#[cfg(any(feature = "poseidon", feature = "solana"))]
+impl Hasher for Poseidon {
+    fn hash_leaf(value: &Hash) -> Result<Hash, MerkleError> {
+        Self::hashv(&[value])
+    }
+
+    fn hash_domain_leaf(domain: &Hash, value: &Hash) -> Result<Hash, MerkleError> {
+        let [high, low] = crate::domain::domain_field_elements(domain);
+        Self::hashv(&[&high, &low, value])
+    }
+
+    fn hash_nodes(left: &Hash, right: &Hash) -> Result<Hash, MerkleError> {
+        Self::hashv(&[left, right])
+    }
+}
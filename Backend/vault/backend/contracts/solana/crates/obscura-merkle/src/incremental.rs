@@ -0,0 +1,107 @@
+//! Append-only tree with O(depth) storage
+//!
+//! Keeps only the rightmost filled node of each level (the frontier), so
+//! it can live in a fixed-size account. Produces the same roots as
+//! `MerkleTree` for the same leaves.
+
+use core::marker::PhantomData;
+
+use crate::hasher::zero_hashes;
+use crate::{capacity, is_valid_depth, Hash, Hasher, MerkleError, MAX_DEPTH};
+
+pub struct IncrementalTree<H: Hasher> {
+    depth: u8,
+    next_index: u64,
+    frontier: [Hash; MAX_DEPTH],
+    root: Hash,
+    zeros: [Hash; MAX_DEPTH + 1],
+    _hasher: PhantomData<H>,
+}
+
+impl<H: Hasher> IncrementalTree<H> {
+    /// Create an empty tree
+    pub fn new(depth: u8) -> Result<Self, MerkleError> {
+        if !is_valid_depth(depth) {
+            return Err(MerkleError::InvalidDepth);
+        }
+        let zeros = zero_hashes::<H>(depth)?;
+        Ok(Self {
+            depth,
+            next_index: 0,
+            frontier: zeros[..MAX_DEPTH].try_into().expect("MAX_DEPTH entries"),
+            root: zeros[depth as usize],
+            zeros,
+            _hasher: PhantomData,
+        })
+    }
+
+    /// Restore a tree from a stored frontier
+    pub fn from_frontier(
+        depth: u8,
+        next_index: u64,
+        frontier: [Hash; MAX_DEPTH],
+        root: Hash,
+    ) -> Result<Self, MerkleError> {
+        let mut tree = Self::new(depth)?;
+        if next_index > capacity(depth) {
+            return Err(MerkleError::IndexOutOfRange);
+        }
+        tree.next_index = next_index;
+        tree.frontier = frontier;
+        tree.root = root;
+        Ok(tree)
+    }
+
+    /// Hash a leaf value and append it, returning its index
+    pub fn append(&mut self, value: &Hash) -> Result<u64, MerkleError> {
+        let node = H::hash_leaf(value)?;
+        self.append_node(node)
+    }
+
+    /// Append an already-hashed leaf node, returning its index
+    pub fn append_node(&mut self, node: Hash) -> Result<u64, MerkleError> {
+        let leaf_index = self.next_index;
+        if leaf_index >= capacity(self.depth) {
+            return Err(MerkleError::TreeFull);
+        }
+
+        let mut index = leaf_index;
+        let mut current = node;
+        for level in 0..self.depth as usize {
+            current = if index & 1 == 0 {
+                // Left child: remember it, pair with an empty subtree
+                self.frontier[level] = current;
+                H::hash_nodes(&current, &self.zeros[level])?
+            } else {
+                H::hash_nodes(&self.frontier[level], &current)?
+            };
+            index >>= 1;
+        }
+
+        self.root = current;
+        self.next_index += 1;
+        Ok(leaf_index)
+    }
+
+    pub fn root(&self) -> Hash {
+        self.root
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Number of appended leaves
+    pub fn len(&self) -> u64 {
+        self.next_index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next_index == 0
+    }
+
+    /// Rightmost left-child node of each level
+    pub fn frontier(&self) -> &[Hash; MAX_DEPTH] {
+        &self.frontier
+    }
+}
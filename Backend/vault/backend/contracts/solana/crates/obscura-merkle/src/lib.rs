@@ -0,0 +1,68 @@
+//! Obscura Merkle tree library
+//!
+//! Fixed-depth binary Merkle trees shared by the on-chain programs and
+//! off-chain tooling:
+//! - Tree building and proof generation (`alloc`)
+//! - Multiproofs for several leaves at once (`alloc`)
+//! - Incremental append with O(depth) storage
+//! - Proof verification (no allocation, usable on-chain)
+//!
+//! Unfilled positions hold `EMPTY_LEAF`, so a tree of depth `d` always has
+//! `2^d` leaves and every proof has exactly `d` siblings.
+//!
+//! Hash backends:
+//! - `Keccak` — keccak256(0x00 || leaf), keccak256(0x01 || left || right)
+//! - `Poseidon` — Poseidon over BN254 (circom parameters, big-endian)
+//!
+//! With the `solana` feature both backends use the Solana syscalls.
+//!
+//! `domain` defines the versioned separator that binds leaves and
+//! commitments to one cluster and program.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(any(feature = "keccak", feature = "solana"))]
+pub mod domain;
+pub mod error;
+pub mod hasher;
+pub mod incremental;
+pub mod verify;
+
+#[cfg(feature = "alloc")]
+pub mod multiproof;
+#[cfg(feature = "alloc")]
+pub mod tree;
+
+#[cfg(any(feature = "keccak", feature = "solana"))]
+pub use domain::{domain_field_elements, domain_separator, DOMAIN_TAG, DOMAIN_VERSION};
+pub use error::MerkleError;
+pub use hasher::*;
+pub use incremental::IncrementalTree;
+pub use verify::{compute_root, verify_proof};
+
+#[cfg(feature = "alloc")]
+pub use multiproof::{verify_multiproof, MultiProof};
+#[cfg(feature = "alloc")]
+pub use tree::{MerkleProof, MerkleTree};
+
+/// 32-byte tree node
+pub type Hash = [u8; 32];
+
+/// Maximum tree depth
+pub const MAX_DEPTH: usize = 32;
+
+/// Value of an unfilled leaf position (not hashed)
+pub const EMPTY_LEAF: Hash = [0u8; 32];
+
+/// Check that a depth is usable (1..=MAX_DEPTH)
+pub fn is_valid_depth(depth: u8) -> bool {
+    depth >= 1 && depth as usize <= MAX_DEPTH
+}
+
+/// Number of leaves in a tree of the given depth
+pub fn capacity(depth: u8) -> u64 {
+    1u64 << depth
+}
@@ -0,0 +1,94 @@
+//! Multiproofs: one proof for several leaves
+//!
+//! Siblings are listed level by level, left to right, and only for nodes
+//! that cannot be computed from the proven leaves themselves.
+
+use alloc::vec::Vec;
+
+use crate::{capacity, is_valid_depth, Hash, Hasher, MerkleError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiProof {
+    /// Proven leaf indices (sorted, unique)
+    pub leaf_indices: Vec<u64>,
+    /// Sibling hashes in the order they are consumed
+    pub siblings: Vec<Hash>,
+}
+
+impl MultiProof {
+    pub(crate) fn check_indices(leaf_indices: &[u64], depth: u8) -> Result<(), MerkleError> {
+        if !is_valid_depth(depth) {
+            return Err(MerkleError::InvalidDepth);
+        }
+        if leaf_indices.is_empty() || leaf_indices.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(MerkleError::InvalidIndices);
+        }
+        if leaf_indices[leaf_indices.len() - 1] >= capacity(depth) {
+            return Err(MerkleError::IndexOutOfRange);
+        }
+        Ok(())
+    }
+
+    /// Verify against a root; `leaf_nodes[i]` is the node at `leaf_indices[i]`
+    pub fn verify<H: Hasher>(
+        &self,
+        leaf_nodes: &[Hash],
+        depth: u8,
+        root: &Hash,
+    ) -> Result<bool, MerkleError> {
+        verify_multiproof::<H>(&self.leaf_indices, leaf_nodes, &self.siblings, depth, root)
+    }
+}
+
+/// Verify a multiproof against a root of a tree with fixed depth
+pub fn verify_multiproof<H: Hasher>(
+    leaf_indices: &[u64],
+    leaf_nodes: &[Hash],
+    siblings: &[Hash],
+    depth: u8,
+    root: &Hash,
+) -> Result<bool, MerkleError> {
+    MultiProof::check_indices(leaf_indices, depth)?;
+    if leaf_nodes.len() != leaf_indices.len() {
+        return Err(MerkleError::InvalidIndices);
+    }
+
+    let mut known: Vec<(u64, Hash)> = leaf_indices
+        .iter()
+        .copied()
+        .zip(leaf_nodes.iter().copied())
+        .collect();
+    let mut siblings = siblings.iter();
+
+    for _ in 0..depth {
+        let mut parents = Vec::with_capacity(known.len());
+        let mut i = 0;
+        while i < known.len() {
+            let (index, node) = known[i];
+            let parent = match known.get(i + 1) {
+                Some((next, right)) if index & 1 == 0 && *next == index | 1 => {
+                    i += 2;
+                    H::hash_nodes(&node, right)?
+                }
+                _ => {
+                    i += 1;
+                    let sibling = siblings.next().ok_or(MerkleError::InvalidMultiproof)?;
+                    if index & 1 == 1 {
+                        H::hash_nodes(sibling, &node)?
+                    } else {
+                        H::hash_nodes(&node, sibling)?
+                    }
+                }
+            };
+            parents.push((index >> 1, parent));
+        }
+        known = parents;
+    }
+
+    // Every sibling must be consumed
+    if siblings.next().is_some() {
+        return Err(MerkleError::InvalidMultiproof);
+    }
+
+    Ok(known[0].1 == *root)
+}
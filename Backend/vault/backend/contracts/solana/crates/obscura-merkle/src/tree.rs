@@ -0,0 +1,198 @@
+//! Full tree with proof generation
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::hasher::zero_hashes;
+use crate::multiproof::MultiProof;
+use crate::verify::verify_proof;
+use crate::{capacity, is_valid_depth, Hash, Hasher, MerkleError, MAX_DEPTH};
+
+/// Fixed-depth Merkle tree
+///
+/// Only the filled prefix of each level is stored; everything to the right
+/// is an empty subtree.
+pub struct MerkleTree<H: Hasher> {
+    depth: u8,
+    /// Filled nodes per level (leaf nodes first, root last)
+    levels: Vec<Vec<Hash>>,
+    zeros: [Hash; MAX_DEPTH + 1],
+    _hasher: PhantomData<H>,
+}
+
+/// Proof for a single leaf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Leaf index in the tree
+    pub leaf_index: u64,
+    /// Sibling hashes from leaf to root
+    pub siblings: Vec<Hash>,
+}
+
+impl MerkleProof {
+    /// Verify against a root; the tree depth is the proof length
+    pub fn verify<H: Hasher>(&self, leaf_node: &Hash, root: &Hash) -> Result<bool, MerkleError> {
+        verify_proof::<H>(
+            leaf_node,
+            &self.siblings,
+            self.leaf_index,
+            self.siblings.len() as u8,
+            root,
+        )
+    }
+}
+
+impl<H: Hasher> MerkleTree<H> {
+    /// Create an empty tree
+    pub fn new(depth: u8) -> Result<Self, MerkleError> {
+        if !is_valid_depth(depth) {
+            return Err(MerkleError::InvalidDepth);
+        }
+        Ok(Self {
+            depth,
+            levels: vec![Vec::new(); depth as usize + 1],
+            zeros: zero_hashes::<H>(depth)?,
+            _hasher: PhantomData,
+        })
+    }
+
+    /// Build a tree from leaf values (hashed with `H::hash_leaf`)
+    pub fn from_leaves(depth: u8, values: &[Hash]) -> Result<Self, MerkleError> {
+        let nodes = values
+            .iter()
+            .map(H::hash_leaf)
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_leaf_nodes(depth, nodes)
+    }
+
+    /// Build a tree from already-hashed leaf nodes
+    pub fn from_leaf_nodes(depth: u8, nodes: Vec<Hash>) -> Result<Self, MerkleError> {
+        let mut tree = Self::new(depth)?;
+        if nodes.len() as u64 > capacity(depth) {
+            return Err(MerkleError::TreeFull);
+        }
+
+        tree.levels[0] = nodes;
+        for level in 0..depth as usize {
+            let children = &tree.levels[level];
+            let mut parents = Vec::with_capacity(children.len().div_ceil(2));
+            for pair in children.chunks(2) {
+                let right = pair.get(1).unwrap_or(&tree.zeros[level]);
+                parents.push(H::hash_nodes(&pair[0], right)?);
+            }
+            tree.levels[level + 1] = parents;
+        }
+        Ok(tree)
+    }
+
+    /// Hash a leaf value and append it, returning its index
+    pub fn append(&mut self, value: &Hash) -> Result<u64, MerkleError> {
+        let node = H::hash_leaf(value)?;
+        self.append_node(node)
+    }
+
+    /// Append an already-hashed leaf node, returning its index
+    pub fn append_node(&mut self, node: Hash) -> Result<u64, MerkleError> {
+        let leaf_index = self.len();
+        if leaf_index >= capacity(self.depth) {
+            return Err(MerkleError::TreeFull);
+        }
+        self.levels[0].push(node);
+
+        // Recompute the path to the root
+        let mut index = leaf_index;
+        for level in 0..self.depth as usize {
+            let left = self.node(level, index & !1);
+            let right = self.node(level, index | 1);
+            let parent = H::hash_nodes(&left, &right)?;
+
+            index >>= 1;
+            let parents = &mut self.levels[level + 1];
+            if (index as usize) < parents.len() {
+                parents[index as usize] = parent;
+            } else {
+                parents.push(parent);
+            }
+        }
+        Ok(leaf_index)
+    }
+
+    pub fn root(&self) -> Hash {
+        self.node(self.depth as usize, 0)
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Number of filled leaves
+    pub fn len(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Leaf node at an index (`EMPTY_LEAF` for unfilled positions)
+    pub fn leaf_node(&self, index: u64) -> Result<Hash, MerkleError> {
+        if index >= capacity(self.depth) {
+            return Err(MerkleError::IndexOutOfRange);
+        }
+        Ok(self.node(0, index))
+    }
+
+    /// Proof for the leaf at an index
+    pub fn proof(&self, leaf_index: u64) -> Result<MerkleProof, MerkleError> {
+        if leaf_index >= capacity(self.depth) {
+            return Err(MerkleError::IndexOutOfRange);
+        }
+
+        let mut siblings = Vec::with_capacity(self.depth as usize);
+        let mut index = leaf_index;
+        for level in 0..self.depth as usize {
+            siblings.push(self.node(level, index ^ 1));
+            index >>= 1;
+        }
+        Ok(MerkleProof { leaf_index, siblings })
+    }
+
+    /// Combined proof for several leaves
+    ///
+    /// `leaf_indices` must be sorted and unique.
+    pub fn multiproof(&self, leaf_indices: &[u64]) -> Result<MultiProof, MerkleError> {
+        MultiProof::check_indices(leaf_indices, self.depth)?;
+
+        let mut siblings = Vec::new();
+        let mut known = leaf_indices.to_vec();
+        for level in 0..self.depth as usize {
+            let mut parents = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let index = known[i];
+                if index & 1 == 0 && known.get(i + 1) == Some(&(index | 1)) {
+                    // Both children known
+                    i += 2;
+                } else {
+                    siblings.push(self.node(level, index ^ 1));
+                    i += 1;
+                }
+                parents.push(index >> 1);
+            }
+            known = parents;
+        }
+
+        Ok(MultiProof {
+            leaf_indices: leaf_indices.to_vec(),
+            siblings,
+        })
+    }
+
+    fn node(&self, level: usize, index: u64) -> Hash {
+        self.levels[level]
+            .get(index as usize)
+            .copied()
+            .unwrap_or(self.zeros[level])
+    }
+}
@@ -0,0 +1,53 @@
+//! Single-leaf proof verification (no allocation)
+
+use crate::{capacity, is_valid_depth, Hash, Hasher, MerkleError};
+
+/// Compute the root reached from a leaf node and its siblings
+///
+/// Bit `i` of `index` selects whether the current node is the right child
+/// at level `i`. Does not check the proof length; use `verify_proof` for that.
+pub fn compute_root<H: Hasher>(
+    leaf_node: &Hash,
+    proof: &[Hash],
+    mut index: u64,
+) -> Result<Hash, MerkleError> {
+    let mut computed = *leaf_node;
+
+    for sibling in proof {
+        computed = if index & 1 == 1 {
+            // Current is right child
+            H::hash_nodes(sibling, &computed)?
+        } else {
+            // Current is left child
+            H::hash_nodes(&computed, sibling)?
+        };
+        index >>= 1;
+    }
+
+    Ok(computed)
+}
+
+/// Verify a proof against a root of a tree with fixed depth
+///
+/// The proof must have exactly `depth` siblings and `index < 2^depth`, so a
+/// leaf verifies at exactly one position and internal nodes cannot be
+/// presented as leaves.
+pub fn verify_proof<H: Hasher>(
+    leaf_node: &Hash,
+    proof: &[Hash],
+    index: u64,
+    depth: u8,
+    root: &Hash,
+) -> Result<bool, MerkleError> {
+    if !is_valid_depth(depth) {
+        return Err(MerkleError::InvalidDepth);
+    }
+    if proof.len() != depth as usize {
+        return Err(MerkleError::ProofLengthMismatch);
+    }
+    if index >= capacity(depth) {
+        return Err(MerkleError::IndexOutOfRange);
+    }
+
+    Ok(compute_root::<H>(leaf_node, proof, index)? == *root)
+}
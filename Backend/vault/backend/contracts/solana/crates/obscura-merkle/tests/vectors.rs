@@ -0,0 +1,247 @@
+//! Cross-implementation test vectors
+//!
+//! `tests/vectors/*.json` is shared with the TypeScript tree in
+//! `packages/crypto` (tests/merkle.vectors.test.ts); both must reproduce it.
+
+use obscura_merkle::hasher::zero_hashes;
+use obscura_merkle::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Vectors {
+    zero_hashes: Vec<String>,
+    trees: Vec<TreeVector>,
+}
+
+#[derive(Deserialize)]
+struct TreeVector {
+    depth: u8,
+    leaves: Vec<String>,
+    leaf_nodes: Vec<String>,
+    root: String,
+    proofs: Vec<ProofVector>,
+    multiproof: MultiProofVector,
+}
+
+#[derive(Deserialize)]
+struct ProofVector {
+    index: u64,
+    siblings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MultiProofVector {
+    indices: Vec<u64>,
+    siblings: Vec<String>,
+}
+
+fn hash(value: &str) -> Hash {
+    hex::decode(value).unwrap().try_into().unwrap()
+}
+
+fn hashes(values: &[String]) -> Vec<Hash> {
+    values.iter().map(|v| hash(v)).collect()
+}
+
+fn check_vectors<H: Hasher>(json: &str) {
+    let vectors: Vectors = serde_json::from_str(json).unwrap();
+
+    let zeros = zero_hashes::<H>(8).unwrap();
+    assert_eq!(zeros[..vectors.zero_hashes.len()], hashes(&vectors.zero_hashes)[..]);
+
+    for case in &vectors.trees {
+        let leaves = hashes(&case.leaves);
+        let root = hash(&case.root);
+
+        // Batch build
+        let tree = MerkleTree::<H>::from_leaves(case.depth, &leaves).unwrap();
+        assert_eq!(tree.root(), root);
+        for (i, node) in hashes(&case.leaf_nodes).iter().enumerate() {
+            assert_eq!(tree.leaf_node(i as u64).unwrap(), *node);
+        }
+
+        // Append one by one
+        let mut appended = MerkleTree::<H>::new(case.depth).unwrap();
+        let mut incremental = IncrementalTree::<H>::new(case.depth).unwrap();
+        for leaf in &leaves {
+            appended.append(leaf).unwrap();
+            incremental.append(leaf).unwrap();
+        }
+        assert_eq!(appended.root(), root);
+        assert_eq!(incremental.root(), root);
+
+        // Single proofs
+        for proof in &case.proofs {
+            let siblings = hashes(&proof.siblings);
+            assert_eq!(tree.proof(proof.index).unwrap().siblings, siblings);
+            let leaf_node = tree.leaf_node(proof.index).unwrap();
+            assert!(verify_proof::<H>(&leaf_node, &siblings, proof.index, case.depth, &root).unwrap());
+        }
+
+        // Multiproof
+        let siblings = hashes(&case.multiproof.siblings);
+        let multi = tree.multiproof(&case.multiproof.indices).unwrap();
+        assert_eq!(multi.siblings, siblings);
+        let nodes: Vec<Hash> = case
+            .multiproof
+            .indices
+            .iter()
+            .map(|&i| tree.leaf_node(i).unwrap())
+            .collect();
+        assert!(multi.verify::<H>(&nodes, case.depth, &root).unwrap());
+    }
+}
+
+#[test]
+fn keccak_vectors() {
+    check_vectors::<Keccak>(include_str!("vectors/keccak.json"));
+}
+
+#[cfg(feature = "poseidon")]
+#[test]
+fn poseidon_vectors() {
+    check_vectors::<Poseidon>(include_str!("vectors/poseidon.json"));
+}
+
+#[cfg(feature = "poseidon")]
+#[test]
+fn poseidon_matches_circomlib() {
+    // circomlibjs poseidon([1, 2])
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    let mut two = [0u8; 32];
+    two[31] = 2;
+    assert_eq!(
+        Poseidon::hash_nodes(&one, &two).unwrap(),
+        hash("115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a")
+    );
+}
+
+#[derive(Deserialize)]
+struct DomainVectors {
+    tag: String,
+    version: u8,
+    domains: Vec<DomainVector>,
+}
+
+#[derive(Deserialize)]
+struct DomainVector {
+    genesis_hash: String,
+    program_id: String,
+    separator: String,
+    leaves: Vec<String>,
+    keccak_leaf_nodes: Vec<String>,
+    keccak_root: String,
+    #[cfg_attr(not(feature = "poseidon"), allow(dead_code))]
+    poseidon_leaf_nodes: Vec<String>,
+}
+
+fn domain_vectors() -> DomainVectors {
+    serde_json::from_str(include_str!("vectors/domain.json")).unwrap()
+}
+
+fn check_domain_leaves<H: Hasher>(case: &DomainVector, expected: &[String]) {
+    let separator = hash(&case.separator);
+    for (leaf, node) in case.leaves.iter().zip(hashes(expected)) {
+        assert_eq!(H::hash_domain_leaf(&separator, &hash(leaf)).unwrap(), node);
+    }
+}
+
+#[test]
+fn domain_vectors_keccak() {
+    let vectors = domain_vectors();
+    assert_eq!(vectors.tag.as_bytes(), DOMAIN_TAG);
+    assert_eq!(vectors.version, DOMAIN_VERSION);
+
+    for case in &vectors.domains {
+        let separator = domain_separator(&hash(&case.genesis_hash), &hash(&case.program_id));
+        assert_eq!(separator, hash(&case.separator));
+        check_domain_leaves::<Keccak>(case, &case.keccak_leaf_nodes);
+
+        let tree = MerkleTree::<Keccak>::from_leaf_nodes(2, hashes(&case.keccak_leaf_nodes)).unwrap();
+        assert_eq!(tree.root(), hash(&case.keccak_root));
+    }
+
+    // The same leaves verify under one domain only
+    let [mainnet, devnet] = &vectors.domains[..] else { panic!("expected two domains") };
+    assert_ne!(mainnet.keccak_root, devnet.keccak_root);
+}
+
+#[cfg(feature = "poseidon")]
+#[test]
+fn domain_vectors_poseidon() {
+    for case in &domain_vectors().domains {
+        check_domain_leaves::<Poseidon>(case, &case.poseidon_leaf_nodes);
+    }
+}
+
+#[test]
+fn domain_leaves_differ_from_plain_leaves() {
+    let value = [7u8; 32];
+    let separator = domain_separator(&[1u8; 32], &[2u8; 32]);
+    assert_ne!(Keccak::hash_domain_leaf(&separator, &value), Keccak::hash_leaf(&value));
+    assert_ne!(
+        Keccak::hash_domain_leaf(&separator, &value),
+        Keccak::hash_domain_leaf(&domain_separator(&[1u8; 32], &[3u8; 32]), &value)
+    );
+}
+
+#[test]
+fn rejects_wrong_depth_and_aliased_index() {
+    let leaves = [[1u8; 32], [2u8; 32], [3u8; 32]];
+    let tree = MerkleTree::<Keccak>::from_leaves(2, &leaves).unwrap();
+    let root = tree.root();
+    let proof = tree.proof(1).unwrap();
+    let leaf_node = tree.leaf_node(1).unwrap();
+
+    // Same leaf at index + 2^depth computes the same root but must be rejected
+    assert_eq!(compute_root::<Keccak>(&leaf_node, &proof.siblings, 5).unwrap(), root);
+    assert_eq!(
+        verify_proof::<Keccak>(&leaf_node, &proof.siblings, 5, 2, &root),
+        Err(MerkleError::IndexOutOfRange)
+    );
+
+    // Internal node presented as a leaf with a shortened proof
+    let internal = Keccak::hash_nodes(&tree.leaf_node(0).unwrap(), &leaf_node).unwrap();
+    assert_eq!(
+        verify_proof::<Keccak>(&internal, &proof.siblings[1..], 0, 2, &root),
+        Err(MerkleError::ProofLengthMismatch)
+    );
+}
+
+#[test]
+fn rejects_bad_multiproofs() {
+    let leaves: Vec<Hash> = (0u8..6).map(|i| [i; 32]).collect();
+    let tree = MerkleTree::<Keccak>::from_leaves(3, &leaves).unwrap();
+    let root = tree.root();
+
+    assert_eq!(tree.multiproof(&[2, 1]), Err(MerkleError::InvalidIndices));
+    assert_eq!(tree.multiproof(&[1, 1]), Err(MerkleError::InvalidIndices));
+    assert_eq!(tree.multiproof(&[8]), Err(MerkleError::IndexOutOfRange));
+
+    let multi = tree.multiproof(&[0, 3]).unwrap();
+    let nodes = [tree.leaf_node(0).unwrap(), tree.leaf_node(3).unwrap()];
+    let mut extra = multi.siblings.clone();
+    extra.push([0u8; 32]);
+    assert_eq!(
+        verify_multiproof::<Keccak>(&[0, 3], &nodes, &extra, 3, &root),
+        Err(MerkleError::InvalidMultiproof)
+    );
+    assert_eq!(
+        verify_multiproof::<Keccak>(&[0, 3], &nodes, &multi.siblings[1..], 3, &root),
+        Err(MerkleError::InvalidMultiproof)
+    );
+    assert!(!verify_multiproof::<Keccak>(&[0, 3], &[nodes[1], nodes[0]], &multi.siblings, 3, &root).unwrap());
+}
+
+#[test]
+fn full_tree_rejects_append() {
+    let mut tree = MerkleTree::<Keccak>::new(1).unwrap();
+    let mut incremental = IncrementalTree::<Keccak>::new(1).unwrap();
+    for leaf in [[1u8; 32], [2u8; 32]] {
+        tree.append(&leaf).unwrap();
+        incremental.append(&leaf).unwrap();
+    }
+    assert_eq!(tree.append(&[3u8; 32]), Err(MerkleError::TreeFull));
+    assert_eq!(incremental.append(&[3u8; 32]), Err(MerkleError::TreeFull));
+}
@@ -0,0 +1,25 @@
+//! SHA-256 backend
+
+use crate::Hash;
+
+/// SHA-256 of the concatenated parts
+pub fn hashv(parts: &[&[u8]]) -> Hash {
+    #[cfg(feature = "solana")]
+    {
+        solana_program::hash::hashv(parts).to_bytes()
+    }
+    #[cfg(not(feature = "solana"))]
+    {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Chain step: H(chain_index || position || value), integers big-endian
+pub fn chain_step(value: &Hash, chain_index: u32, position: u32) -> Hash {
+    hashv(&[&chain_index.to_be_bytes(), &position.to_be_bytes(), value])
+}
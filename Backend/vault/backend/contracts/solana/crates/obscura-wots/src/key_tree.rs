@@ -0,0 +1,69 @@
+//! Key pool trees
+//!
+//! A user's pre-generated keys are committed by the root of a tree over their
+//! public key hashes, as built by `WOTSKeyManager` in `packages/crypto`:
+//! leaves are the public key hashes themselves, nodes are
+//! H(0x01 || left || right) and the leaves are padded with zero hashes to a
+//! power of two.
+
+use crate::hash::hashv;
+use crate::Hash;
+
+/// Deepest key tree (2^20 keys, the key manager's pool limit)
+pub const MAX_KEY_TREE_DEPTH: u8 = 20;
+
+/// Hash two child nodes into their parent
+pub fn hash_nodes(left: &Hash, right: &Hash) -> Hash {
+    hashv(&[&[0x01], left, right])
+}
+
+/// Root reached from a public key hash and its siblings
+///
+/// Bit `i` of `index` selects whether the current node is the right child
+/// at level `i`.
+pub fn compute_root(public_key_hash: &Hash, proof: &[Hash], mut index: u64) -> Hash {
+    let mut computed = *public_key_hash;
+    for sibling in proof {
+        computed = if index & 1 == 1 {
+            hash_nodes(sibling, &computed)
+        } else {
+            hash_nodes(&computed, sibling)
+        };
+        index >>= 1;
+    }
+    computed
+}
+
+/// Check that a key is at `index` in a tree of the given depth
+///
+/// The proof must have exactly `depth` siblings and `index < 2^depth`, so a
+/// key verifies at exactly one position.
+pub fn verify_key_proof(
+    public_key_hash: &Hash,
+    proof: &[Hash],
+    index: u64,
+    depth: u8,
+    root: &Hash,
+) -> bool {
+    depth <= MAX_KEY_TREE_DEPTH
+        && proof.len() == depth as usize
+        && index < (1u64 << depth)
+        && compute_root(public_key_hash, proof, index) == *root
+}
+
+/// Root of a tree over `leaves`, computed in place
+///
+/// `leaves` must be padded to a power of two; returns `None` otherwise.
+pub fn root(leaves: &mut [Hash]) -> Option<Hash> {
+    if !leaves.len().is_power_of_two() {
+        return None;
+    }
+    let mut width = leaves.len();
+    while width > 1 {
+        for i in 0..width / 2 {
+            leaves[i] = hash_nodes(&leaves[2 * i], &leaves[2 * i + 1]);
+        }
+        width /= 2;
+    }
+    Some(leaves[0])
+}
@@ -0,0 +1,46 @@
+//! Key derivation and signing
+//!
+//! Signing is for tooling and tests; a private key must sign only once.
+
+use crate::hash::hashv;
+use crate::verify::chain;
+use crate::{message_digits, Hash, LEN, W};
+
+/// Domain of derived private key elements (`hashWithDomain('WOTS_SK', ..)`)
+pub const PRIVATE_KEY_DOMAIN: &[u8] = b"WOTS_SK\0";
+
+/// Derive the private key at `index` from a seed
+///
+/// Element `i` is H("WOTS_SK" || 0x00 || seed || index || i), integers
+/// big-endian, matching `WOTSScheme.derivePrivateKey`.
+pub fn derive_private_key(seed: &[u8], index: u32) -> [Hash; LEN] {
+    let mut private_key = [[0u8; 32]; LEN];
+    for (i, element) in private_key.iter_mut().enumerate() {
+        *element = hashv(&[
+            PRIVATE_KEY_DOMAIN,
+            seed,
+            &index.to_be_bytes(),
+            &(i as u32).to_be_bytes(),
+        ]);
+    }
+    private_key
+}
+
+/// Public key of a private key (every chain advanced to W - 1)
+pub fn public_key(private_key: &[Hash; LEN]) -> [Hash; LEN] {
+    let mut public_key = [[0u8; 32]; LEN];
+    for (i, element) in private_key.iter().enumerate() {
+        public_key[i] = chain(element, 0, W - 1, i as u32);
+    }
+    public_key
+}
+
+/// Sign a message (one time only)
+pub fn sign(private_key: &[Hash; LEN], message: &Hash) -> [Hash; LEN] {
+    let digits = message_digits(message);
+    let mut signature = [[0u8; 32]; LEN];
+    for (i, element) in private_key.iter().enumerate() {
+        signature[i] = chain(element, 0, digits[i] as u32, i as u32);
+    }
+    signature
+}
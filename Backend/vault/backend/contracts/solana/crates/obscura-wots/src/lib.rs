@@ -0,0 +1,93 @@
+//! Obscura WOTS+ library
+//!
+//! Winternitz one-time signatures (w = 16, n = 32, SHA-256) compatible with
+//! `packages/crypto/src/wots` in the TypeScript SDK:
+//! - Message and checksum digits
+//! - Hash chains with `(chain index, position)` domain separation
+//! - Public key recovery and hashing (no allocation, usable on-chain)
+//! - Key derivation and signing for tooling and tests
+//! - Key pool trees committing many public key hashes in one root
+//!
+//! The signed message is the 32-byte intent commitment. A public key is
+//! identified by `public_key_hash`, which is what leaves and registries
+//! commit to.
+//!
+//! With the `solana` feature hashing uses the Solana sha256 syscall.
+
+#![no_std]
+
+pub mod hash;
+pub mod key_tree;
+pub mod keys;
+pub mod verify;
+
+pub use keys::{derive_private_key, public_key, sign};
+pub use verify::{complete_chain, public_key_hash, recover_public_key, verify};
+
+/// 32-byte hash, chain value or message
+pub type Hash = [u8; 32];
+
+/// Winternitz parameter
+pub const W: u32 = 16;
+
+/// Bits per digit (log2 W)
+pub const LOG_W: u32 = 4;
+
+/// Hash size in bytes
+pub const N: usize = 32;
+
+/// Message digits: ceil(8N / LOG_W)
+pub const LEN1: usize = 64;
+
+/// Checksum digits: floor(log2(LEN1 * (W - 1)) / LOG_W) + 1
+pub const LEN2: usize = 3;
+
+/// Chains per key and signature
+pub const LEN: usize = LEN1 + LEN2;
+
+/// Left shift applied to the checksum before it is split into digits
+///
+/// The TypeScript scheme shifts by `LEN2 * LOG_W - 10` and then reads the
+/// leading 12 bits of two bytes, so only the top 8 of the 10 checksum bits
+/// end up in the digits. Kept as is so existing signatures verify.
+const CHECKSUM_SHIFT: u32 = 2;
+
+/// Split bytes into base-W digits, most significant first
+///
+/// Reads as many digits as fit in `out`; input shorter than that is padded
+/// with zero bits.
+pub fn base_w(input: &[u8], out: &mut [u8]) {
+    let mut total: u32 = 0;
+    let mut bits: u32 = 0;
+    let mut consumed = 0;
+
+    for digit in out.iter_mut() {
+        if bits < LOG_W {
+            if consumed < input.len() {
+                total = (total << 8) | input[consumed] as u32;
+                consumed += 1;
+                bits += 8;
+            } else {
+                total <<= LOG_W - bits;
+                bits = LOG_W;
+            }
+        }
+        bits -= LOG_W;
+        *digit = ((total >> bits) & (W - 1)) as u8;
+    }
+}
+
+/// Digits selecting the chain position of each signature element
+pub fn message_digits(message: &Hash) -> [u8; LEN] {
+    let mut digits = [0u8; LEN];
+    base_w(message, &mut digits[..LEN1]);
+
+    let checksum: u32 = digits[..LEN1]
+        .iter()
+        .map(|digit| (W - 1) - *digit as u32)
+        .sum();
+    let checksum_bytes = (checksum << CHECKSUM_SHIFT).to_be_bytes();
+    base_w(&checksum_bytes[2..], &mut digits[LEN1..]);
+
+    digits
+}
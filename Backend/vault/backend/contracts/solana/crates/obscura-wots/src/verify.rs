@@ -0,0 +1,49 @@
+//! Signature verification (no allocation)
+
+use crate::hash::{chain_step, hashv};
+use crate::{message_digits, Hash, LEN, W};
+
+/// Domain of public key hashes (`hashWithDomain('WOTS_PK', ..)`)
+pub const PUBLIC_KEY_DOMAIN: &[u8] = b"WOTS_PK\0";
+
+/// Advance a chain value from `start` by `steps` positions
+pub fn chain(value: &Hash, start: u32, steps: u32, chain_index: u32) -> Hash {
+    let mut current = *value;
+    for position in start..start + steps {
+        current = chain_step(&current, chain_index, position);
+    }
+    current
+}
+
+/// Complete one signature chain to its public key element
+///
+/// Chains are independent, so callers can verify a signature a few chains
+/// at a time.
+pub fn complete_chain(element: &Hash, digit: u8, chain_index: u32) -> Hash {
+    chain(element, digit as u32, W - 1 - digit as u32, chain_index)
+}
+
+/// Recover the public key a signature was made with
+pub fn recover_public_key(signature: &[Hash; LEN], message: &Hash) -> [Hash; LEN] {
+    let digits = message_digits(message);
+    let mut public_key = [[0u8; 32]; LEN];
+    for (i, element) in signature.iter().enumerate() {
+        public_key[i] = complete_chain(element, digits[i], i as u32);
+    }
+    public_key
+}
+
+/// Hash of a public key: H("WOTS_PK" || 0x00 || pk_0 || .. || pk_66)
+pub fn public_key_hash(public_key: &[Hash; LEN]) -> Hash {
+    let mut parts: [&[u8]; LEN + 1] = [&[]; LEN + 1];
+    parts[0] = PUBLIC_KEY_DOMAIN;
+    for (i, element) in public_key.iter().enumerate() {
+        parts[i + 1] = element;
+    }
+    hashv(&parts)
+}
+
+/// Check a signature against the hash of the signer's public key
+pub fn verify(signature: &[Hash; LEN], message: &Hash, expected_public_key_hash: &Hash) -> bool {
+    public_key_hash(&recover_public_key(signature, message)) == *expected_public_key_hash
+}
@@ -0,0 +1,151 @@
+//! Cross-implementation test vectors
+//!
+//! `tests/vectors/wots.json` was produced with a port of the TypeScript scheme in
+//! `packages/crypto/src/wots` (w = 16); keys are derived with
+//! `derivePrivateKey(seed, key_index)`. `key_trees` are key pool trees over
+//! the vectors' public key hashes (`packages/crypto/src/merkle/tree.ts`).
+
+use obscura_wots::key_tree;
+use obscura_wots::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Vectors {
+    len: usize,
+    vectors: Vec<Vector>,
+    key_trees: Vec<KeyTreeVector>,
+}
+
+#[derive(Deserialize)]
+struct KeyTreeVector {
+    depth: u8,
+    leaves: Vec<String>,
+    root: String,
+    proofs: Vec<KeyProofVector>,
+}
+
+#[derive(Deserialize)]
+struct KeyProofVector {
+    index: u64,
+    siblings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Vector {
+    seed: String,
+    key_index: u32,
+    message: String,
+    digits: Vec<u8>,
+    public_key: Vec<String>,
+    public_key_hash: String,
+    signature: Vec<String>,
+}
+
+fn hash(value: &str) -> Hash {
+    hex::decode(value).unwrap().try_into().unwrap()
+}
+
+fn elements(values: &[String]) -> [Hash; LEN] {
+    let elements: Vec<Hash> = values.iter().map(|v| hash(v)).collect();
+    elements.try_into().unwrap()
+}
+
+fn vectors() -> Vectors {
+    let vectors: Vectors = serde_json::from_str(include_str!("vectors/wots.json")).unwrap();
+    assert_eq!(vectors.len, LEN);
+    vectors
+}
+
+#[test]
+fn message_digits_match() {
+    for vector in vectors().vectors {
+        assert_eq!(message_digits(&hash(&vector.message))[..], vector.digits[..]);
+    }
+}
+
+#[test]
+fn keys_and_signatures_match() {
+    for vector in vectors().vectors {
+        let private_key = derive_private_key(&hex::decode(&vector.seed).unwrap(), vector.key_index);
+        let public_key = public_key(&private_key);
+        assert_eq!(public_key, elements(&vector.public_key));
+        assert_eq!(public_key_hash(&public_key), hash(&vector.public_key_hash));
+        assert_eq!(sign(&private_key, &hash(&vector.message)), elements(&vector.signature));
+    }
+}
+
+#[test]
+fn signatures_verify() {
+    for vector in vectors().vectors {
+        let signature = elements(&vector.signature);
+        let message = hash(&vector.message);
+        assert_eq!(recover_public_key(&signature, &message), elements(&vector.public_key));
+        assert!(verify(&signature, &message, &hash(&vector.public_key_hash)));
+    }
+}
+
+#[test]
+fn chains_verify_in_steps() {
+    let vector = &vectors().vectors[2];
+    let message = hash(&vector.message);
+    let digits = message_digits(&message);
+    let mut chains = elements(&vector.signature);
+
+    // Same result as recover_public_key when completed a few chains at a time
+    for start in (0..LEN).step_by(10) {
+        for i in start..(start + 10).min(LEN) {
+            chains[i] = complete_chain(&chains[i], digits[i], i as u32);
+        }
+    }
+    assert_eq!(public_key_hash(&chains), hash(&vector.public_key_hash));
+}
+
+#[test]
+fn wrong_message_or_element_fails() {
+    let vector = &vectors().vectors[2];
+    let signature = elements(&vector.signature);
+    let expected = hash(&vector.public_key_hash);
+
+    let mut message = hash(&vector.message);
+    message[31] ^= 1;
+    assert!(!verify(&signature, &message, &expected));
+
+    let mut tampered = signature;
+    tampered[LEN - 1][0] ^= 1;
+    assert!(!verify(&tampered, &hash(&vector.message), &expected));
+}
+
+#[test]
+fn checksum_digits_cover_zero_message() {
+    // All-zero digits give the largest checksum (64 * 15 = 960)
+    let digits = message_digits(&[0u8; 32]);
+    assert!(digits[..LEN1].iter().all(|digit| *digit == 0));
+    assert_eq!(digits[LEN1..], [0, 15, 0]);
+}
+
+#[test]
+fn key_tree_roots_and_proofs_match() {
+    for tree in vectors().key_trees {
+        let root = hash(&tree.root);
+        let mut leaves: Vec<Hash> = tree.leaves.iter().map(|v| hash(v)).collect();
+        leaves.resize(1 << tree.depth, [0u8; 32]);
+        assert_eq!(key_tree::root(&mut leaves), Some(root));
+
+        for proof in &tree.proofs {
+            let siblings: Vec<Hash> = proof.siblings.iter().map(|v| hash(v)).collect();
+            let leaf = hash(&tree.leaves[proof.index as usize]);
+            assert!(key_tree::verify_key_proof(&leaf, &siblings, proof.index, tree.depth, &root));
+
+            // Wrong position or truncated proof
+            let other = proof.index ^ 1;
+            assert!(!key_tree::verify_key_proof(&leaf, &siblings, other, tree.depth, &root));
+            assert!(!key_tree::verify_key_proof(&leaf, &siblings[1..], proof.index >> 1, tree.depth - 1, &root));
+        }
+    }
+}
+
+#[test]
+fn key_tree_root_needs_power_of_two() {
+    let mut leaves = [[1u8; 32]; 3];
+    assert_eq!(key_tree::root(&mut leaves), None);
+}
@@ -0,0 +1,578 @@
+//! Governance flows against the real upgradeable loader
+//!
+//! Governed programs are installed as upgradeable programs whose authority is
+//! the governance PDA. Their code and upgrade buffers are the SPL Memo ELFs
+//! that program-test ships, so upgrades go through full loader verification.
+
+use anchor_lang::solana_program::instruction::InstructionError;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use sip_governance::error::GovernanceError;
+use sip_governance::instructions::*;
+use sip_governance::state::*;
+use solana_program_test::*;
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::account_info::AccountInfo;
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::clock::Clock;
+use solana_sdk::entrypoint::ProgramResult;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::{system_program, sysvar};
+
+const MIN_DELAY: i64 = 3600;
+
+const MEMO_V1: Pubkey = solana_sdk::pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+const MEMO_V3: Pubkey = solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+// Anchor's entrypoint ties account lifetimes together; program-test's doesn't
+fn entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    sip_governance::entry(program_id, accounts, data)
+}
+
+struct Harness {
+    context: ProgramTestContext,
+    members: Vec<Keypair>,
+}
+
+/// Start the bank with governance deployed by the payer, not yet initialized
+async fn start() -> Harness {
+    let program_test = ProgramTest::new("sip_governance", sip_governance::ID, processor!(entry));
+    let mut context = program_test.start_with_context().await;
+    let members: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for member in &members {
+        let account = AccountSharedData::new(1_000_000_000, 0, &system_program::ID);
+        context.set_account(&member.pubkey(), &account);
+    }
+
+    let mut harness = Harness { context, members };
+    // program-test runs governance as a builtin; give it the ProgramData a
+    // deployment by the payer would have
+    let payer = harness.context.payer.pubkey();
+    harness.set_loader_account(
+        program_data_address(&sip_governance::ID),
+        UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(payer),
+        },
+        &[],
+        false,
+    );
+    harness
+}
+
+async fn setup(threshold: u8) -> Harness {
+    let mut harness = start().await;
+    let payer = harness.context.payer.pubkey();
+    let ix = harness.initialize_ix(payer, threshold);
+    harness.send(ix, None).await.unwrap();
+    harness
+}
+
+fn governance_address() -> Pubkey {
+    Pubkey::find_program_address(&[GOVERNANCE_SEED], &sip_governance::ID).0
+}
+
+fn proposal_address(id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[PROPOSAL_SEED, &id.to_le_bytes()], &sip_governance::ID).0
+}
+
+fn custom(error: GovernanceError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error.into()))
+}
+
+impl Harness {
+    fn initialize_ix(&self, payer: Pubkey, threshold: u8) -> Instruction {
+        Instruction {
+            program_id: sip_governance::ID,
+            accounts: sip_governance::accounts::Initialize {
+                governance: governance_address(),
+                upgrade_authority: upgrade_authority_address(),
+                program_data: program_data_address(&sip_governance::ID),
+                payer,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: sip_governance::instruction::Initialize {
+                members: self.members.iter().map(|m| m.pubkey()).collect(),
+                threshold,
+                min_delay: MIN_DELAY,
+            }
+            .data(),
+        }
+    }
+
+    async fn send(
+        &mut self,
+        ix: Instruction,
+        member: Option<usize>,
+    ) -> Result<(), TransactionError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut signers = vec![&self.context.payer];
+        if let Some(index) = member {
+            signers.push(&self.members[index]);
+        }
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.context.payer.pubkey()),
+            &signers,
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(tx)
+            .await
+            .map_err(|e| e.unwrap())
+    }
+
+    async fn account(&mut self, address: Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+    }
+
+    async fn elf(&mut self, program: Pubkey) -> Vec<u8> {
+        self.account(program).await.unwrap().data
+    }
+
+    async fn advance(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    fn set_loader_account(
+        &mut self,
+        address: Pubkey,
+        state: UpgradeableLoaderState,
+        elf: &[u8],
+        executable: bool,
+    ) {
+        let mut data = bincode::serialize(&state).unwrap();
+        data.extend_from_slice(elf);
+        let account = Account {
+            lamports: 10_000_000_000,
+            data,
+            owner: bpf_loader_upgradeable::ID,
+            executable,
+            rent_epoch: 0,
+        };
+        self.context.set_account(&address, &account.into());
+    }
+
+    /// Install an upgradeable program running memo v1 held by `authority`
+    async fn install_program(&mut self, authority: Pubkey) -> Pubkey {
+        let program = Pubkey::new_unique();
+        let program_data = program_data_address(&program);
+        // Room for the larger memo v3 ELF
+        let mut elf = self.elf(MEMO_V1).await;
+        elf.resize(self.elf(MEMO_V3).await.len().max(elf.len()), 0);
+        self.set_loader_account(
+            program,
+            UpgradeableLoaderState::Program {
+                programdata_address: program_data,
+            },
+            &[],
+            true,
+        );
+        self.set_loader_account(
+            program_data,
+            UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(authority),
+            },
+            &elf,
+            false,
+        );
+        program
+    }
+
+    /// Write memo v3 into a buffer held by `authority`
+    async fn install_buffer(&mut self, authority: Pubkey) -> Pubkey {
+        let buffer = Pubkey::new_unique();
+        let elf = self.elf(MEMO_V3).await;
+        self.set_loader_account(
+            buffer,
+            UpgradeableLoaderState::Buffer {
+                authority_address: Some(authority),
+            },
+            &elf,
+            false,
+        );
+        buffer
+    }
+
+    async fn upgrade_authority_of(&mut self, program: Pubkey) -> Option<Pubkey> {
+        let data = self
+            .account(program_data_address(&program))
+            .await
+            .unwrap()
+            .data;
+        match bincode::deserialize(&data).unwrap() {
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            } => upgrade_authority_address,
+            _ => panic!("not program data"),
+        }
+    }
+
+    async fn propose(
+        &mut self,
+        member: usize,
+        id: u64,
+        action: ProposalAction,
+        delay: i64,
+    ) -> Result<(), TransactionError> {
+        let (program_data, buffer) = match &action {
+            ProposalAction::Upgrade {
+                program, buffer, ..
+            } => (Some(program_data_address(program)), Some(*buffer)),
+            ProposalAction::SetUpgradeAuthority { program, .. } => {
+                (Some(program_data_address(program)), None)
+            }
+            ProposalAction::SetConfig { .. } => (None, None),
+        };
+        let ix = Instruction {
+            program_id: sip_governance::ID,
+            accounts: sip_governance::accounts::Propose {
+                governance: governance_address(),
+                proposal: proposal_address(id),
+                program_data,
+                buffer,
+                proposer: self.members[member].pubkey(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: sip_governance::instruction::Propose { action, delay }.data(),
+        };
+        self.send(ix, Some(member)).await
+    }
+
+    async fn approve(&mut self, member: usize, id: u64) -> Result<(), TransactionError> {
+        let ix = Instruction {
+            program_id: sip_governance::ID,
+            accounts: sip_governance::accounts::Approve {
+                governance: governance_address(),
+                proposal: proposal_address(id),
+                member: self.members[member].pubkey(),
+            }
+            .to_account_metas(None),
+            data: sip_governance::instruction::Approve {}.data(),
+        };
+        self.send(ix, Some(member)).await
+    }
+
+    async fn execute_upgrade(
+        &mut self,
+        id: u64,
+        proposer: usize,
+        program: Pubkey,
+        buffer: Pubkey,
+        spill: Pubkey,
+    ) -> Result<(), TransactionError> {
+        let executor = self.context.payer.pubkey();
+        let ix = Instruction {
+            program_id: sip_governance::ID,
+            accounts: sip_governance::accounts::ExecuteUpgrade {
+                governance: governance_address(),
+                proposal: proposal_address(id),
+                proposer: self.members[proposer].pubkey(),
+                upgrade_authority: upgrade_authority_address(),
+                program,
+                program_data: program_data_address(&program),
+                buffer,
+                spill,
+                rent: sysvar::rent::ID,
+                clock: sysvar::clock::ID,
+                bpf_loader_upgradeable: bpf_loader_upgradeable::ID,
+                executor,
+            }
+            .to_account_metas(None),
+            data: sip_governance::instruction::ExecuteUpgrade {}.data(),
+        };
+        self.send(ix, None).await
+    }
+}
+
+#[tokio::test]
+async fn only_upgrade_authority_initializes() {
+    let mut h = start().await;
+
+    let outsider = h.members[0].pubkey();
+    let ix = h.initialize_ix(outsider, 2);
+    assert_eq!(
+        h.send(ix, Some(0)).await.unwrap_err(),
+        custom(GovernanceError::NotUpgradeAuthority)
+    );
+    assert!(h.account(governance_address()).await.is_none());
+
+    let payer = h.context.payer.pubkey();
+    let ix = h.initialize_ix(payer, 2);
+    h.send(ix, None).await.unwrap();
+    assert!(h.account(governance_address()).await.is_some());
+}
+
+#[tokio::test]
+async fn upgrade_waits_for_threshold_and_delay() {
+    let mut h = setup(2).await;
+    let authority = upgrade_authority_address();
+    let program = h.install_program(authority).await;
+    let buffer = h.install_buffer(authority).await;
+    let spill = Pubkey::new_unique();
+    let action = ProposalAction::Upgrade {
+        program,
+        buffer,
+        spill,
+    };
+
+    h.propose(0, 0, action, MIN_DELAY).await.unwrap();
+    assert_eq!(
+        h.execute_upgrade(0, 0, program, buffer, spill).await,
+        Err(custom(GovernanceError::ProposalNotQueued))
+    );
+
+    h.approve(1, 0).await.unwrap();
+    assert_eq!(
+        h.execute_upgrade(0, 0, program, buffer, spill).await,
+        Err(custom(GovernanceError::TimelockActive))
+    );
+
+    // Anyone executes once the delay has passed; the wrong spill is rejected
+    h.advance(MIN_DELAY).await;
+    assert_eq!(
+        h.execute_upgrade(0, 0, program, buffer, Pubkey::new_unique())
+            .await,
+        Err(custom(GovernanceError::ActionMismatch))
+    );
+    h.execute_upgrade(0, 0, program, buffer, spill)
+        .await
+        .unwrap();
+
+    let v3 = h.elf(MEMO_V3).await;
+    let data = h
+        .account(program_data_address(&program))
+        .await
+        .unwrap()
+        .data;
+    let offset = UpgradeableLoaderState::size_of_programdata_metadata();
+    assert_eq!(&data[offset..offset + v3.len()], &v3[..]);
+    assert!(h.account(buffer).await.is_none());
+    assert!(h.account(spill).await.unwrap().lamports > 0);
+    assert_eq!(h.upgrade_authority_of(program).await, Some(authority));
+
+    // The proposal is closed on execution
+    assert!(h.account(proposal_address(0)).await.is_none());
+}
+
+#[tokio::test]
+async fn proposals_require_governed_program_and_buffer() {
+    let mut h = setup(2).await;
+    let authority = upgrade_authority_address();
+    let outsider = Pubkey::new_unique();
+    let governed = h.install_program(authority).await;
+    let foreign = h.install_program(outsider).await;
+    let buffer = h.install_buffer(authority).await;
+    let writable_buffer = h.install_buffer(outsider).await;
+
+    let upgrade = |program, buffer| ProposalAction::Upgrade {
+        program,
+        buffer,
+        spill: outsider,
+    };
+    assert_eq!(
+        h.propose(0, 0, upgrade(foreign, buffer), MIN_DELAY).await,
+        Err(custom(GovernanceError::ProgramNotGoverned))
+    );
+    assert_eq!(
+        h.propose(0, 0, upgrade(governed, writable_buffer), MIN_DELAY)
+            .await,
+        Err(custom(GovernanceError::InvalidBuffer))
+    );
+    assert_eq!(
+        h.propose(
+            0,
+            0,
+            ProposalAction::SetUpgradeAuthority {
+                program: foreign,
+                new_authority: None,
+            },
+            MIN_DELAY
+        )
+        .await,
+        Err(custom(GovernanceError::ProgramNotGoverned))
+    );
+    h.propose(0, 0, upgrade(governed, buffer), MIN_DELAY)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn only_members_propose_and_approve_once() {
+    let mut h = setup(3).await;
+    let config = ProposalAction::SetConfig {
+        members: vec![h.members[0].pubkey()],
+        threshold: 1,
+        min_delay: 0,
+    };
+
+    assert_eq!(
+        h.propose(0, 0, config.clone(), MIN_DELAY - 1).await,
+        Err(custom(GovernanceError::InvalidDelay))
+    );
+    assert_eq!(
+        h.propose(
+            0,
+            0,
+            ProposalAction::SetConfig {
+                members: vec![h.members[0].pubkey()],
+                threshold: 2,
+                min_delay: 0,
+            },
+            MIN_DELAY
+        )
+        .await,
+        Err(custom(GovernanceError::InvalidConfig))
+    );
+
+    h.members.push(Keypair::new());
+    let account = AccountSharedData::new(1_000_000_000, 0, &system_program::ID);
+    h.context.set_account(&h.members[3].pubkey(), &account);
+    assert_eq!(
+        h.propose(3, 0, config.clone(), MIN_DELAY).await,
+        Err(custom(GovernanceError::NotMember))
+    );
+
+    h.propose(0, 0, config, MIN_DELAY).await.unwrap();
+    assert_eq!(
+        h.approve(0, 0).await,
+        Err(custom(GovernanceError::AlreadyApproved))
+    );
+    assert_eq!(
+        h.approve(3, 0).await,
+        Err(custom(GovernanceError::NotMember))
+    );
+    h.approve(1, 0).await.unwrap();
+    assert_eq!(
+        h.approve(1, 0).await,
+        Err(custom(GovernanceError::AlreadyApproved))
+    );
+}
+
+#[tokio::test]
+async fn set_upgrade_authority_hands_off_program() {
+    let mut h = setup(1).await;
+    let program = h.install_program(upgrade_authority_address()).await;
+    let new_authority = Pubkey::new_unique();
+
+    // Threshold 1 queues on proposal
+    let action = ProposalAction::SetUpgradeAuthority {
+        program,
+        new_authority: Some(new_authority),
+    };
+    h.propose(2, 0, action, MIN_DELAY).await.unwrap();
+    h.advance(MIN_DELAY).await;
+
+    let executor = h.context.payer.pubkey();
+    let ix = Instruction {
+        program_id: sip_governance::ID,
+        accounts: sip_governance::accounts::ExecuteSetUpgradeAuthority {
+            governance: governance_address(),
+            proposal: proposal_address(0),
+            proposer: h.members[2].pubkey(),
+            upgrade_authority: upgrade_authority_address(),
+            program_data: program_data_address(&program),
+            new_authority: Some(new_authority),
+            bpf_loader_upgradeable: bpf_loader_upgradeable::ID,
+            executor,
+        }
+        .to_account_metas(None),
+        data: sip_governance::instruction::ExecuteSetUpgradeAuthority {}.data(),
+    };
+    h.send(ix, None).await.unwrap();
+
+    assert_eq!(h.upgrade_authority_of(program).await, Some(new_authority));
+}
+
+#[tokio::test]
+async fn config_change_makes_open_proposals_stale() {
+    let mut h = setup(2).await;
+    let authority = upgrade_authority_address();
+    let program = h.install_program(authority).await;
+    let buffer = h.install_buffer(authority).await;
+    let spill = Pubkey::new_unique();
+
+    let config = ProposalAction::SetConfig {
+        members: vec![h.members[0].pubkey(), h.members[1].pubkey()],
+        threshold: 2,
+        min_delay: MIN_DELAY * 2,
+    };
+    h.propose(0, 0, config, MIN_DELAY).await.unwrap();
+    h.approve(1, 0).await.unwrap();
+    let upgrade = ProposalAction::Upgrade {
+        program,
+        buffer,
+        spill,
+    };
+    h.propose(2, 1, upgrade, MIN_DELAY).await.unwrap();
+    h.approve(0, 1).await.unwrap();
+    h.advance(MIN_DELAY).await;
+
+    let executor = h.context.payer.pubkey();
+    let ix = Instruction {
+        program_id: sip_governance::ID,
+        accounts: sip_governance::accounts::ExecuteSetConfig {
+            governance: governance_address(),
+            proposal: proposal_address(0),
+            proposer: h.members[0].pubkey(),
+            executor,
+        }
+        .to_account_metas(None),
+        data: sip_governance::instruction::ExecuteSetConfig {}.data(),
+    };
+    h.send(ix, None).await.unwrap();
+
+    // The queued upgrade predates the change and can no longer run
+    assert_eq!(
+        h.execute_upgrade(1, 2, program, buffer, spill).await,
+        Err(custom(GovernanceError::StaleProposal))
+    );
+    assert_eq!(
+        h.approve(1, 1).await,
+        Err(custom(GovernanceError::StaleProposal))
+    );
+
+    // Anyone can clean up a stale proposal
+    let ix = Instruction {
+        program_id: sip_governance::ID,
+        accounts: sip_governance::accounts::CloseProposal {
+            governance: governance_address(),
+            proposal: proposal_address(1),
+            proposer: h.members[2].pubkey(),
+            caller: executor,
+        }
+        .to_account_metas(None),
+        data: sip_governance::instruction::CloseProposal {}.data(),
+    };
+    h.send(ix, None).await.unwrap();
+    assert!(h.account(proposal_address(1)).await.is_none());
+
+    // The removed member can no longer propose
+    assert_eq!(
+        h.propose(
+            2,
+            2,
+            ProposalAction::SetUpgradeAuthority {
+                program,
+                new_authority: None,
+            },
+            MIN_DELAY * 2
+        )
+        .await,
+        Err(custom(GovernanceError::NotMember))
+    );
+}
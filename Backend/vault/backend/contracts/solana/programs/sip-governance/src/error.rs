@@ -0,0 +1,42 @@
+//! Governance error definitions
+
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum GovernanceError {
+    #[msg("Unauthorized: caller is not a governance member")]
+    NotMember,
+
+    #[msg("Invalid configuration: members must be unique and non-empty, threshold within 1..=members, delay within 0..=30 days")]
+    InvalidConfig,
+
+    #[msg("Delay is below the governance minimum or above 30 days")]
+    InvalidDelay,
+
+    #[msg("Program is not upgradeable or not held by the governance upgrade authority")]
+    ProgramNotGoverned,
+
+    #[msg("Buffer is not a loader buffer held by the governance upgrade authority")]
+    InvalidBuffer,
+
+    #[msg("Member already approved this proposal")]
+    AlreadyApproved,
+
+    #[msg("Proposal has not reached the approval threshold")]
+    ProposalNotQueued,
+
+    #[msg("Proposal timelock has not elapsed")]
+    TimelockActive,
+
+    #[msg("Proposal was created under an older governance configuration")]
+    StaleProposal,
+
+    #[msg("Accounts do not match the proposal action")]
+    ActionMismatch,
+
+    #[msg("Only the proposer can close a proposal that can still execute")]
+    ProposalStillActive,
+
+    #[msg("Signer is not the governance program's upgrade authority")]
+    NotUpgradeAuthority,
+}
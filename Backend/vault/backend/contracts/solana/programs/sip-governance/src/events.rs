@@ -0,0 +1,58 @@
+//! Governance events
+//!
+//! Every proposal is visible from creation so the timelock gives holders and
+//! operators time to review a buffer before it is deployed.
+
+use anchor_lang::prelude::*;
+
+use crate::state::ProposalAction;
+
+#[event]
+pub struct GovernanceInitialized {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub min_delay: i64,
+    pub upgrade_authority: Pubkey,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub id: u64,
+    pub action: ProposalAction,
+    pub proposer: Pubkey,
+    pub delay: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub id: u64,
+    pub member: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct ProposalQueued {
+    pub id: u64,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub id: u64,
+    pub action: ProposalAction,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct ProposalClosed {
+    pub id: u64,
+    pub closed_by: Pubkey,
+}
+
+#[event]
+pub struct ConfigChanged {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub min_delay: i64,
+    pub config_version: u64,
+}
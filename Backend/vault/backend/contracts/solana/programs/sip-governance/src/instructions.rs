@@ -0,0 +1,596 @@
+//! Governance instructions
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use anchor_lang::solana_program::program::invoke_signed;
+
+use crate::error::GovernanceError;
+use crate::events::*;
+use crate::state::*;
+
+/// Seeds for PDAs
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+pub const UPGRADE_AUTHORITY_SEED: &[u8] = b"upgrade_authority";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Upgrade authority PDA that governed programs are handed to
+pub fn upgrade_authority_address() -> Pubkey {
+    Pubkey::find_program_address(&[UPGRADE_AUTHORITY_SEED], &crate::ID).0
+}
+
+/// ProgramData address of an upgradeable program
+pub fn program_data_address(program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+// ============ Initialize ============
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Governance::LEN,
+        seeds = [GOVERNANCE_SEED],
+        bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    /// CHECK: PDA only; signs loader CPIs
+    #[account(seeds = [UPGRADE_AUTHORITY_SEED], bump)]
+    pub upgrade_authority: UncheckedAccount<'info>,
+
+    /// This program's ProgramData; only its upgrade authority can initialize,
+    /// so nobody can front-run the deployment with their own members
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ GovernanceError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize(
+    ctx: Context<Initialize>,
+    members: Vec<Pubkey>,
+    threshold: u8,
+    min_delay: i64,
+) -> Result<()> {
+    require!(
+        Governance::is_valid_config(&members, threshold, min_delay),
+        GovernanceError::InvalidConfig
+    );
+
+    let governance = &mut ctx.accounts.governance;
+    governance.set_members(&members);
+    governance.threshold = threshold;
+    governance.min_delay = min_delay;
+    governance.proposal_count = 0;
+    governance.config_version = 0;
+    governance.bump = ctx.bumps.governance;
+    governance.upgrade_authority_bump = ctx.bumps.upgrade_authority;
+
+    emit!(GovernanceInitialized {
+        members,
+        threshold,
+        min_delay,
+        upgrade_authority: ctx.accounts.upgrade_authority.key(),
+    });
+    msg!(
+        "Governance initialized. Threshold: {}, min_delay: {}",
+        threshold,
+        min_delay
+    );
+    Ok(())
+}
+
+// ============ Proposals ============
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(
+        mut,
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::LEN,
+        seeds = [PROPOSAL_SEED, &governance.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// ProgramData of the target program (Upgrade and SetUpgradeAuthority)
+    pub program_data: Option<Account<'info, ProgramData>>,
+
+    /// Buffer holding the new program (Upgrade)
+    pub buffer: Option<Account<'info, UpgradeableLoaderState>>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Loader actions are checked against the accounts now so members only ever
+/// approve programs this governance holds and buffers nobody else can rewrite.
+pub fn propose(ctx: Context<Propose>, action: ProposalAction, delay: i64) -> Result<()> {
+    let governance = &ctx.accounts.governance;
+    let proposer = ctx.accounts.proposer.key();
+
+    require!(governance.is_member(&proposer), GovernanceError::NotMember);
+    require!(
+        delay >= governance.min_delay && delay <= MAX_DELAY,
+        GovernanceError::InvalidDelay
+    );
+
+    let authority = Some(upgrade_authority_address());
+    match &action {
+        ProposalAction::Upgrade {
+            program, buffer, ..
+        } => {
+            require_governed(&ctx.accounts.program_data, program, authority)?;
+            let state = ctx
+                .accounts
+                .buffer
+                .as_ref()
+                .filter(|account| account.key() == *buffer)
+                .ok_or(GovernanceError::InvalidBuffer)?;
+            require!(
+                matches!(
+                    **state,
+                    UpgradeableLoaderState::Buffer { authority_address } if authority_address == authority
+                ),
+                GovernanceError::InvalidBuffer
+            );
+        }
+        ProposalAction::SetUpgradeAuthority { program, .. } => {
+            require_governed(&ctx.accounts.program_data, program, authority)?;
+        }
+        ProposalAction::SetConfig {
+            members,
+            threshold,
+            min_delay,
+        } => {
+            require!(
+                Governance::is_valid_config(members, *threshold, *min_delay),
+                GovernanceError::InvalidConfig
+            );
+        }
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let governance = &mut ctx.accounts.governance;
+    let id = governance.proposal_count;
+    governance.proposal_count += 1;
+
+    // The proposer is the first approval
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.id = id;
+    proposal.action = action.clone();
+    proposal.proposer = proposer;
+    proposal.config_version = governance.config_version;
+    proposal.delay = delay;
+    proposal.proposed_at = now;
+    proposal.eta = 0;
+    proposal.approval_count = 1;
+    proposal.approvals = [Pubkey::default(); MAX_MEMBERS];
+    proposal.approvals[0] = proposer;
+    proposal.bump = ctx.bumps.proposal;
+
+    emit!(ProposalCreated {
+        id,
+        action,
+        proposer,
+        delay,
+    });
+    msg!("Proposal created: id={}", id);
+
+    queue_if_approved(governance, proposal, now);
+    Ok(())
+}
+
+/// The program's upgrade authority must be the governance PDA
+fn require_governed(
+    program_data: &Option<Account<ProgramData>>,
+    program: &Pubkey,
+    authority: Option<Pubkey>,
+) -> Result<()> {
+    let program_data = program_data
+        .as_ref()
+        .filter(|account| account.key() == program_data_address(program))
+        .ok_or(GovernanceError::ProgramNotGoverned)?;
+    require!(
+        program_data.upgrade_authority_address == authority,
+        GovernanceError::ProgramNotGoverned
+    );
+    Ok(())
+}
+
+/// Start the timelock once the threshold is met
+fn queue_if_approved(governance: &Governance, proposal: &mut Proposal, now: i64) {
+    if proposal.is_queued() || proposal.approval_count < governance.threshold {
+        return;
+    }
+    proposal.eta = now + proposal.delay;
+
+    emit!(ProposalQueued {
+        id: proposal.id,
+        eta: proposal.eta,
+    });
+    msg!("Proposal queued: id={}, eta={}", proposal.id, proposal.eta);
+}
+
+#[derive(Accounts)]
+pub struct Approve<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &proposal.id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub member: Signer<'info>,
+}
+
+pub fn approve(ctx: Context<Approve>) -> Result<()> {
+    let governance = &ctx.accounts.governance;
+    let member = ctx.accounts.member.key();
+    let proposal = &mut ctx.accounts.proposal;
+
+    require!(governance.is_member(&member), GovernanceError::NotMember);
+    require!(
+        proposal.config_version == governance.config_version,
+        GovernanceError::StaleProposal
+    );
+    require!(
+        !proposal.has_approved(&member),
+        GovernanceError::AlreadyApproved
+    );
+
+    // Members are unique, so approvals never exceed MAX_MEMBERS
+    let idx = proposal.approval_count as usize;
+    proposal.approvals[idx] = member;
+    proposal.approval_count += 1;
+
+    emit!(ProposalApproved {
+        id: proposal.id,
+        member,
+        approvals: proposal.approval_count,
+    });
+    msg!(
+        "Proposal approved: id={}, approvals={}",
+        proposal.id,
+        proposal.approval_count
+    );
+
+    queue_if_approved(governance, proposal, Clock::get()?.unix_timestamp);
+    Ok(())
+}
+
+/// Close a withdrawn or stale proposal
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &proposal.id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Receives the proposal rent; checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    let caller = ctx.accounts.caller.key();
+
+    // The proposer can withdraw at any time; anyone can clean up proposals
+    // made stale by a configuration change
+    let can_close = caller == proposal.proposer
+        || proposal.config_version != ctx.accounts.governance.config_version;
+    require!(can_close, GovernanceError::ProposalStillActive);
+
+    emit!(ProposalClosed {
+        id: proposal.id,
+        closed_by: caller,
+    });
+    msg!("Proposal closed: id={}", proposal.id);
+    Ok(())
+}
+
+/// Queued, past its timelock and created under the current configuration
+fn require_executable(governance: &Governance, proposal: &Proposal) -> Result<()> {
+    require!(
+        proposal.config_version == governance.config_version,
+        GovernanceError::StaleProposal
+    );
+    require!(proposal.is_queued(), GovernanceError::ProposalNotQueued);
+    require!(
+        proposal.is_ready(Clock::get()?.unix_timestamp),
+        GovernanceError::TimelockActive
+    );
+    Ok(())
+}
+
+fn emit_executed(proposal: &Proposal, executor: Pubkey) {
+    emit!(ProposalExecuted {
+        id: proposal.id,
+        action: proposal.action.clone(),
+        executor,
+    });
+    msg!("Proposal executed: id={}", proposal.id);
+}
+
+// ============ Execute ============
+
+#[derive(Accounts)]
+pub struct ExecuteUpgrade<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &proposal.id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Receives the proposal rent; checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// CHECK: PDA only; signs the upgrade
+    #[account(
+        seeds = [UPGRADE_AUTHORITY_SEED],
+        bump = governance.upgrade_authority_bump
+    )]
+    pub upgrade_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Matched against the proposal; validated by the loader
+    #[account(mut)]
+    pub program: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the loader against the program
+    #[account(mut)]
+    pub program_data: UncheckedAccount<'info>,
+
+    /// CHECK: Matched against the proposal; validated by the loader
+    #[account(mut)]
+    pub buffer: UncheckedAccount<'info>,
+
+    /// CHECK: Matched against the proposal; receives the buffer lamports
+    #[account(mut)]
+    pub spill: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    /// CHECK: The BPF upgradeable loader
+    #[account(address = bpf_loader_upgradeable::ID)]
+    pub bpf_loader_upgradeable: UncheckedAccount<'info>,
+
+    /// Anyone can execute a ready proposal
+    pub executor: Signer<'info>,
+}
+
+pub fn execute_upgrade(ctx: Context<ExecuteUpgrade>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require_executable(&accounts.governance, &accounts.proposal)?;
+
+    let ProposalAction::Upgrade {
+        program,
+        buffer,
+        spill,
+    } = accounts.proposal.action
+    else {
+        return err!(GovernanceError::ActionMismatch);
+    };
+    require!(
+        accounts.program.key() == program
+            && accounts.buffer.key() == buffer
+            && accounts.spill.key() == spill,
+        GovernanceError::ActionMismatch
+    );
+
+    let ix = bpf_loader_upgradeable::upgrade(
+        &program,
+        &buffer,
+        &accounts.upgrade_authority.key(),
+        &spill,
+    );
+    let seeds = &[
+        UPGRADE_AUTHORITY_SEED,
+        &[accounts.governance.upgrade_authority_bump],
+    ];
+    invoke_signed(
+        &ix,
+        &[
+            accounts.program_data.to_account_info(),
+            accounts.program.to_account_info(),
+            accounts.buffer.to_account_info(),
+            accounts.spill.to_account_info(),
+            accounts.rent.to_account_info(),
+            accounts.clock.to_account_info(),
+            accounts.upgrade_authority.to_account_info(),
+            accounts.bpf_loader_upgradeable.to_account_info(),
+        ],
+        &[&seeds[..]],
+    )?;
+
+    emit_executed(&accounts.proposal, accounts.executor.key());
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSetUpgradeAuthority<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &proposal.id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Receives the proposal rent; checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// CHECK: PDA only; signs the authority change
+    #[account(
+        seeds = [UPGRADE_AUTHORITY_SEED],
+        bump = governance.upgrade_authority_bump
+    )]
+    pub upgrade_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Derived from the proposal's program; validated by the loader
+    #[account(mut)]
+    pub program_data: UncheckedAccount<'info>,
+
+    /// CHECK: Matched against the proposal (omitted to make the program immutable)
+    pub new_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The BPF upgradeable loader
+    #[account(address = bpf_loader_upgradeable::ID)]
+    pub bpf_loader_upgradeable: UncheckedAccount<'info>,
+
+    /// Anyone can execute a ready proposal
+    pub executor: Signer<'info>,
+}
+
+pub fn execute_set_upgrade_authority(ctx: Context<ExecuteSetUpgradeAuthority>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    require_executable(&accounts.governance, &accounts.proposal)?;
+
+    let ProposalAction::SetUpgradeAuthority {
+        program,
+        new_authority,
+    } = accounts.proposal.action
+    else {
+        return err!(GovernanceError::ActionMismatch);
+    };
+    require!(
+        accounts.program_data.key() == program_data_address(&program)
+            && accounts.new_authority.as_ref().map(|a| a.key()) == new_authority,
+        GovernanceError::ActionMismatch
+    );
+
+    let ix = bpf_loader_upgradeable::set_upgrade_authority(
+        &program,
+        &accounts.upgrade_authority.key(),
+        new_authority.as_ref(),
+    );
+    let mut infos = vec![
+        accounts.program_data.to_account_info(),
+        accounts.upgrade_authority.to_account_info(),
+        accounts.bpf_loader_upgradeable.to_account_info(),
+    ];
+    if let Some(new_authority) = &accounts.new_authority {
+        infos.push(new_authority.to_account_info());
+    }
+    let seeds = &[
+        UPGRADE_AUTHORITY_SEED,
+        &[accounts.governance.upgrade_authority_bump],
+    ];
+    invoke_signed(&ix, &infos, &[&seeds[..]])?;
+
+    emit_executed(&accounts.proposal, accounts.executor.key());
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSetConfig<'info> {
+    #[account(
+        mut,
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &proposal.id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Receives the proposal rent; checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Anyone can execute a ready proposal
+    pub executor: Signer<'info>,
+}
+
+/// Bumping config_version makes every other open proposal stale
+pub fn execute_set_config(ctx: Context<ExecuteSetConfig>) -> Result<()> {
+    require_executable(&ctx.accounts.governance, &ctx.accounts.proposal)?;
+
+    let ProposalAction::SetConfig {
+        members,
+        threshold,
+        min_delay,
+    } = ctx.accounts.proposal.action.clone()
+    else {
+        return err!(GovernanceError::ActionMismatch);
+    };
+
+    let governance = &mut ctx.accounts.governance;
+    governance.set_members(&members);
+    governance.threshold = threshold;
+    governance.min_delay = min_delay;
+    governance.config_version += 1;
+
+    emit!(ConfigChanged {
+        members,
+        threshold,
+        min_delay,
+        config_version: governance.config_version,
+    });
+    emit_executed(&ctx.accounts.proposal, ctx.accounts.executor.key());
+    Ok(())
+}
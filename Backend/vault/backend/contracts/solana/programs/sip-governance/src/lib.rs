@@ -0,0 +1,82 @@
+//! SIP Governance Program for Solana
+//!
+//! Holds the upgrade authority of the SIP custody programs (sip_vault,
+//! sip_settlement, obscura_vault) behind a multisig and a timelock:
+//! - Members propose upgrades from buffers held by the governance authority
+//! - Proposals are queued once enough members approve
+//! - Queued proposals execute after their delay through the upgradeable loader
+//!
+//! Key security properties:
+//! - No single key can upgrade a governed program
+//! - Every upgrade is public for at least the minimum delay before it lands
+//! - A proposed buffer cannot be rewritten; only the governance PDA can write it
+
+use anchor_lang::prelude::*;
+
+#[cfg(not(feature = "no-entrypoint"))]
+use solana_security_txt::security_txt;
+
+#[cfg(not(feature = "no-entrypoint"))]
+security_txt! {
+    name: "SIP Governance",
+    project_url: "https://daemonprotocol.com",
+    contacts: "email:admin@daemonprotocol.com",
+    policy: "https://daemonprotocol.com/security",
+    preferred_languages: "en",
+    auditors: "None"
+}
+
+declare_id!("Gov22VUqZhQq7r3DACDu7GXK3PszXgeC5urVg3YHSPXL");
+
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod state;
+
+use instructions::*;
+use state::ProposalAction;
+
+#[program]
+pub mod sip_governance {
+    use super::*;
+
+    /// Initialize governance with its members, threshold and minimum delay
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+        min_delay: i64,
+    ) -> Result<()> {
+        instructions::initialize(ctx, members, threshold, min_delay)
+    }
+
+    /// Propose an action (member); the proposer's approval is counted
+    pub fn propose(ctx: Context<Propose>, action: ProposalAction, delay: i64) -> Result<()> {
+        instructions::propose(ctx, action, delay)
+    }
+
+    /// Approve a proposal (member); queues it once the threshold is met
+    pub fn approve(ctx: Context<Approve>) -> Result<()> {
+        instructions::approve(ctx)
+    }
+
+    /// Withdraw a proposal (proposer) or clean up a stale one (anyone)
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        instructions::close_proposal(ctx)
+    }
+
+    /// Upgrade a governed program once its proposal's timelock has elapsed
+    pub fn execute_upgrade(ctx: Context<ExecuteUpgrade>) -> Result<()> {
+        instructions::execute_upgrade(ctx)
+    }
+
+    /// Hand a governed program to a new upgrade authority after the timelock
+    pub fn execute_set_upgrade_authority(ctx: Context<ExecuteSetUpgradeAuthority>) -> Result<()> {
+        instructions::execute_set_upgrade_authority(ctx)
+    }
+
+    /// Replace members, threshold and minimum delay after the timelock
+    pub fn execute_set_config(ctx: Context<ExecuteSetConfig>) -> Result<()> {
+        instructions::execute_set_config(ctx)
+    }
+}
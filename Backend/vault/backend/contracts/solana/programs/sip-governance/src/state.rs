@@ -0,0 +1,167 @@
+//! Governance state definitions
+
+use anchor_lang::prelude::*;
+
+/// Maximum number of governance members
+pub const MAX_MEMBERS: usize = 10;
+
+/// Longest timelock a proposal can request (30 days)
+pub const MAX_DELAY: i64 = 30 * 24 * 60 * 60;
+
+/// Governance configuration (multisig members and timelock)
+#[account]
+pub struct Governance {
+    /// Members who can propose and approve
+    pub members: [Pubkey; MAX_MEMBERS],
+
+    /// Number of members in use
+    pub member_count: u8,
+
+    /// Approvals required before a proposal is queued
+    pub threshold: u8,
+
+    /// Minimum seconds between queueing and execution
+    pub min_delay: i64,
+
+    /// Proposal counter (next proposal id)
+    pub proposal_count: u64,
+
+    /// Bumped on every configuration change; older proposals become stale
+    pub config_version: u64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Bump seed of the upgrade authority PDA
+    pub upgrade_authority_bump: u8,
+}
+
+impl Governance {
+    pub const LEN: usize = 8 + // discriminator
+        (32 * MAX_MEMBERS) + // members
+        1 +  // member_count
+        1 +  // threshold
+        8 +  // min_delay
+        8 +  // proposal_count
+        8 +  // config_version
+        1 +  // bump
+        1;   // upgrade_authority_bump
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members[..self.member_count as usize].contains(key)
+    }
+
+    /// Members must be non-empty, unique and meet the threshold
+    pub fn is_valid_config(members: &[Pubkey], threshold: u8, min_delay: i64) -> bool {
+        let unique = members
+            .iter()
+            .enumerate()
+            .all(|(i, m)| *m != Pubkey::default() && !members[..i].contains(m));
+        !members.is_empty()
+            && members.len() <= MAX_MEMBERS
+            && unique
+            && threshold >= 1
+            && threshold as usize <= members.len()
+            && (0..=MAX_DELAY).contains(&min_delay)
+    }
+
+    pub fn set_members(&mut self, members: &[Pubkey]) {
+        self.members = [Pubkey::default(); MAX_MEMBERS];
+        self.members[..members.len()].copy_from_slice(members);
+        self.member_count = members.len() as u8;
+    }
+}
+
+/// Action a proposal performs when executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ProposalAction {
+    /// Upgrade a governed program from a buffer held by the upgrade authority
+    Upgrade {
+        program: Pubkey,
+        buffer: Pubkey,
+        /// Receives the buffer's lamports
+        spill: Pubkey,
+    },
+
+    /// Hand a governed program to another authority (None makes it immutable)
+    SetUpgradeAuthority {
+        program: Pubkey,
+        new_authority: Option<Pubkey>,
+    },
+
+    /// Replace the members, threshold and minimum delay
+    SetConfig {
+        members: Vec<Pubkey>,
+        threshold: u8,
+        min_delay: i64,
+    },
+}
+
+impl ProposalAction {
+    /// Serialized size of the largest variant (SetConfig with MAX_MEMBERS)
+    pub const LEN: usize = 1 + // variant
+        4 + (32 * MAX_MEMBERS) + // members
+        1 +  // threshold
+        8;   // min_delay
+}
+
+/// Pending governance proposal
+#[account]
+pub struct Proposal {
+    /// Proposal id (seed)
+    pub id: u64,
+
+    /// Action performed on execution
+    pub action: ProposalAction,
+
+    /// Member who proposed (receives rent on close)
+    pub proposer: Pubkey,
+
+    /// Governance config_version the proposal was created under
+    pub config_version: u64,
+
+    /// Seconds between queueing and execution (at least min_delay)
+    pub delay: i64,
+
+    /// Timestamp when proposed
+    pub proposed_at: i64,
+
+    /// Earliest execution time (zero until the threshold is met)
+    pub eta: i64,
+
+    /// Number of approvals
+    pub approval_count: u8,
+
+    /// Members who approved (proposer first)
+    pub approvals: [Pubkey; MAX_MEMBERS],
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + // discriminator
+        8 +  // id
+        ProposalAction::LEN + // action
+        32 + // proposer
+        8 +  // config_version
+        8 +  // delay
+        8 +  // proposed_at
+        8 +  // eta
+        1 +  // approval_count
+        (32 * MAX_MEMBERS) + // approvals
+        1;   // bump
+
+    pub fn has_approved(&self, member: &Pubkey) -> bool {
+        self.approvals[..self.approval_count as usize].contains(member)
+    }
+
+    pub fn is_queued(&self) -> bool {
+        self.eta != 0
+    }
+
+    /// Queued and past its timelock
+    pub fn is_ready(&self, now: i64) -> bool {
+        self.is_queued() && now >= self.eta
+    }
+}
@@ -0,0 +1,73 @@
+//! Guardian attestations for roots mirrored from the EVM SIPSettlement
+//!
+//! Guardians sign `keccak256(ATTESTATION_DOMAIN || target_domain ||
+//! source_chain_id || source_contract || source_batch_id || root || depth)`
+//! with secp256k1 (integers big-endian), where `target_domain` is the
+//! receiving deployment's domain separator. A guardian is identified by its
+//! Ethereum address, the last 20 bytes of keccak256 of its uncompressed
+//! public key.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::secp256k1_recover::secp256k1_recover;
+
+/// Domain tag of root attestations
+pub const ATTESTATION_DOMAIN: &[u8] = b"SIP_ROOT_ATTESTATION_V2";
+
+/// Ethereum address of a guardian key
+pub type EthAddress = [u8; 20];
+
+/// One guardian's signature over an attestation digest
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GuardianSignature {
+    /// Position of the signer in the guardian set
+    pub guardian_index: u8,
+
+    /// Compact signature (r || s)
+    pub signature: [u8; 64],
+
+    /// Recovery id (0 or 1; 27/28 are accepted as well)
+    pub recovery_id: u8,
+}
+
+/// Digest the guardians sign for an EVM batch root
+///
+/// `target_domain` binds the signature to one cluster and program, so it
+/// cannot be replayed into another deployment following the same contract.
+pub fn attestation_digest(
+    target_domain: &[u8; 32],
+    source_chain_id: u64,
+    source_contract: &EthAddress,
+    source_batch_id: u64,
+    root: &[u8; 32],
+    depth: u8,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        ATTESTATION_DOMAIN,
+        target_domain,
+        &source_chain_id.to_be_bytes(),
+        source_contract,
+        &source_batch_id.to_be_bytes(),
+        root,
+        &[depth],
+    ])
+    .to_bytes()
+}
+
+/// Ethereum address of an uncompressed public key (x || y)
+pub fn eth_address(public_key: &[u8; 64]) -> EthAddress {
+    let hash = keccak::hash(public_key).to_bytes();
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// Recover the address that produced `signature` over `digest`
+pub fn recover_signer(digest: &[u8; 32], signature: &GuardianSignature) -> Option<EthAddress> {
+    let recovery_id = match signature.recovery_id {
+        27 | 28 => signature.recovery_id - 27,
+        id => id,
+    };
+    let public_key = secp256k1_recover(digest, recovery_id, &signature.signature).ok()?;
+    Some(eth_address(&public_key.to_bytes()))
+}
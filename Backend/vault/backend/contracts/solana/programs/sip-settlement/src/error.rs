@@ -36,4 +36,130 @@ pub enum SipError {
 
     #[msg("No pending authority transfer")]
     NoPendingTransfer,
+
+    #[msg("Invalid tree depth: must be between 1 and 32")]
+    InvalidTreeDepth,
+
+    #[msg("Proof length does not match the batch tree depth")]
+    ProofLengthMismatch,
+
+    #[msg("Leaf index out of range for the batch tree depth")]
+    LeafIndexOutOfRange,
+
+    #[msg("Batch root is not in the legacy layout")]
+    NotLegacyBatchRoot,
+
+    #[msg("Invalid hash function")]
+    InvalidHashFunction,
+
+    #[msg("Settlement state is not in the legacy layout")]
+    NotLegacySettlementState,
+
+    #[msg("Root updates require executor quorum: use propose_root")]
+    QuorumRequired,
+
+    #[msg("Invalid threshold: must be between 1 and 16")]
+    InvalidThreshold,
+
+    #[msg("Invalid proposal lifetime")]
+    InvalidProposalTtl,
+
+    #[msg("Root proposal has expired")]
+    ProposalExpired,
+
+    #[msg("Root proposal targets a batch that is no longer next")]
+    StaleProposal,
+
+    #[msg("Executor has already approved this root")]
+    AlreadyApproved,
+
+    #[msg("Not enough executor approvals")]
+    ThresholdNotMet,
+
+    #[msg("Maximum approvals reached")]
+    MaxApprovalsReached,
+
+    #[msg("Root proposal is still active")]
+    ProposalStillActive,
+
+    #[msg("Settlement is paused")]
+    SettlementPaused,
+
+    #[msg("Invalid role")]
+    InvalidRole,
+
+    #[msg("Executor rate limit exceeded")]
+    RateLimitExceeded,
+
+    #[msg("Invalid executor label")]
+    InvalidLabel,
+
+    #[msg("Invalid executor record")]
+    InvalidExecutorRecord,
+
+    #[msg("Token releases need both token accounts and the token program")]
+    MissingTokenAccounts,
+
+    #[msg("Invalid guardian set")]
+    InvalidGuardianSet,
+
+    #[msg("Invalid guardian signature")]
+    InvalidGuardianSignature,
+
+    #[msg("Guardian signatures must be sorted by index without duplicates")]
+    DuplicateGuardianSignature,
+
+    #[msg("Not enough guardian signatures")]
+    InsufficientGuardianSignatures,
+
+    #[msg("Attestation is for an already mirrored batch")]
+    StaleAttestation,
+
+    #[msg("Too many WOTS signature elements")]
+    WotsSignatureTooLong,
+
+    #[msg("WOTS signature is not fully written")]
+    WotsSignatureIncomplete,
+
+    #[msg("Invalid WOTS signature")]
+    InvalidWotsSignature,
+
+    #[msg("WOTS signature has not been verified")]
+    WotsNotVerified,
+
+    #[msg("WOTS leaves need a keccak deployment and a prefixed batch")]
+    WotsLeafUnsupported,
+
+    #[msg("Invalid key tree")]
+    InvalidKeyTree,
+
+    #[msg("WOTS key is not in the key tree")]
+    WotsKeyNotInTree,
+
+    #[msg("Key tree does not match the bound key")]
+    KeyTreeMismatch,
+
+    #[msg("Batch has expired")]
+    BatchExpired,
+
+    #[msg("Batch has not expired")]
+    BatchNotExpired,
+
+    #[msg("Invalid batch lifetime")]
+    InvalidBatchTtl,
+
+    #[msg("Domain already set")]
+    DomainAlreadySet,
+
+    #[msg("Invalid genesis hash")]
+    InvalidGenesisHash,
+
+    #[msg("Release leaves need a keccak deployment and a prefixed batch")]
+    ReleaseLeafUnsupported,
+
+    #[msg("Domain is not set")]
+    DomainNotSet,
+
+    #[msg("WOTS signature is not over the key's burn message")]
+    InvalidBurnMessage,
 }
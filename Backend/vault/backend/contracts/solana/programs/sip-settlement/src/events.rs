@@ -0,0 +1,164 @@
+//! Program events
+//!
+//! Typed records of every state change for indexers. Events carry only what
+//! is already public on-chain (roots, commitments, keys); no amounts or
+//! recipients.
+
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct RootUpdated {
+    pub batch_id: u64,
+    pub root: [u8; 32],
+    pub depth: u8,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct RootMirrored {
+    pub batch_id: u64,
+    pub source_batch_id: u64,
+    pub root: [u8; 32],
+    pub signatures: u8,
+}
+
+#[event]
+pub struct GuardianSetChanged {
+    pub source_chain_id: u64,
+    pub guardian_count: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct RootProposed {
+    pub root: [u8; 32],
+    pub target_batch_id: u64,
+    pub proposer: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct RootApproved {
+    pub root: [u8; 32],
+    pub target_batch_id: u64,
+    pub executor: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct RootProposalClosed {
+    pub root: [u8; 32],
+    pub target_batch_id: u64,
+    pub closed_by: Pubkey,
+}
+
+#[event]
+pub struct RootQuorumChanged {
+    pub threshold: u8,
+    pub proposal_ttl: i64,
+}
+
+#[event]
+pub struct BatchTtlChanged {
+    pub batch_ttl: i64,
+}
+
+#[event]
+pub struct BatchRootClosed {
+    pub batch_id: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct DomainSet {
+    pub genesis_hash: [u8; 32],
+    pub domain: [u8; 32],
+}
+
+#[event]
+pub struct CommitmentSettled {
+    pub commitment: [u8; 32],
+    pub batch_id: u64,
+}
+
+#[event]
+pub struct WotsVerified {
+    pub commitment: [u8; 32],
+    pub public_key_hash: [u8; 32],
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct KeyTreeRegistered {
+    pub owner: Pubkey,
+    pub root: [u8; 32],
+    pub depth: u8,
+}
+
+#[event]
+pub struct WotsKeyConsumed {
+    pub public_key_hash: [u8; 32],
+    pub key_tree: Pubkey,
+    pub commitment: [u8; 32],
+}
+
+#[event]
+pub struct ExecutorAdded {
+    pub executor: Pubkey,
+    pub added_by: Pubkey,
+    pub rate_limit: u16,
+}
+
+#[event]
+pub struct ExecutorUpdated {
+    pub executor: Pubkey,
+    pub rate_limit: u16,
+}
+
+#[event]
+pub struct ExecutorRemoved {
+    pub executor: Pubkey,
+    pub removed_by: Pubkey,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub role: u8,
+    pub holder: Pubkey,
+    pub granted_by: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub role: u8,
+    pub holder: Pubkey,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferStarted {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferAccepted {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct Paused {
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct Unpaused {
+    pub admin: Pubkey,
+}
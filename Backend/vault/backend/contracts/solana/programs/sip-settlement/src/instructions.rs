@@ -1,329 +1,2251 @@
-//! Program instructions
-
-use anchor_lang::prelude::*;
-use anchor_lang::solana_program::keccak;
-
-use crate::error::SipError;
-use crate::state::*;
-
-/// Seeds for the settlement state PDA
-pub const SETTLEMENT_SEED: &[u8] = b"settlement";
-pub const COMMITMENT_SEED: &[u8] = b"commitment";
-pub const BATCH_SEED: &[u8] = b"batch";
-
-/// Initialize the settlement state
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = SettlementState::LEN,
-        seeds = [SETTLEMENT_SEED],
-        bump
-    )]
-    pub settlement_state: Account<'info, SettlementState>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-    let state = &mut ctx.accounts.settlement_state;
-    state.authority = ctx.accounts.authority.key();
-    state.pending_authority = Pubkey::default();
-    state.current_root = [0u8; 32];
-    state.batch_id = 0;
-    state.executor_count = 0;
-    state.executors = [Pubkey::default(); MAX_EXECUTORS];
-    state.bump = ctx.bumps.settlement_state;
-
-    msg!("Settlement state initialized");
-    Ok(())
-}
-
-/// Update the Merkle root
-#[derive(Accounts)]
-#[instruction(new_root: [u8; 32])]
-pub struct UpdateRoot<'info> {
-    #[account(
-        mut,
-        seeds = [SETTLEMENT_SEED],
-        bump = settlement_state.bump
-    )]
-    pub settlement_state: Account<'info, SettlementState>,
-
-    #[account(
-        init,
-        payer = executor,
-        space = BatchRoot::LEN,
-        seeds = [BATCH_SEED, &(settlement_state.batch_id + 1).to_le_bytes()],
-        bump
-    )]
-    pub batch_root: Account<'info, BatchRoot>,
-
-    #[account(mut)]
-    pub executor: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-pub fn update_root(ctx: Context<UpdateRoot>, new_root: [u8; 32]) -> Result<()> {
-    let state = &ctx.accounts.settlement_state;
-    
-    // Check executor authorization
-    require!(
-        state.is_executor(&ctx.accounts.executor.key()),
-        SipError::Unauthorized
-    );
-
-    // Check root is not zero
-    require!(new_root != [0u8; 32], SipError::InvalidRoot);
-
-    // Update state
-    let state = &mut ctx.accounts.settlement_state;
-    state.batch_id += 1;
-    state.current_root = new_root;
-
-    // Store batch root record
-    let batch = &mut ctx.accounts.batch_root;
-    batch.batch_id = state.batch_id;
-    batch.root = new_root;
-    batch.created_at = Clock::get()?.unix_timestamp;
-    batch.executor = ctx.accounts.executor.key();
-    batch.bump = ctx.bumps.batch_root;
-
-    msg!("Root updated: batch_id={}", state.batch_id);
-    Ok(())
-}
-
-/// Settle a commitment
-#[derive(Accounts)]
-#[instruction(commitment: [u8; 32], proof: Vec<[u8; 32]>, leaf_index: u64)]
-pub struct Settle<'info> {
-    #[account(
-        seeds = [SETTLEMENT_SEED],
-        bump = settlement_state.bump
-    )]
-    pub settlement_state: Account<'info, SettlementState>,
-
-    #[account(
-        init,
-        payer = payer,
-        space = UsedCommitment::LEN,
-        seeds = [COMMITMENT_SEED, &commitment],
-        bump
-    )]
-    pub used_commitment: Account<'info, UsedCommitment>,
-
-    #[account(mut)]
-    pub payer: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-pub fn settle(
-    ctx: Context<Settle>,
-    commitment: [u8; 32],
-    proof: Vec<[u8; 32]>,
-    leaf_index: u64,
-) -> Result<()> {
-    let state = &ctx.accounts.settlement_state;
-
-    // Validate proof length
-    require!(!proof.is_empty(), SipError::EmptyProof);
-    require!(proof.len() <= MAX_PROOF_LENGTH, SipError::ProofTooLong);
-
-    // Verify Merkle proof
-    let valid = verify_merkle_proof(&commitment, &proof, leaf_index, &state.current_root);
-    require!(valid, SipError::InvalidProof);
-
-    // Mark as used (the account creation acts as replay protection)
-    let used = &mut ctx.accounts.used_commitment;
-    used.commitment = commitment;
-    used.batch_id = state.batch_id;
-    used.settled_at = Clock::get()?.unix_timestamp;
-    used.executor = ctx.accounts.payer.key();
-    used.bump = ctx.bumps.used_commitment;
-
-    msg!("Commitment settled");
-    Ok(())
-}
-
-/// Manage executor (add/remove)
-#[derive(Accounts)]
-pub struct ManageExecutor<'info> {
-    #[account(
-        mut,
-        seeds = [SETTLEMENT_SEED],
-        bump = settlement_state.bump,
-        has_one = authority
-    )]
-    pub settlement_state: Account<'info, SettlementState>,
-
-    pub authority: Signer<'info>,
-}
-
-pub fn add_executor(ctx: Context<ManageExecutor>, executor: Pubkey) -> Result<()> {
-    let state = &mut ctx.accounts.settlement_state;
-
-    // Check not already present
-    for i in 0..state.executor_count as usize {
-        require!(state.executors[i] != executor, SipError::ExecutorAlreadyExists);
-    }
-
-    // Check capacity
-    require!(
-        (state.executor_count as usize) < MAX_EXECUTORS,
-        SipError::MaxExecutorsReached
-    );
-
-    // Add executor
-    let idx = state.executor_count as usize;
-    state.executors[idx] = executor;
-    state.executor_count += 1;
-
-    msg!("Executor added: {}", executor);
-    Ok(())
-}
-
-pub fn remove_executor(ctx: Context<ManageExecutor>, executor: Pubkey) -> Result<()> {
-    let state = &mut ctx.accounts.settlement_state;
-
-    // Find executor index
-    let mut found_idx: Option<usize> = None;
-    for i in 0..state.executor_count as usize {
-        if state.executors[i] == executor {
-            found_idx = Some(i);
-            break;
-        }
-    }
-
-    let idx = found_idx.ok_or(SipError::ExecutorNotFound)?;
-
-    // Swap with last and decrement count (more efficient than shifting)
-    let last_idx = state.executor_count as usize - 1;
-    if idx != last_idx {
-        state.executors[idx] = state.executors[last_idx];
-    }
-    state.executors[last_idx] = Pubkey::default();
-    state.executor_count -= 1;
-
-    msg!("Executor removed: {}", executor);
-    Ok(())
-}
-
-/// Transfer authority (two-step pattern)
-#[derive(Accounts)]
-pub struct TransferAuthority<'info> {
-    #[account(
-        mut,
-        seeds = [SETTLEMENT_SEED],
-        bump = settlement_state.bump,
-        has_one = authority
-    )]
-    pub settlement_state: Account<'info, SettlementState>,
-
-    pub authority: Signer<'info>,
-}
-
-pub fn transfer_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
-    require!(new_authority != Pubkey::default(), SipError::InvalidPendingAuthority);
-    
-    let state = &mut ctx.accounts.settlement_state;
-    state.pending_authority = new_authority;
-
-    msg!("Authority transfer initiated to: {}", new_authority);
-    Ok(())
-}
-
-/// Accept authority transfer
-#[derive(Accounts)]
-pub struct AcceptAuthority<'info> {
-    #[account(
-        mut,
-        seeds = [SETTLEMENT_SEED],
-        bump = settlement_state.bump
-    )]
-    pub settlement_state: Account<'info, SettlementState>,
-
-    pub new_authority: Signer<'info>,
-}
-
-pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
-    let state = &mut ctx.accounts.settlement_state;
-    
-    require!(
-        state.pending_authority != Pubkey::default(),
-        SipError::NoPendingTransfer
-    );
-    require!(
-        ctx.accounts.new_authority.key() == state.pending_authority,
-        SipError::Unauthorized
-    );
-
-    let old_authority = state.authority;
-    state.authority = state.pending_authority;
-    state.pending_authority = Pubkey::default();
-
-    msg!("Authority transferred from {} to {}", old_authority, state.authority);
-    Ok(())
-}
-
-/// Cancel authority transfer
-#[derive(Accounts)]
-pub struct CancelAuthorityTransfer<'info> {
-    #[account(
-        mut,
-        seeds = [SETTLEMENT_SEED],
-        bump = settlement_state.bump,
-        has_one = authority
-    )]
-    pub settlement_state: Account<'info, SettlementState>,
-
-    pub authority: Signer<'info>,
-}
-
-pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
-    let state = &mut ctx.accounts.settlement_state;
-    state.pending_authority = Pubkey::default();
-
-    msg!("Authority transfer cancelled");
-    Ok(())
-}
-
-/// Verify Merkle proof
-/// 
-/// Matches the TypeScript implementation with 0x01 prefix for internal nodes
-fn verify_merkle_proof(
-    leaf: &[u8; 32],
-    proof: &[[u8; 32]],
-    mut index: u64,
-    root: &[u8; 32],
-) -> bool {
-    let mut computed_hash = *leaf;
-
-    for sibling in proof {
-        if index & 1 == 1 {
-            // Current is right child
-            computed_hash = hash_pair(sibling, &computed_hash);
-        } else {
-            // Current is left child
-            computed_hash = hash_pair(&computed_hash, sibling);
-        }
-        index >>= 1;
-    }
-
-    computed_hash == *root
-}
-
-/// Hash two nodes together with domain separation
-/// Matches TypeScript: prefix with 0x01
-fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
-    let mut data = [0u8; 65];
-    data[0] = 0x01;
-    data[1..33].copy_from_slice(left);
-    data[33..65].copy_from_slice(right);
-    keccak::hash(&data).to_bytes()
-}
+//! Program instructions
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::token::Token;
+use obscura_merkle::{domain_separator, Hasher, Keccak, Poseidon};
+use obscura_wots::key_tree::{verify_key_proof, MAX_KEY_TREE_DEPTH};
+use sip_vault::program::SipVault;
+
+use crate::attestation::{attestation_digest, recover_signer, GuardianSignature};
+use crate::error::SipError;
+use crate::events::*;
+use crate::state::*;
+
+/// Seeds for the settlement state PDA
+pub const SETTLEMENT_SEED: &[u8] = b"settlement";
+pub const COMMITMENT_SEED: &[u8] = b"commitment";
+pub const BATCH_SEED: &[u8] = b"batch";
+pub const PENDING_ROOT_SEED: &[u8] = b"pending_root";
+pub const ROLE_SEED: &[u8] = b"role";
+pub const EXECUTOR_SEED: &[u8] = b"executor";
+pub const GUARDIAN_SET_SEED: &[u8] = b"guardian_set";
+pub const WOTS_SEED: &[u8] = b"wots";
+pub const KEY_TREE_SEED: &[u8] = b"key_tree";
+pub const CONSUMED_KEY_SEED: &[u8] = b"consumed_key";
+
+/// Initialize the settlement state
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = SettlementState::LEN,
+        seeds = [SETTLEMENT_SEED],
+        bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize(ctx: Context<Initialize>, hash_function: u8) -> Result<()> {
+    require!(is_valid_hash_function(hash_function), SipError::InvalidHashFunction);
+
+    let state = &mut ctx.accounts.settlement_state;
+    state.authority = ctx.accounts.authority.key();
+    state.pending_authority = Pubkey::default();
+    state.current_root = [0u8; 32];
+    state.batch_id = 0;
+    state.executor_count = 0;
+    state.executors = [Pubkey::default(); MAX_EXECUTORS];
+    state.bump = ctx.bumps.settlement_state;
+    state.hash_function = hash_function;
+    state.root_threshold = 1;
+    state.proposal_ttl = DEFAULT_PROPOSAL_TTL;
+    state.paused = false;
+    state.registered_executors = 0;
+    state.batch_ttl = 0;
+    state.domain = [0u8; 32];
+
+    msg!("Settlement state initialized: hash_function={}", hash_function);
+    Ok(())
+}
+
+/// Update the Merkle root
+#[derive(Accounts)]
+#[instruction(new_root: [u8; 32])]
+pub struct UpdateRoot<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = BatchRoot::LEN,
+        seeds = [BATCH_SEED, &(settlement_state.batch_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub batch_root: Account<'info, BatchRoot>,
+
+    /// Signer's executor record (authorization is its existence)
+    #[account(
+        mut,
+        seeds = [EXECUTOR_SEED, executor.key().as_ref()],
+        bump = executor_record.bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_root(ctx: Context<UpdateRoot>, new_root: [u8; 32], depth: u8) -> Result<()> {
+    let state = &ctx.accounts.settlement_state;
+    
+    // Executor authorization is the executor_record account
+    require!(!state.paused, SipError::SettlementPaused);
+
+    // A single executor may only update the root when no quorum is configured
+    require!(state.root_threshold <= 1, SipError::QuorumRequired);
+
+    // Check root is not zero
+    require!(new_root != [0u8; 32], SipError::InvalidRoot);
+
+    // Depth is fixed per batch
+    require!(BatchRoot::is_valid_depth(depth), SipError::InvalidTreeDepth);
+
+    let record = &mut ctx.accounts.executor_record;
+    require!(
+        record.record_submission(Clock::get()?.unix_timestamp),
+        SipError::RateLimitExceeded
+    );
+    record.last_active_batch = state.batch_id + 1;
+
+    activate_root(
+        &mut ctx.accounts.settlement_state,
+        &mut ctx.accounts.batch_root,
+        new_root,
+        depth,
+        ctx.accounts.executor.key(),
+        ctx.bumps.batch_root,
+    )
+}
+
+/// Propose a new Merkle root for executor approval
+#[derive(Accounts)]
+#[instruction(new_root: [u8; 32])]
+pub struct ProposeRoot<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = PendingRoot::LEN,
+        seeds = [PENDING_ROOT_SEED, &new_root],
+        bump
+    )]
+    pub pending_root: Account<'info, PendingRoot>,
+
+    /// Signer's executor record (authorization is its existence)
+    #[account(
+        mut,
+        seeds = [EXECUTOR_SEED, executor.key().as_ref()],
+        bump = executor_record.bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn propose_root(ctx: Context<ProposeRoot>, new_root: [u8; 32], depth: u8) -> Result<()> {
+    let state = &ctx.accounts.settlement_state;
+    let executor = ctx.accounts.executor.key();
+
+    require!(!state.paused, SipError::SettlementPaused);
+    require!(new_root != [0u8; 32], SipError::InvalidRoot);
+    require!(BatchRoot::is_valid_depth(depth), SipError::InvalidTreeDepth);
+
+    let now = Clock::get()?.unix_timestamp;
+
+    let record = &mut ctx.accounts.executor_record;
+    require!(record.record_submission(now), SipError::RateLimitExceeded);
+    record.last_active_batch = state.batch_id + 1;
+
+    // The proposer is the first approval
+    let pending = &mut ctx.accounts.pending_root;
+    pending.root = new_root;
+    pending.depth = depth;
+    pending.target_batch_id = state.batch_id + 1;
+    pending.proposer = executor;
+    pending.proposed_at = now;
+    pending.expires_at = now + state.proposal_ttl;
+    pending.approval_count = 1;
+    pending.approvals = [Pubkey::default(); MAX_ROOT_APPROVALS];
+    pending.approvals[0] = executor;
+    pending.bump = ctx.bumps.pending_root;
+
+    emit!(RootProposed {
+        root: new_root,
+        target_batch_id: pending.target_batch_id,
+        proposer: executor,
+        expires_at: pending.expires_at,
+    });
+    msg!("Root proposed: target_batch_id={}", pending.target_batch_id);
+    Ok(())
+}
+
+/// Approve a proposed root
+#[derive(Accounts)]
+pub struct ApproveRoot<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_ROOT_SEED, &pending_root.root],
+        bump = pending_root.bump
+    )]
+    pub pending_root: Account<'info, PendingRoot>,
+
+    /// Signer's executor record (authorization is its existence)
+    #[account(
+        mut,
+        seeds = [EXECUTOR_SEED, executor.key().as_ref()],
+        bump = executor_record.bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    pub executor: Signer<'info>,
+}
+
+pub fn approve_root(ctx: Context<ApproveRoot>) -> Result<()> {
+    let state = &ctx.accounts.settlement_state;
+    let executor = ctx.accounts.executor.key();
+    let pending = &mut ctx.accounts.pending_root;
+
+    require!(!state.paused, SipError::SettlementPaused);
+    require!(
+        !pending.is_expired(Clock::get()?.unix_timestamp),
+        SipError::ProposalExpired
+    );
+    require!(
+        pending.target_batch_id == state.batch_id + 1,
+        SipError::StaleProposal
+    );
+    require!(!pending.has_approved(&executor), SipError::AlreadyApproved);
+    require!(
+        (pending.approval_count as usize) < MAX_ROOT_APPROVALS,
+        SipError::MaxApprovalsReached
+    );
+
+    let idx = pending.approval_count as usize;
+    pending.approvals[idx] = executor;
+    pending.approval_count += 1;
+    ctx.accounts.executor_record.last_active_batch = pending.target_batch_id;
+
+    emit!(RootApproved {
+        root: pending.root,
+        target_batch_id: pending.target_batch_id,
+        executor,
+        approvals: pending.approval_count,
+    });
+    msg!(
+        "Root approved: target_batch_id={}, approvals={}",
+        pending.target_batch_id,
+        pending.approval_count
+    );
+    Ok(())
+}
+
+/// Activate a proposed root once the threshold is met
+#[derive(Accounts)]
+pub struct ExecuteRoot<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_ROOT_SEED, &pending_root.root],
+        bump = pending_root.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub pending_root: Account<'info, PendingRoot>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = BatchRoot::LEN,
+        seeds = [BATCH_SEED, &(settlement_state.batch_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub batch_root: Account<'info, BatchRoot>,
+
+    /// CHECK: Receives the proposal rent; checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Signer's executor record (authorization is its existence)
+    #[account(
+        mut,
+        seeds = [EXECUTOR_SEED, executor.key().as_ref()],
+        bump = executor_record.bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: ExecutorRecords of the approvers
+}
+
+pub fn execute_root(ctx: Context<ExecuteRoot>) -> Result<()> {
+    let state = &ctx.accounts.settlement_state;
+    let pending = &ctx.accounts.pending_root;
+
+    require!(!state.paused, SipError::SettlementPaused);
+    require!(
+        !pending.is_expired(Clock::get()?.unix_timestamp),
+        SipError::ProposalExpired
+    );
+    require!(
+        pending.target_batch_id == state.batch_id + 1,
+        SipError::StaleProposal
+    );
+
+    // Only approvals from keys that still have an executor record count
+    let approvals = count_live_approvals(pending, ctx.remaining_accounts)?;
+    require!(
+        approvals >= state.root_threshold as usize,
+        SipError::ThresholdNotMet
+    );
+
+    // The executor pays for the batch root, so close_batch_root refunds them
+    let (root, depth) = (pending.root, pending.depth);
+    activate_root(
+        &mut ctx.accounts.settlement_state,
+        &mut ctx.accounts.batch_root,
+        root,
+        depth,
+        ctx.accounts.executor.key(),
+        ctx.bumps.batch_root,
+    )
+}
+
+/// Close an expired, stale or withdrawn root proposal
+#[derive(Accounts)]
+pub struct CloseRootProposal<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_ROOT_SEED, &pending_root.root],
+        bump = pending_root.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub pending_root: Account<'info, PendingRoot>,
+
+    /// CHECK: Receives the proposal rent; checked by has_one
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Admin role of the caller (lets an admin withdraw active proposals)
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], caller.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Option<Account<'info, RoleAssignment>>,
+
+    pub caller: Signer<'info>,
+}
+
+pub fn close_root_proposal(ctx: Context<CloseRootProposal>) -> Result<()> {
+    let state = &ctx.accounts.settlement_state;
+    let pending = &ctx.accounts.pending_root;
+    let caller = ctx.accounts.caller.key();
+
+    // Proposer and admins can withdraw at any time; anyone can clean up
+    // proposals that can no longer be executed
+    let can_close = caller == pending.proposer
+        || ctx.accounts.admin_role.is_some()
+        || pending.is_expired(Clock::get()?.unix_timestamp)
+        || pending.target_batch_id != state.batch_id + 1;
+    require!(can_close, SipError::ProposalStillActive);
+
+    emit!(RootProposalClosed {
+        root: pending.root,
+        target_batch_id: pending.target_batch_id,
+        closed_by: caller,
+    });
+    msg!("Root proposal closed: target_batch_id={}", pending.target_batch_id);
+    Ok(())
+}
+
+/// Configure the root approval threshold
+#[derive(Accounts)]
+pub struct SetRootQuorum<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_root_quorum(ctx: Context<SetRootQuorum>, threshold: u8, proposal_ttl: i64) -> Result<()> {
+    let state = &mut ctx.accounts.settlement_state;
+
+    require!(
+        threshold >= 1 && threshold as usize <= MAX_ROOT_APPROVALS,
+        SipError::InvalidThreshold
+    );
+    // Quorum must stay reachable with the current executors (1 = direct updates)
+    require!(state.is_reachable_threshold(threshold), SipError::InvalidThreshold);
+    require!(proposal_ttl > 0, SipError::InvalidProposalTtl);
+
+    state.root_threshold = threshold;
+    state.proposal_ttl = proposal_ttl;
+
+    emit!(RootQuorumChanged {
+        threshold,
+        proposal_ttl,
+    });
+    msg!("Root quorum set: threshold={}, ttl={}", threshold, proposal_ttl);
+    Ok(())
+}
+
+/// Count approvers with a live ExecutorRecord among `records`
+///
+/// Records are program-owned and only ever created at their executor's PDA,
+/// so owner and discriminator checks are enough to trust `record.executor`.
+fn count_live_approvals(pending: &PendingRoot, records: &[AccountInfo]) -> Result<usize> {
+    let mut counted: Vec<Pubkey> = Vec::with_capacity(records.len());
+    for info in records {
+        require_keys_eq!(*info.owner, crate::ID, SipError::InvalidExecutorRecord);
+        let data = info.try_borrow_data()?;
+        let record = ExecutorRecord::try_deserialize(&mut &data[..])
+            .map_err(|_| SipError::InvalidExecutorRecord)?;
+        if pending.has_approved(&record.executor) && !counted.contains(&record.executor) {
+            counted.push(record.executor);
+        }
+    }
+    Ok(counted.len())
+}
+
+/// Make a root current and record its batch
+fn activate_root(
+    state: &mut SettlementState,
+    batch: &mut BatchRoot,
+    root: [u8; 32],
+    depth: u8,
+    executor: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    // Update state
+    state.batch_id += 1;
+    state.current_root = root;
+
+    // Store batch root record
+    let now = Clock::get()?.unix_timestamp;
+    batch.batch_id = state.batch_id;
+    batch.root = root;
+    batch.created_at = now;
+    batch.executor = executor;
+    batch.bump = bump;
+    batch.depth = depth;
+    batch.leaf_format = if state.has_domain() {
+        LEAF_FORMAT_DOMAIN
+    } else {
+        LEAF_FORMAT_PREFIXED
+    };
+    batch.expires_at = if state.batch_ttl > 0 {
+        now.checked_add(state.batch_ttl).ok_or(SipError::InvalidBatchTtl)?
+    } else {
+        0
+    };
+
+    emit!(RootUpdated {
+        batch_id: state.batch_id,
+        root,
+        depth,
+        executor,
+    });
+    msg!("Root updated: batch_id={}, depth={}", state.batch_id, depth);
+    Ok(())
+}
+
+/// Configure the guardians attesting EVM roots
+#[derive(Accounts)]
+pub struct InitializeGuardianSet<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = GuardianSet::LEN,
+        seeds = [GUARDIAN_SET_SEED],
+        bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_guardian_set(
+    ctx: Context<InitializeGuardianSet>,
+    source_chain_id: u64,
+    source_contract: [u8; 20],
+    guardians: Vec<[u8; 20]>,
+    threshold: u8,
+    last_source_batch_id: u64,
+) -> Result<()> {
+    // EVM trees are keccak trees
+    require!(
+        ctx.accounts.settlement_state.hash_function == HASH_FUNCTION_KECCAK,
+        SipError::InvalidHashFunction
+    );
+    require!(source_contract != [0u8; 20], SipError::InvalidGuardianSet);
+    require!(
+        GuardianSet::is_valid_config(&guardians, threshold),
+        SipError::InvalidGuardianSet
+    );
+
+    let set = &mut ctx.accounts.guardian_set;
+    set.source_chain_id = source_chain_id;
+    set.source_contract = source_contract;
+    set.set_guardians(&guardians, threshold);
+    set.last_source_batch_id = last_source_batch_id;
+    set.bump = ctx.bumps.guardian_set;
+
+    emit!(GuardianSetChanged {
+        source_chain_id,
+        guardian_count: set.guardian_count,
+        threshold,
+    });
+    msg!(
+        "Guardian set initialized: chain_id={}, guardians={}, threshold={}",
+        source_chain_id,
+        set.guardian_count,
+        threshold
+    );
+    Ok(())
+}
+
+/// Rotate the guardian keys and threshold
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    #[account(
+        mut,
+        seeds = [GUARDIAN_SET_SEED],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_guardians(ctx: Context<SetGuardians>, guardians: Vec<[u8; 20]>, threshold: u8) -> Result<()> {
+    require!(
+        GuardianSet::is_valid_config(&guardians, threshold),
+        SipError::InvalidGuardianSet
+    );
+
+    let set = &mut ctx.accounts.guardian_set;
+    set.set_guardians(&guardians, threshold);
+
+    emit!(GuardianSetChanged {
+        source_chain_id: set.source_chain_id,
+        guardian_count: set.guardian_count,
+        threshold,
+    });
+    msg!("Guardians set: guardians={}, threshold={}", set.guardian_count, threshold);
+    Ok(())
+}
+
+/// Mirror an EVM batch root attested by the guardians
+///
+/// Anyone may relay an attestation; the guardian signatures authorize it.
+/// Each signature costs a secp256k1 recovery (~25k CU), so relayers of large
+/// sets need to request a higher compute budget.
+#[derive(Accounts)]
+pub struct MirrorRoot<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        mut,
+        seeds = [GUARDIAN_SET_SEED],
+        bump = guardian_set.bump
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = BatchRoot::LEN,
+        seeds = [BATCH_SEED, &(settlement_state.batch_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub batch_root: Account<'info, BatchRoot>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn mirror_root(
+    ctx: Context<MirrorRoot>,
+    source_batch_id: u64,
+    root: [u8; 32],
+    depth: u8,
+    signatures: Vec<GuardianSignature>,
+) -> Result<()> {
+    let set = &ctx.accounts.guardian_set;
+
+    require!(!ctx.accounts.settlement_state.paused, SipError::SettlementPaused);
+    require!(root != [0u8; 32], SipError::InvalidRoot);
+    require!(BatchRoot::is_valid_depth(depth), SipError::InvalidTreeDepth);
+
+    // Attestations are signed for one deployment's domain
+    require!(ctx.accounts.settlement_state.has_domain(), SipError::DomainNotSet);
+
+    // Batches are mirrored in order, so an attestation can only be used once
+    require!(source_batch_id > set.last_source_batch_id, SipError::StaleAttestation);
+
+    let digest = attestation_digest(
+        &ctx.accounts.settlement_state.domain,
+        set.source_chain_id,
+        &set.source_contract,
+        source_batch_id,
+        &root,
+        depth,
+    );
+    let signed = count_guardian_signatures(set, &digest, &signatures)?;
+    require!(signed >= set.threshold as usize, SipError::InsufficientGuardianSignatures);
+
+    ctx.accounts.guardian_set.last_source_batch_id = source_batch_id;
+
+    activate_root(
+        &mut ctx.accounts.settlement_state,
+        &mut ctx.accounts.batch_root,
+        root,
+        depth,
+        ctx.accounts.payer.key(),
+        ctx.bumps.batch_root,
+    )?;
+
+    // The EVM contract verifies proofs over raw commitments
+    ctx.accounts.batch_root.leaf_format = LEAF_FORMAT_RAW;
+
+    emit!(RootMirrored {
+        batch_id: ctx.accounts.settlement_state.batch_id,
+        source_batch_id,
+        root,
+        signatures: signed as u8,
+    });
+    msg!("Root mirrored: source_batch_id={}, signatures={}", source_batch_id, signed);
+    Ok(())
+}
+
+/// Check every signature against its guardian and count them
+///
+/// Signatures are sorted by guardian index so each guardian counts once.
+fn count_guardian_signatures(
+    set: &GuardianSet,
+    digest: &[u8; 32],
+    signatures: &[GuardianSignature],
+) -> Result<usize> {
+    let mut previous: Option<u8> = None;
+    for signature in signatures {
+        let index = signature.guardian_index;
+        // None orders before any index
+        require!(previous < Some(index), SipError::DuplicateGuardianSignature);
+        require!(index < set.guardian_count, SipError::InvalidGuardianSignature);
+
+        let signer = recover_signer(digest, signature).ok_or(SipError::InvalidGuardianSignature)?;
+        require!(
+            signer == set.guardians[index as usize],
+            SipError::InvalidGuardianSignature
+        );
+        previous = Some(index);
+    }
+    Ok(signatures.len())
+}
+
+/// Settle a commitment
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32], proof: Vec<[u8; 32]>, leaf_index: u64)]
+pub struct Settle<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [BATCH_SEED, &settlement_state.batch_id.to_le_bytes()],
+        bump = batch_root.bump
+    )]
+    pub batch_root: Account<'info, BatchRoot>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = UsedCommitment::LEN,
+        seeds = [COMMITMENT_SEED, &commitment],
+        bump
+    )]
+    pub used_commitment: Account<'info, UsedCommitment>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn settle(
+    ctx: Context<Settle>,
+    commitment: [u8; 32],
+    proof: Vec<[u8; 32]>,
+    leaf_index: u64,
+) -> Result<()> {
+    let payer = ctx.accounts.payer.key();
+    settle_commitment(
+        &ctx.accounts.settlement_state,
+        &ctx.accounts.batch_root,
+        &mut ctx.accounts.used_commitment,
+        commitment,
+        &proof,
+        leaf_index,
+        LeafBinding::Plain,
+        payer,
+        ctx.bumps.used_commitment,
+    )
+}
+
+/// Start verifying a WOTS+ signature over a commitment
+///
+/// A signature (67 x 32 bytes) does not fit in one transaction and its chains
+/// do not fit in one instruction's compute budget, so the owner writes it
+/// with `write_wots_signature`, completes it with `verify_wots_chains` and
+/// finally settles with `settle_with_wots`.
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32])]
+pub struct BeginWotsVerification<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = WotsVerification::LEN,
+        seeds = [WOTS_SEED, owner.key().as_ref(), &commitment],
+        bump
+    )]
+    pub wots_verification: Box<Account<'info, WotsVerification>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn begin_wots_verification(
+    ctx: Context<BeginWotsVerification>,
+    commitment: [u8; 32],
+    public_key_hash: [u8; 32],
+) -> Result<()> {
+    let verification = &mut ctx.accounts.wots_verification;
+    verification.commitment = commitment;
+    verification.public_key_hash = public_key_hash;
+    verification.owner = ctx.accounts.owner.key();
+    verification.written = 0;
+    verification.completed = 0;
+    verification.verified = false;
+    verification.key_tree = Pubkey::default();
+    verification.key_index = 0;
+    verification.chains = [[0u8; 32]; WOTS_LEN];
+    verification.bump = ctx.bumps.wots_verification;
+
+    msg!("WOTS verification started");
+    Ok(())
+}
+
+/// Append WOTS+ signature elements
+#[derive(Accounts)]
+pub struct WriteWotsSignature<'info> {
+    #[account(
+        mut,
+        seeds = [WOTS_SEED, owner.key().as_ref(), &wots_verification.commitment],
+        bump = wots_verification.bump
+    )]
+    pub wots_verification: Box<Account<'info, WotsVerification>>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn write_wots_signature(ctx: Context<WriteWotsSignature>, elements: Vec<[u8; 32]>) -> Result<()> {
+    let verification = &mut ctx.accounts.wots_verification;
+
+    let start = verification.written as usize;
+    let end = start + elements.len();
+    require!(end <= WOTS_LEN, SipError::WotsSignatureTooLong);
+
+    verification.chains[start..end].copy_from_slice(&elements);
+    verification.written = end as u8;
+
+    msg!("WOTS signature written: {}/{}", end, WOTS_LEN);
+    Ok(())
+}
+
+/// Complete the next signature chains
+#[derive(Accounts)]
+pub struct VerifyWotsChains<'info> {
+    #[account(
+        mut,
+        seeds = [WOTS_SEED, owner.key().as_ref(), &wots_verification.commitment],
+        bump = wots_verification.bump
+    )]
+    pub wots_verification: Box<Account<'info, WotsVerification>>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn verify_wots_chains(ctx: Context<VerifyWotsChains>, max_chains: u8) -> Result<()> {
+    let verification = &mut ctx.accounts.wots_verification;
+    require!(
+        verification.written as usize == WOTS_LEN,
+        SipError::WotsSignatureIncomplete
+    );
+
+    // Each chain takes at most 15 hashes; the digits fix where each one starts
+    let digits = obscura_wots::message_digits(&verification.commitment);
+    let start = verification.completed as usize;
+    let end = (start + max_chains as usize).min(WOTS_LEN);
+    for (i, chain) in verification.chains.iter_mut().enumerate().take(end).skip(start) {
+        *chain = obscura_wots::complete_chain(chain, digits[i], i as u32);
+    }
+    verification.completed = end as u8;
+
+    if end == WOTS_LEN && !verification.verified {
+        require!(
+            obscura_wots::public_key_hash(&verification.chains) == verification.public_key_hash,
+            SipError::InvalidWotsSignature
+        );
+        verification.verified = true;
+
+        emit!(WotsVerified {
+            commitment: verification.commitment,
+            public_key_hash: verification.public_key_hash,
+            owner: verification.owner,
+        });
+    }
+
+    msg!("WOTS chains completed: {}/{}", end, WOTS_LEN);
+    Ok(())
+}
+
+/// Prove the key under verification belongs to the owner's key tree
+#[derive(Accounts)]
+pub struct BindWotsKey<'info> {
+    #[account(
+        mut,
+        seeds = [WOTS_SEED, owner.key().as_ref(), &wots_verification.commitment],
+        bump = wots_verification.bump
+    )]
+    pub wots_verification: Box<Account<'info, WotsVerification>>,
+
+    #[account(
+        seeds = [KEY_TREE_SEED, owner.key().as_ref(), &key_tree.root],
+        bump = key_tree.bump
+    )]
+    pub key_tree: Account<'info, KeyTree>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn bind_wots_key(ctx: Context<BindWotsKey>, key_index: u32, proof: Vec<[u8; 32]>) -> Result<()> {
+    let key_tree = &ctx.accounts.key_tree;
+    let verification = &mut ctx.accounts.wots_verification;
+
+    require!(
+        verify_key_proof(
+            &verification.public_key_hash,
+            &proof,
+            key_index as u64,
+            key_tree.depth,
+            &key_tree.root
+        ),
+        SipError::WotsKeyNotInTree
+    );
+
+    verification.key_tree = key_tree.key();
+    verification.key_index = key_index;
+
+    msg!("WOTS key bound: key_index={}", key_index);
+    Ok(())
+}
+
+/// Settle a commitment whose leaf binds a verified WOTS+ key
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32], proof: Vec<[u8; 32]>, leaf_index: u64)]
+pub struct SettleWithWots<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [BATCH_SEED, &settlement_state.batch_id.to_le_bytes()],
+        bump = batch_root.bump
+    )]
+    pub batch_root: Account<'info, BatchRoot>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = UsedCommitment::LEN,
+        seeds = [COMMITMENT_SEED, &commitment],
+        bump
+    )]
+    pub used_commitment: Account<'info, UsedCommitment>,
+
+    /// Closed on settlement (rent back to the owner)
+    #[account(
+        mut,
+        seeds = [WOTS_SEED, owner.key().as_ref(), &commitment],
+        bump = wots_verification.bump,
+        close = owner
+    )]
+    pub wots_verification: Box<Account<'info, WotsVerification>>,
+
+    /// Marks the one-time key consumed (already exists if it was used)
+    #[account(
+        init,
+        payer = owner,
+        space = ConsumedKey::LEN,
+        seeds = [CONSUMED_KEY_SEED, &wots_verification.public_key_hash],
+        bump
+    )]
+    pub consumed_key: Account<'info, ConsumedKey>,
+
+    /// Required when the key was bound to a key tree
+    #[account(
+        mut,
+        seeds = [KEY_TREE_SEED, owner.key().as_ref(), &key_tree.root],
+        bump = key_tree.bump
+    )]
+    pub key_tree: Option<Account<'info, KeyTree>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn settle_with_wots(
+    ctx: Context<SettleWithWots>,
+    commitment: [u8; 32],
+    proof: Vec<[u8; 32]>,
+    leaf_index: u64,
+) -> Result<()> {
+    let verification = &ctx.accounts.wots_verification;
+    require!(verification.verified, SipError::WotsNotVerified);
+
+    // WOTS leaves are keccak nodes and never raw commitments
+    require!(
+        ctx.accounts.settlement_state.hash_function == HASH_FUNCTION_KECCAK
+            && ctx.accounts.batch_root.leaf_format != LEAF_FORMAT_RAW,
+        SipError::WotsLeafUnsupported
+    );
+
+    let public_key_hash = verification.public_key_hash;
+    let bound_tree = verification.key_tree;
+    let key_index = verification.key_index;
+
+    // Bound keys count against their key tree
+    if bound_tree != Pubkey::default() {
+        let key_tree = ctx.accounts.key_tree.as_mut().ok_or(SipError::KeyTreeMismatch)?;
+        require_keys_eq!(key_tree.key(), bound_tree, SipError::KeyTreeMismatch);
+        key_tree.consumed += 1;
+    }
+    record_consumed_key(
+        &mut ctx.accounts.consumed_key,
+        public_key_hash,
+        bound_tree,
+        key_index,
+        commitment,
+        ctx.bumps.consumed_key,
+    )?;
+
+    let owner = ctx.accounts.owner.key();
+    settle_commitment(
+        &ctx.accounts.settlement_state,
+        &ctx.accounts.batch_root,
+        &mut ctx.accounts.used_commitment,
+        commitment,
+        &proof,
+        leaf_index,
+        LeafBinding::Wots(&public_key_hash),
+        owner,
+        ctx.bumps.used_commitment,
+    )
+}
+
+/// Close an abandoned WOTS+ verification
+#[derive(Accounts)]
+pub struct CloseWotsVerification<'info> {
+    #[account(
+        mut,
+        seeds = [WOTS_SEED, owner.key().as_ref(), &wots_verification.commitment],
+        bump = wots_verification.bump,
+        close = owner
+    )]
+    pub wots_verification: Box<Account<'info, WotsVerification>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_wots_verification(_ctx: Context<CloseWotsVerification>) -> Result<()> {
+    msg!("WOTS verification closed");
+    Ok(())
+}
+
+/// Register a tree of pre-generated WOTS+ keys
+///
+/// One account commits up to 2^20 keys; each key is proven against the root
+/// when it is consumed.
+#[derive(Accounts)]
+#[instruction(root: [u8; 32])]
+pub struct RegisterKeyTree<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = KeyTree::LEN,
+        seeds = [KEY_TREE_SEED, owner.key().as_ref(), &root],
+        bump
+    )]
+    pub key_tree: Account<'info, KeyTree>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn register_key_tree(ctx: Context<RegisterKeyTree>, root: [u8; 32], depth: u8) -> Result<()> {
+    require!(root != [0u8; 32], SipError::InvalidKeyTree);
+    require!(depth <= MAX_KEY_TREE_DEPTH, SipError::InvalidKeyTree);
+
+    let key_tree = &mut ctx.accounts.key_tree;
+    key_tree.owner = ctx.accounts.owner.key();
+    key_tree.root = root;
+    key_tree.depth = depth;
+    key_tree.registered_at = Clock::get()?.unix_timestamp;
+    key_tree.consumed = 0;
+    key_tree.bump = ctx.bumps.key_tree;
+
+    emit!(KeyTreeRegistered {
+        owner: key_tree.owner,
+        root,
+        depth,
+    });
+    msg!("Key tree registered: depth={}", depth);
+    Ok(())
+}
+
+/// Burn a registered key without settling
+///
+/// The key must have signed its burn message (`wots_burn_message`) through
+/// the usual verification flow, so only its holder can burn it.
+#[derive(Accounts)]
+pub struct ConsumeWotsKey<'info> {
+    #[account(
+        mut,
+        seeds = [KEY_TREE_SEED, owner.key().as_ref(), &key_tree.root],
+        bump = key_tree.bump
+    )]
+    pub key_tree: Account<'info, KeyTree>,
+
+    /// Closed on burn (rent back to the owner)
+    #[account(
+        mut,
+        seeds = [WOTS_SEED, owner.key().as_ref(), &wots_verification.commitment],
+        bump = wots_verification.bump,
+        close = owner
+    )]
+    pub wots_verification: Box<Account<'info, WotsVerification>>,
+
+    /// Already exists if the key was used
+    #[account(
+        init,
+        payer = owner,
+        space = ConsumedKey::LEN,
+        seeds = [CONSUMED_KEY_SEED, &wots_verification.public_key_hash],
+        bump
+    )]
+    pub consumed_key: Account<'info, ConsumedKey>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn consume_wots_key(ctx: Context<ConsumeWotsKey>, key_index: u32, proof: Vec<[u8; 32]>) -> Result<()> {
+    let verification = &ctx.accounts.wots_verification;
+    require!(verification.verified, SipError::WotsNotVerified);
+    let public_key_hash = verification.public_key_hash;
+    require!(
+        verification.commitment == wots_burn_message(&public_key_hash),
+        SipError::InvalidBurnMessage
+    );
+
+    let key_tree = &mut ctx.accounts.key_tree;
+
+    // Zero hashes pad the tree and are not keys
+    require!(public_key_hash != [0u8; 32], SipError::WotsKeyNotInTree);
+    require!(
+        verify_key_proof(&public_key_hash, &proof, key_index as u64, key_tree.depth, &key_tree.root),
+        SipError::WotsKeyNotInTree
+    );
+    key_tree.consumed += 1;
+
+    let key_tree = key_tree.key();
+    record_consumed_key(
+        &mut ctx.accounts.consumed_key,
+        public_key_hash,
+        key_tree,
+        key_index,
+        [0u8; 32],
+        ctx.bumps.consumed_key,
+    )
+}
+
+/// Close a key tree
+#[derive(Accounts)]
+pub struct CloseKeyTree<'info> {
+    #[account(
+        mut,
+        seeds = [KEY_TREE_SEED, owner.key().as_ref(), &key_tree.root],
+        bump = key_tree.bump,
+        close = owner
+    )]
+    pub key_tree: Account<'info, KeyTree>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_key_tree(ctx: Context<CloseKeyTree>) -> Result<()> {
+    msg!("Key tree closed: consumed={}", ctx.accounts.key_tree.consumed);
+    Ok(())
+}
+
+/// Record a consumed key (the account creation rejects reuse)
+fn record_consumed_key(
+    consumed: &mut ConsumedKey,
+    public_key_hash: [u8; 32],
+    key_tree: Pubkey,
+    key_index: u32,
+    commitment: [u8; 32],
+    bump: u8,
+) -> Result<()> {
+    consumed.public_key_hash = public_key_hash;
+    consumed.key_tree = key_tree;
+    consumed.key_index = key_index;
+    consumed.commitment = commitment;
+    consumed.consumed_at = Clock::get()?.unix_timestamp;
+    consumed.bump = bump;
+
+    emit!(WotsKeyConsumed {
+        public_key_hash,
+        key_tree,
+        commitment,
+    });
+    msg!("WOTS key consumed: key_index={}", key_index);
+    Ok(())
+}
+
+/// Settle a commitment and release its funds from sip_vault atomically
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32], proof: Vec<[u8; 32]>, leaf_index: u64)]
+pub struct SettleAndRelease<'info> {
+    /// Also signs the vault release (must be the vault's `settlement` key)
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [BATCH_SEED, &settlement_state.batch_id.to_le_bytes()],
+        bump = batch_root.bump
+    )]
+    pub batch_root: Account<'info, BatchRoot>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = UsedCommitment::LEN,
+        seeds = [COMMITMENT_SEED, &commitment],
+        bump
+    )]
+    pub used_commitment: Account<'info, UsedCommitment>,
+
+    /// Releases are submitted by executors
+    #[account(
+        seeds = [EXECUTOR_SEED, executor.key().as_ref()],
+        bump = executor_record.bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// CHECK: Validated by sip_vault
+    #[account(mut)]
+    pub vault_state: UncheckedAccount<'info>,
+
+    /// CHECK: sip_vault replay record, created by sip_vault
+    #[account(mut)]
+    pub vault_used_commitment: UncheckedAccount<'info>,
+
+    /// CHECK: Receives SOL for native releases; validated by sip_vault
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Token releases only; validated by sip_vault
+    #[account(mut)]
+    pub vault_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Token releases only; validated by sip_vault
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub vault_program: Program<'info, SipVault>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn settle_and_release(
+    ctx: Context<SettleAndRelease>,
+    commitment: [u8; 32],
+    proof: Vec<[u8; 32]>,
+    leaf_index: u64,
+    amount: u64,
+    recipient: Pubkey,
+    mint: Pubkey,
+) -> Result<()> {
+    // Release leaves are keccak nodes and never raw commitments
+    require!(
+        ctx.accounts.settlement_state.hash_function == HASH_FUNCTION_KECCAK
+            && ctx.accounts.batch_root.leaf_format != LEAF_FORMAT_RAW,
+        SipError::ReleaseLeafUnsupported
+    );
+
+    // The leaf fixes what is paid out; sip_vault checks the accounts match
+    let executor = ctx.accounts.executor.key();
+    settle_commitment(
+        &ctx.accounts.settlement_state,
+        &ctx.accounts.batch_root,
+        &mut ctx.accounts.used_commitment,
+        commitment,
+        &proof,
+        leaf_index,
+        LeafBinding::Release {
+            amount,
+            recipient: &recipient,
+            mint: &mint,
+        },
+        executor,
+        ctx.bumps.used_commitment,
+    )?;
+
+    // The settlement state PDA is the vault's settlement key
+    let seeds = &[SETTLEMENT_SEED, &[ctx.accounts.settlement_state.bump]];
+    let signer = &[&seeds[..]];
+    let vault_program = ctx.accounts.vault_program.to_account_info();
+    let accounts = &ctx.accounts;
+
+    match (
+        &accounts.vault_token_account,
+        &accounts.recipient_token_account,
+        &accounts.token_program,
+    ) {
+        (Some(vault_token_account), Some(recipient_token_account), Some(token_program)) => {
+            let cpi_accounts = sip_vault::cpi::accounts::ReleaseToken {
+                vault_state: accounts.vault_state.to_account_info(),
+                used_commitment: accounts.vault_used_commitment.to_account_info(),
+                vault_token_account: vault_token_account.to_account_info(),
+                recipient_token_account: recipient_token_account.to_account_info(),
+                settlement: accounts.settlement_state.to_account_info(),
+                payer: accounts.executor.to_account_info(),
+                token_program: token_program.to_account_info(),
+                system_program: accounts.system_program.to_account_info(),
+            };
+            sip_vault::cpi::release_token(
+                CpiContext::new_with_signer(vault_program, cpi_accounts, signer),
+                commitment,
+                amount,
+                recipient,
+                mint,
+            )?;
+        }
+        (None, None, None) => {
+            require_keys_eq!(mint, Pubkey::default(), SipError::MissingTokenAccounts);
+            let cpi_accounts = sip_vault::cpi::accounts::ReleaseNative {
+                vault_state: accounts.vault_state.to_account_info(),
+                used_commitment: accounts.vault_used_commitment.to_account_info(),
+                recipient: accounts.recipient.to_account_info(),
+                settlement: accounts.settlement_state.to_account_info(),
+                payer: accounts.executor.to_account_info(),
+                system_program: accounts.system_program.to_account_info(),
+            };
+            sip_vault::cpi::release_native(
+                CpiContext::new_with_signer(vault_program, cpi_accounts, signer),
+                commitment,
+                amount,
+                recipient,
+            )?;
+        }
+        _ => return err!(SipError::MissingTokenAccounts),
+    }
+
+    msg!("Commitment released");
+    Ok(())
+}
+
+/// Add an executor record; executors hold the root-submitter role
+#[derive(Accounts)]
+#[instruction(executor: Pubkey)]
+pub struct AddExecutor<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ExecutorRecord::LEN,
+        seeds = [EXECUTOR_SEED, executor.as_ref()],
+        bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_executor(
+    ctx: Context<AddExecutor>,
+    executor: Pubkey,
+    label: String,
+    rate_limit: u16,
+) -> Result<()> {
+    require!(executor != Pubkey::default(), SipError::InvalidRole);
+
+    init_executor_record(
+        &mut ctx.accounts.executor_record,
+        executor,
+        &label,
+        rate_limit,
+        ctx.accounts.admin.key(),
+        ctx.bumps.executor_record,
+    )?;
+
+    let state = &mut ctx.accounts.settlement_state;
+    state.registered_executors = state
+        .registered_executors
+        .checked_add(1)
+        .ok_or(SipError::MaxExecutorsReached)?;
+
+    emit!(ExecutorAdded {
+        executor,
+        added_by: ctx.accounts.admin.key(),
+        rate_limit,
+    });
+    msg!("Executor added: {}", executor);
+    Ok(())
+}
+
+/// Update an executor's label and rate limit
+#[derive(Accounts)]
+pub struct UpdateExecutor<'info> {
+    #[account(
+        mut,
+        seeds = [EXECUTOR_SEED, executor_record.executor.as_ref()],
+        bump = executor_record.bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn update_executor(ctx: Context<UpdateExecutor>, label: String, rate_limit: u16) -> Result<()> {
+    let record = &mut ctx.accounts.executor_record;
+    record.label = encode_label(&label)?;
+    record.rate_limit = rate_limit;
+
+    emit!(ExecutorUpdated {
+        executor: record.executor,
+        rate_limit,
+    });
+    msg!("Executor updated: {}, rate_limit={}", record.executor, rate_limit);
+    Ok(())
+}
+
+/// Remove an executor by closing its record
+#[derive(Accounts)]
+pub struct RemoveExecutor<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        mut,
+        seeds = [EXECUTOR_SEED, executor_record.executor.as_ref()],
+        bump = executor_record.bump,
+        close = admin
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn remove_executor(ctx: Context<RemoveExecutor>) -> Result<()> {
+    let executor = ctx.accounts.executor_record.executor;
+    let state = &mut ctx.accounts.settlement_state;
+    state.registered_executors = state.registered_executors.saturating_sub(1);
+
+    // Keep the root quorum reachable
+    require!(
+        state.is_reachable_threshold(state.root_threshold),
+        SipError::InvalidThreshold
+    );
+
+    emit!(ExecutorRemoved {
+        executor,
+        removed_by: ctx.accounts.admin.key(),
+    });
+    msg!("Executor removed: {}", executor);
+    Ok(())
+}
+
+/// Move an executor from the legacy inline array to its own record
+#[derive(Accounts)]
+#[instruction(executor: Pubkey)]
+pub struct MigrateExecutor<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ExecutorRecord::LEN,
+        seeds = [EXECUTOR_SEED, executor.as_ref()],
+        bump
+    )]
+    pub executor_record: Account<'info, ExecutorRecord>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_executor(ctx: Context<MigrateExecutor>, executor: Pubkey) -> Result<()> {
+    let state = &mut ctx.accounts.settlement_state;
+    let idx = state
+        .legacy_executor_index(&executor)
+        .ok_or(SipError::ExecutorNotFound)?;
+
+    // Swap with last and decrement count (more efficient than shifting)
+    let last_idx = state.executor_count as usize - 1;
+    if idx != last_idx {
+        state.executors[idx] = state.executors[last_idx];
+    }
+    state.executors[last_idx] = Pubkey::default();
+    state.executor_count -= 1;
+    state.registered_executors = state
+        .registered_executors
+        .checked_add(1)
+        .ok_or(SipError::MaxExecutorsReached)?;
+
+    init_executor_record(
+        &mut ctx.accounts.executor_record,
+        executor,
+        "",
+        0,
+        ctx.accounts.admin.key(),
+        ctx.bumps.executor_record,
+    )?;
+
+    emit!(ExecutorAdded {
+        executor,
+        added_by: ctx.accounts.admin.key(),
+        rate_limit: 0,
+    });
+    msg!("Executor migrated: {}", executor);
+    Ok(())
+}
+
+fn init_executor_record(
+    record: &mut ExecutorRecord,
+    executor: Pubkey,
+    label: &str,
+    rate_limit: u16,
+    added_by: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    record.executor = executor;
+    record.label = encode_label(label)?;
+    record.added_at = Clock::get()?.unix_timestamp;
+    record.added_by = added_by;
+    record.last_active_batch = 0;
+    record.rate_limit = rate_limit;
+    record.window_start = 0;
+    record.window_count = 0;
+    record.bump = bump;
+    Ok(())
+}
+
+/// Zero-pad a label into its fixed-size field
+fn encode_label(label: &str) -> Result<[u8; MAX_LABEL_LEN]> {
+    require!(label.len() <= MAX_LABEL_LEN, SipError::InvalidLabel);
+    let mut encoded = [0u8; MAX_LABEL_LEN];
+    encoded[..label.len()].copy_from_slice(label.as_bytes());
+    Ok(encoded)
+}
+
+/// Grant a role through a role PDA
+#[derive(Accounts)]
+#[instruction(role: u8, holder: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = RoleAssignment::LEN,
+        seeds = [ROLE_SEED, &[role], holder.as_ref()],
+        bump
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    /// Admin role of the signer (not needed for the authority)
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Option<Account<'info, RoleAssignment>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn grant_role(ctx: Context<GrantRole>, role: u8, holder: Pubkey) -> Result<()> {
+    let state = &ctx.accounts.settlement_state;
+    let admin = ctx.accounts.admin.key();
+
+    // The authority can always manage roles so admins can be recovered
+    require!(
+        admin == state.authority || ctx.accounts.admin_role.is_some(),
+        SipError::Unauthorized
+    );
+    // Root submitters are managed with add_executor/remove_executor
+    require!(is_valid_role(role), SipError::InvalidRole);
+    require!(holder != Pubkey::default(), SipError::InvalidRole);
+
+    let assignment = &mut ctx.accounts.role_assignment;
+    assignment.role = role;
+    assignment.holder = holder;
+    assignment.granted_by = admin;
+    assignment.granted_at = Clock::get()?.unix_timestamp;
+    assignment.bump = ctx.bumps.role_assignment;
+
+    emit!(RoleGranted {
+        role,
+        holder,
+        granted_by: admin,
+    });
+    msg!("Role {} granted to {}", role, holder);
+    Ok(())
+}
+
+/// Revoke a role by closing its PDA
+#[derive(Accounts)]
+#[instruction(role: u8, holder: Pubkey)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        mut,
+        seeds = [ROLE_SEED, &[role], holder.as_ref()],
+        bump = role_assignment.bump,
+        close = admin
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    /// Admin role of the signer (not needed for the authority)
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Option<Account<'info, RoleAssignment>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn revoke_role(ctx: Context<RevokeRole>, role: u8, holder: Pubkey) -> Result<()> {
+    let state = &ctx.accounts.settlement_state;
+    let admin = ctx.accounts.admin.key();
+
+    require!(
+        admin == state.authority || ctx.accounts.admin_role.is_some(),
+        SipError::Unauthorized
+    );
+
+    emit!(RoleRevoked {
+        role,
+        holder,
+        revoked_by: admin,
+    });
+    msg!("Role {} revoked from {}", role, holder);
+    Ok(())
+}
+
+/// Pause root updates and settlement (emergency)
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_PAUSER], pauser.key().as_ref()],
+        bump = pauser_role.bump
+    )]
+    pub pauser_role: Account<'info, RoleAssignment>,
+
+    pub pauser: Signer<'info>,
+}
+
+pub fn pause(ctx: Context<Pause>) -> Result<()> {
+    ctx.accounts.settlement_state.paused = true;
+    emit!(Paused {
+        pauser: ctx.accounts.pauser.key(),
+    });
+    msg!("Settlement paused");
+    Ok(())
+}
+
+/// Unpause (admin only, so a compromised pauser key cannot undo a pause)
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
+    ctx.accounts.settlement_state.paused = false;
+    emit!(Unpaused {
+        admin: ctx.accounts.admin.key(),
+    });
+    msg!("Settlement unpaused");
+    Ok(())
+}
+
+/// Transfer authority (two-step pattern)
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn transfer_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
+    require!(new_authority != Pubkey::default(), SipError::InvalidPendingAuthority);
+    
+    let state = &mut ctx.accounts.settlement_state;
+    state.pending_authority = new_authority;
+
+    emit!(AuthorityTransferStarted {
+        authority: state.authority,
+        pending_authority: new_authority,
+    });
+    msg!("Authority transfer initiated to: {}", new_authority);
+    Ok(())
+}
+
+/// Accept authority transfer
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    pub new_authority: Signer<'info>,
+}
+
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let state = &mut ctx.accounts.settlement_state;
+    
+    require!(
+        state.pending_authority != Pubkey::default(),
+        SipError::NoPendingTransfer
+    );
+    require!(
+        ctx.accounts.new_authority.key() == state.pending_authority,
+        SipError::Unauthorized
+    );
+
+    let old_authority = state.authority;
+    state.authority = state.pending_authority;
+    state.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferAccepted {
+        old_authority,
+        new_authority: state.authority,
+    });
+    msg!("Authority transferred from {} to {}", old_authority, state.authority);
+    Ok(())
+}
+
+/// Cancel authority transfer
+#[derive(Accounts)]
+pub struct CancelAuthorityTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+    let state = &mut ctx.accounts.settlement_state;
+    let pending_authority = state.pending_authority;
+    state.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferCancelled {
+        authority: state.authority,
+        pending_authority,
+    });
+    msg!("Authority transfer cancelled");
+    Ok(())
+}
+
+/// Configure the lifetime of new batches
+#[derive(Accounts)]
+pub struct SetBatchTtl<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn set_batch_ttl(ctx: Context<SetBatchTtl>, batch_ttl: i64) -> Result<()> {
+    require!(batch_ttl >= 0, SipError::InvalidBatchTtl);
+
+    // Existing batches keep their expiry
+    ctx.accounts.settlement_state.batch_ttl = batch_ttl;
+
+    emit!(BatchTtlChanged { batch_ttl });
+    msg!("Batch ttl set: {}", batch_ttl);
+    Ok(())
+}
+
+/// Close an expired batch root
+///
+/// Permissionless; the rent goes back to whoever paid for the batch.
+#[derive(Accounts)]
+#[instruction(batch_id: u64)]
+pub struct CloseBatchRoot<'info> {
+    #[account(
+        mut,
+        seeds = [BATCH_SEED, &batch_id.to_le_bytes()],
+        bump = batch_root.bump,
+        close = executor
+    )]
+    pub batch_root: Account<'info, BatchRoot>,
+
+    /// CHECK: Receives the rent; must be the batch submitter
+    #[account(mut, address = batch_root.executor)]
+    pub executor: UncheckedAccount<'info>,
+}
+
+pub fn close_batch_root(ctx: Context<CloseBatchRoot>, batch_id: u64) -> Result<()> {
+    let batch = &ctx.accounts.batch_root;
+    require!(
+        batch.is_expired(Clock::get()?.unix_timestamp),
+        SipError::BatchNotExpired
+    );
+
+    emit!(BatchRootClosed {
+        batch_id,
+        expires_at: batch.expires_at,
+    });
+    msg!("Batch root closed: batch_id={}", batch_id);
+    Ok(())
+}
+
+/// Set the deployment domain
+#[derive(Accounts)]
+pub struct SetDomain<'info> {
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+/// Programs cannot read the genesis hash, so the admin supplies it
+/// (`solana genesis-hash`); the program id is always this program's.
+/// Batches activated afterwards use domain leaves; older ones keep theirs.
+pub fn set_domain(ctx: Context<SetDomain>, genesis_hash: [u8; 32]) -> Result<()> {
+    require!(genesis_hash != [0u8; 32], SipError::InvalidGenesisHash);
+
+    let state = &mut ctx.accounts.settlement_state;
+    require!(!state.has_domain(), SipError::DomainAlreadySet);
+    state.domain = domain_separator(&genesis_hash, &crate::ID.to_bytes());
+
+    emit!(DomainSet {
+        genesis_hash,
+        domain: state.domain,
+    });
+    msg!("Domain set");
+    Ok(())
+}
+
+/// Migrate a batch root created with an older layout
+#[derive(Accounts)]
+#[instruction(batch_id: u64)]
+pub struct MigrateBatchRoot<'info> {
+    #[account(
+        seeds = [SETTLEMENT_SEED],
+        bump = settlement_state.bump
+    )]
+    pub settlement_state: Account<'info, SettlementState>,
+
+    /// CHECK: Legacy layout cannot be deserialized as BatchRoot; owner,
+    /// discriminator and size are checked in the handler
+    #[account(
+        mut,
+        seeds = [BATCH_SEED, &batch_id.to_le_bytes()],
+        bump
+    )]
+    pub batch_root: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_batch_root(ctx: Context<MigrateBatchRoot>, batch_id: u64, depth: u8) -> Result<()> {
+    let batch_info = ctx.accounts.batch_root.to_account_info();
+    let old_len = batch_info.data_len();
+    require_keys_eq!(*batch_info.owner, crate::ID, SipError::NotLegacyBatchRoot);
+    require!(
+        old_len == BatchRoot::LEGACY_LEN || old_len == BatchRoot::V1_LEN,
+        SipError::NotLegacyBatchRoot
+    );
+    if old_len == BatchRoot::LEGACY_LEN {
+        require!(BatchRoot::is_valid_depth(depth), SipError::InvalidTreeDepth);
+    }
+    require!(
+        batch_info.try_borrow_data()?[..8] == BatchRoot::DISCRIMINATOR,
+        SipError::NotLegacyBatchRoot
+    );
+
+    // Top up rent for the larger layout
+    let required = Rent::get()?.minimum_balance(BatchRoot::LEN);
+    let shortfall = required.saturating_sub(batch_info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: batch_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    batch_info.realloc(BatchRoot::LEN, false)?;
+    let mut data = batch_info.try_borrow_mut_data()?;
+    if old_len == BatchRoot::LEGACY_LEN {
+        data[BatchRoot::LEGACY_LEN] = depth;
+        data[BatchRoot::LEGACY_LEN + 1] = LEAF_FORMAT_RAW;
+    }
+
+    // Batches created before expiry existed never expire
+    data[BatchRoot::V1_LEN..BatchRoot::LEN].copy_from_slice(&0i64.to_le_bytes());
+
+    msg!("Batch root migrated: batch_id={}, from {} bytes", batch_id, old_len);
+    Ok(())
+}
+
+/// Migrate settlement state to the current layout
+///
+/// Signed by the authority: role PDAs can only be granted once the state
+/// has the current layout.
+#[derive(Accounts)]
+pub struct MigrateSettlementState<'info> {
+    /// CHECK: Older layouts cannot be deserialized as SettlementState; owner,
+    /// discriminator, size and authority are checked in the handler
+    #[account(
+        mut,
+        seeds = [SETTLEMENT_SEED],
+        bump
+    )]
+    pub settlement_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_settlement_state(ctx: Context<MigrateSettlementState>) -> Result<()> {
+    let state_info = ctx.accounts.settlement_state.to_account_info();
+    let old_len = state_info.data_len();
+    require_keys_eq!(*state_info.owner, crate::ID, SipError::NotLegacySettlementState);
+    require!(
+        old_len == SettlementState::LEGACY_LEN
+            || old_len == SettlementState::V1_LEN
+            || old_len == SettlementState::V2_LEN
+            || old_len == SettlementState::V3_LEN
+            || old_len == SettlementState::V4_LEN
+            || old_len == SettlementState::V5_LEN,
+        SipError::NotLegacySettlementState
+    );
+    {
+        let data = state_info.try_borrow_data()?;
+        require!(
+            data[..8] == SettlementState::DISCRIMINATOR,
+            SipError::NotLegacySettlementState
+        );
+        // authority is the first field
+        require!(
+            data[8..40] == ctx.accounts.authority.key().to_bytes(),
+            SipError::Unauthorized
+        );
+    }
+
+    // Top up rent for the larger layout
+    let required = Rent::get()?.minimum_balance(SettlementState::LEN);
+    let shortfall = required.saturating_sub(state_info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: state_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    state_info.realloc(SettlementState::LEN, false)?;
+    let mut data = state_info.try_borrow_mut_data()?;
+
+    // Existing deployments keep keccak trees
+    if old_len == SettlementState::LEGACY_LEN {
+        data[SettlementState::LEGACY_LEN] = HASH_FUNCTION_KECCAK;
+    }
+
+    // No quorum until an admin configures one
+    if old_len < SettlementState::V2_LEN {
+        let quorum = SettlementState::V1_LEN;
+        data[quorum] = 1;
+        data[quorum + 1..quorum + 9].copy_from_slice(&DEFAULT_PROPOSAL_TTL.to_le_bytes());
+    }
+
+    // Not paused
+    if old_len < SettlementState::V3_LEN {
+        data[SettlementState::V2_LEN] = 0;
+    }
+
+    // Legacy executors are moved into records with migrate_executor
+    if old_len < SettlementState::V4_LEN {
+        data[SettlementState::V3_LEN..SettlementState::V4_LEN].copy_from_slice(&0u32.to_le_bytes());
+    }
+
+    // Batches never expire until an admin sets a lifetime
+    if old_len < SettlementState::V5_LEN {
+        data[SettlementState::V4_LEN..SettlementState::V5_LEN].copy_from_slice(&0i64.to_le_bytes());
+    }
+
+    // No domain until the authority sets one
+    data[SettlementState::V5_LEN..SettlementState::LEN].fill(0);
+
+    msg!("Settlement state migrated from {} bytes", old_len);
+    Ok(())
+}
+
+/// What a leaf commits to besides the commitment
+enum LeafBinding<'a> {
+    /// Plain leaf (`settle`)
+    Plain,
+    /// WOTS+ key that signed the commitment (`settle_with_wots`)
+    Wots(&'a [u8; 32]),
+    /// Vault release (`settle_and_release`)
+    Release {
+        amount: u64,
+        recipient: &'a Pubkey,
+        mint: &'a Pubkey,
+    },
+}
+
+/// Verify a commitment against the current batch and mark it used
+#[allow(clippy::too_many_arguments)]
+fn settle_commitment(
+    state: &SettlementState,
+    batch: &BatchRoot,
+    used: &mut UsedCommitment,
+    commitment: [u8; 32],
+    proof: &[[u8; 32]],
+    leaf_index: u64,
+    binding: LeafBinding,
+    executor: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    require!(!state.paused, SipError::SettlementPaused);
+    require!(
+        !batch.is_expired(Clock::get()?.unix_timestamp),
+        SipError::BatchExpired
+    );
+
+    // Validate proof length
+    require!(!proof.is_empty(), SipError::EmptyProof);
+    require!(proof.len() <= MAX_PROOF_LENGTH, SipError::ProofTooLong);
+    require!(proof.len() == batch.depth as usize, SipError::ProofLengthMismatch);
+
+    // Each leaf has exactly one valid index
+    require!(leaf_index < (1u64 << batch.depth), SipError::LeafIndexOutOfRange);
+
+    // Verify Merkle proof with the deployment's hash function
+    let valid = if state.hash_function == HASH_FUNCTION_POSEIDON {
+        verify_commitment::<Poseidon>(batch, &state.domain, &commitment, proof, leaf_index, binding)?
+    } else {
+        verify_commitment::<Keccak>(batch, &state.domain, &commitment, proof, leaf_index, binding)?
+    };
+    require!(valid, SipError::InvalidProof);
+
+    // Mark as used (the account creation acts as replay protection)
+    used.commitment = commitment;
+    used.batch_id = state.batch_id;
+    used.settled_at = Clock::get()?.unix_timestamp;
+    used.executor = executor;
+    used.bump = bump;
+
+    emit!(CommitmentSettled {
+        commitment,
+        batch_id: state.batch_id,
+    });
+    msg!("Commitment settled: batch_id={}", state.batch_id);
+    Ok(())
+}
+
+/// Verify a commitment against a batch root
+fn verify_commitment<H: Hasher>(
+    batch: &BatchRoot,
+    domain: &[u8; 32],
+    commitment: &[u8; 32],
+    proof: &[[u8; 32]],
+    leaf_index: u64,
+    binding: LeafBinding,
+) -> Result<bool> {
+    let leaf = match (binding, batch.leaf_format) {
+        (LeafBinding::Wots(public_key_hash), LEAF_FORMAT_DOMAIN) => {
+            wots_domain_leaf_node(domain, commitment, public_key_hash)
+        }
+        (LeafBinding::Wots(public_key_hash), _) => wots_leaf_node(commitment, public_key_hash),
+        (LeafBinding::Release { amount, recipient, mint }, LEAF_FORMAT_DOMAIN) => {
+            release_domain_leaf_node(domain, commitment, amount, recipient, mint)
+        }
+        (LeafBinding::Release { amount, recipient, mint }, _) => {
+            release_leaf_node(commitment, amount, recipient, mint)
+        }
+        // Legacy batches were built over raw commitments
+        (LeafBinding::Plain, LEAF_FORMAT_RAW) => *commitment,
+        (LeafBinding::Plain, LEAF_FORMAT_DOMAIN) => {
+            H::hash_domain_leaf(domain, commitment).map_err(|_| SipError::InvalidProof)?
+        }
+        (LeafBinding::Plain, _) => H::hash_leaf(commitment).map_err(|_| SipError::InvalidProof)?,
+    };
+
+    let valid = obscura_merkle::verify_proof::<H>(&leaf, proof, leaf_index, batch.depth, &batch.root)
+        .map_err(|_| SipError::InvalidProof)?;
+    Ok(valid)
+}
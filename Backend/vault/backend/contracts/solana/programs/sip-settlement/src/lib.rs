@@ -2,11 +2,14 @@
 //!
 //! Minimal settlement program that handles:
 //! - Merkle root storage (batch commitments)
+//! - Root mirroring from the EVM settlement (guardian attestations)
 //! - Replay protection (used commitments)
 //! - Settlement execution
+//! - Optional WOTS+ verification for leaves that commit the signer's key
+//! - WOTS+ key registry (key pool trees and consumed keys)
 //!
 //! Does NOT handle:
-//! - WOTS signature verification (done off-chain)
+//! - WOTS signature verification for plain leaves (done off-chain)
 //! - Privacy logic (handled by SIP layer)
 //! - Intent details (never stored on-chain)
 
@@ -27,24 +30,87 @@ security_txt! {
 
 declare_id!("BkR8HGcC5T5UhFbCadiUQGqEF2eHCv5Kmx4hz7Anuctq");
 
+pub mod attestation;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
+use attestation::GuardianSignature;
 use instructions::*;
 
 #[program]
 pub mod sip_settlement {
     use super::*;
 
-    /// Initialize the settlement state
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        instructions::initialize(ctx)
+    /// Initialize the settlement state with the deployment's tree hash function
+    pub fn initialize(ctx: Context<Initialize>, hash_function: u8) -> Result<()> {
+        instructions::initialize(ctx, hash_function)
     }
 
-    /// Update the current Merkle root (new batch)
-    pub fn update_root(ctx: Context<UpdateRoot>, new_root: [u8; 32]) -> Result<()> {
-        instructions::update_root(ctx, new_root)
+    /// Update the current Merkle root (new batch) with its fixed tree depth
+    pub fn update_root(ctx: Context<UpdateRoot>, new_root: [u8; 32], depth: u8) -> Result<()> {
+        instructions::update_root(ctx, new_root, depth)
+    }
+
+    /// Propose a new Merkle root for executor approval
+    pub fn propose_root(ctx: Context<ProposeRoot>, new_root: [u8; 32], depth: u8) -> Result<()> {
+        instructions::propose_root(ctx, new_root, depth)
+    }
+
+    /// Approve a proposed root
+    pub fn approve_root(ctx: Context<ApproveRoot>) -> Result<()> {
+        instructions::approve_root(ctx)
+    }
+
+    /// Activate a proposed root once enough executors approved it
+    pub fn execute_root(ctx: Context<ExecuteRoot>) -> Result<()> {
+        instructions::execute_root(ctx)
+    }
+
+    /// Close an expired, stale or withdrawn root proposal
+    pub fn close_root_proposal(ctx: Context<CloseRootProposal>) -> Result<()> {
+        instructions::close_root_proposal(ctx)
+    }
+
+    /// Set the number of executor approvals required for a new root
+    pub fn set_root_quorum(ctx: Context<SetRootQuorum>, threshold: u8, proposal_ttl: i64) -> Result<()> {
+        instructions::set_root_quorum(ctx, threshold, proposal_ttl)
+    }
+
+    /// Configure the guardians attesting EVM SIPSettlement roots
+    pub fn initialize_guardian_set(
+        ctx: Context<InitializeGuardianSet>,
+        source_chain_id: u64,
+        source_contract: [u8; 20],
+        guardians: Vec<[u8; 20]>,
+        threshold: u8,
+        last_source_batch_id: u64,
+    ) -> Result<()> {
+        instructions::initialize_guardian_set(
+            ctx,
+            source_chain_id,
+            source_contract,
+            guardians,
+            threshold,
+            last_source_batch_id,
+        )
+    }
+
+    /// Rotate the guardian keys and threshold
+    pub fn set_guardians(ctx: Context<SetGuardians>, guardians: Vec<[u8; 20]>, threshold: u8) -> Result<()> {
+        instructions::set_guardians(ctx, guardians, threshold)
+    }
+
+    /// Mirror an EVM batch root attested by a threshold of guardians
+    pub fn mirror_root(
+        ctx: Context<MirrorRoot>,
+        source_batch_id: u64,
+        root: [u8; 32],
+        depth: u8,
+        signatures: Vec<GuardianSignature>,
+    ) -> Result<()> {
+        instructions::mirror_root(ctx, source_batch_id, root, depth, signatures)
     }
 
     /// Settle a single commitment
@@ -57,14 +123,150 @@ pub mod sip_settlement {
         instructions::settle(ctx, commitment, proof, leaf_index)
     }
 
-    /// Add an authorized executor
-    pub fn add_executor(ctx: Context<ManageExecutor>, executor: Pubkey) -> Result<()> {
-        instructions::add_executor(ctx, executor)
+    /// Settle a commitment and release its funds from sip_vault in one transaction
+    pub fn settle_and_release(
+        ctx: Context<SettleAndRelease>,
+        commitment: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        leaf_index: u64,
+        amount: u64,
+        recipient: Pubkey,
+        mint: Pubkey,
+    ) -> Result<()> {
+        instructions::settle_and_release(ctx, commitment, proof, leaf_index, amount, recipient, mint)
+    }
+
+    /// Start verifying a WOTS+ signature over a commitment
+    pub fn begin_wots_verification(
+        ctx: Context<BeginWotsVerification>,
+        commitment: [u8; 32],
+        public_key_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::begin_wots_verification(ctx, commitment, public_key_hash)
+    }
+
+    /// Append WOTS+ signature elements
+    pub fn write_wots_signature(ctx: Context<WriteWotsSignature>, elements: Vec<[u8; 32]>) -> Result<()> {
+        instructions::write_wots_signature(ctx, elements)
+    }
+
+    /// Complete up to `max_chains` signature chains
+    pub fn verify_wots_chains(ctx: Context<VerifyWotsChains>, max_chains: u8) -> Result<()> {
+        instructions::verify_wots_chains(ctx, max_chains)
+    }
+
+    /// Prove the key under verification belongs to the owner's key tree
+    pub fn bind_wots_key(ctx: Context<BindWotsKey>, key_index: u32, proof: Vec<[u8; 32]>) -> Result<()> {
+        instructions::bind_wots_key(ctx, key_index, proof)
+    }
+
+    /// Settle a commitment whose leaf binds a verified WOTS+ key
+    pub fn settle_with_wots(
+        ctx: Context<SettleWithWots>,
+        commitment: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        leaf_index: u64,
+    ) -> Result<()> {
+        instructions::settle_with_wots(ctx, commitment, proof, leaf_index)
+    }
+
+    /// Close an abandoned WOTS+ verification
+    pub fn close_wots_verification(ctx: Context<CloseWotsVerification>) -> Result<()> {
+        instructions::close_wots_verification(ctx)
+    }
+
+    /// Register a tree of pre-generated WOTS+ keys
+    pub fn register_key_tree(ctx: Context<RegisterKeyTree>, root: [u8; 32], depth: u8) -> Result<()> {
+        instructions::register_key_tree(ctx, root, depth)
+    }
+
+    /// Burn a registered key without settling (e.g. signed off-chain)
+    pub fn consume_wots_key(
+        ctx: Context<ConsumeWotsKey>,
+        key_index: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::consume_wots_key(ctx, key_index, proof)
+    }
+
+    /// Close a key tree (consumed keys stay recorded)
+    pub fn close_key_tree(ctx: Context<CloseKeyTree>) -> Result<()> {
+        instructions::close_key_tree(ctx)
+    }
+
+    /// Add an authorized executor with its label and rate limit
+    pub fn add_executor(
+        ctx: Context<AddExecutor>,
+        executor: Pubkey,
+        label: String,
+        rate_limit: u16,
+    ) -> Result<()> {
+        instructions::add_executor(ctx, executor, label, rate_limit)
+    }
+
+    /// Update an executor's label and rate limit
+    pub fn update_executor(ctx: Context<UpdateExecutor>, label: String, rate_limit: u16) -> Result<()> {
+        instructions::update_executor(ctx, label, rate_limit)
     }
 
     /// Remove an authorized executor
-    pub fn remove_executor(ctx: Context<ManageExecutor>, executor: Pubkey) -> Result<()> {
-        instructions::remove_executor(ctx, executor)
+    pub fn remove_executor(ctx: Context<RemoveExecutor>) -> Result<()> {
+        instructions::remove_executor(ctx)
+    }
+
+    /// Move an executor from the legacy inline array to its own record
+    pub fn migrate_executor(ctx: Context<MigrateExecutor>, executor: Pubkey) -> Result<()> {
+        instructions::migrate_executor(ctx, executor)
+    }
+
+    /// Set the lifetime of new batches (0 = no expiry)
+    pub fn set_batch_ttl(ctx: Context<SetBatchTtl>, batch_ttl: i64) -> Result<()> {
+        instructions::set_batch_ttl(ctx, batch_ttl)
+    }
+
+    /// Close an expired batch root (rent back to its submitter)
+    pub fn close_batch_root(ctx: Context<CloseBatchRoot>, batch_id: u64) -> Result<()> {
+        instructions::close_batch_root(ctx, batch_id)
+    }
+
+    /// Bind new batches to this cluster and program (once)
+    pub fn set_domain(ctx: Context<SetDomain>, genesis_hash: [u8; 32]) -> Result<()> {
+        instructions::set_domain(ctx, genesis_hash)
+    }
+
+    /// Resize a batch root created with an older layout (`depth` is recorded
+    /// for batches created before depths were stored)
+    pub fn migrate_batch_root(
+        ctx: Context<MigrateBatchRoot>,
+        batch_id: u64,
+        depth: u8,
+    ) -> Result<()> {
+        instructions::migrate_batch_root(ctx, batch_id, depth)
+    }
+
+    /// Resize settlement state created with an older layout
+    pub fn migrate_settlement_state(ctx: Context<MigrateSettlementState>) -> Result<()> {
+        instructions::migrate_settlement_state(ctx)
+    }
+
+    /// Grant a role to a key (admin or authority)
+    pub fn grant_role(ctx: Context<GrantRole>, role: u8, holder: Pubkey) -> Result<()> {
+        instructions::grant_role(ctx, role, holder)
+    }
+
+    /// Revoke a role from a key (admin or authority)
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: u8, holder: Pubkey) -> Result<()> {
+        instructions::revoke_role(ctx, role, holder)
+    }
+
+    /// Pause root updates and settlement (emergency)
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        instructions::pause(ctx)
+    }
+
+    /// Unpause root updates and settlement
+    pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
+        instructions::unpause(ctx)
     }
 
     /// Start authority transfer (two-step pattern)
@@ -1,113 +1,625 @@
-//! Program state definitions
-
-use anchor_lang::prelude::*;
-
-/// Maximum number of executors
-pub const MAX_EXECUTORS: usize = 10;
-
-/// Maximum proof length (tree depth)
-pub const MAX_PROOF_LENGTH: usize = 32;
-
-/// Settlement state account
-#[account]
-pub struct SettlementState {
-    /// Program authority (owner)
-    pub authority: Pubkey,
-    
-    /// Pending authority for two-step transfer
-    pub pending_authority: Pubkey,
-    
-    /// Current Merkle root
-    pub current_root: [u8; 32],
-    
-    /// Current batch ID
-    pub batch_id: u64,
-    
-    /// Number of authorized executors
-    pub executor_count: u8,
-    
-    /// Authorized executors
-    pub executors: [Pubkey; MAX_EXECUTORS],
-    
-    /// Bump seed for PDA
-    pub bump: u8,
-}
-
-impl SettlementState {
-    pub const LEN: usize = 8 + // discriminator
-        32 + // authority
-        32 + // pending_authority
-        32 + // current_root
-        8 +  // batch_id
-        1 +  // executor_count
-        (32 * MAX_EXECUTORS) + // executors
-        1;   // bump
-
-    pub fn is_executor(&self, pubkey: &Pubkey) -> bool {
-        if *pubkey == self.authority {
-            return true;
-        }
-        for i in 0..self.executor_count as usize {
-            if self.executors[i] == *pubkey {
-                return true;
-            }
-        }
-        false
-    }
-}
-
-/// Used commitment account (for replay protection)
-#[account]
-pub struct UsedCommitment {
-    /// The commitment that was used
-    pub commitment: [u8; 32],
-    
-    /// Batch ID when settled
-    pub batch_id: u64,
-    
-    /// Settlement timestamp
-    pub settled_at: i64,
-    
-    /// Executor who settled
-    pub executor: Pubkey,
-    
-    /// Bump seed for PDA
-    pub bump: u8,
-}
-
-impl UsedCommitment {
-    pub const LEN: usize = 8 + // discriminator
-        32 + // commitment
-        8 +  // batch_id
-        8 +  // settled_at
-        32 + // executor
-        1;   // bump
-}
-
-/// Batch root historical record
-#[account]
-pub struct BatchRoot {
-    /// Batch ID
-    pub batch_id: u64,
-    
-    /// Merkle root
-    pub root: [u8; 32],
-    
-    /// Timestamp when created
-    pub created_at: i64,
-    
-    /// Executor who submitted
-    pub executor: Pubkey,
-    
-    /// Bump seed for PDA
-    pub bump: u8,
-}
-
-impl BatchRoot {
-    pub const LEN: usize = 8 + // discriminator
-        8 +  // batch_id
-        32 + // root
-        8 +  // created_at
-        32 + // executor
-        1;   // bump
-}
+//! Program state definitions
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+/// Capacity of the legacy inline executor array (executors now live in
+/// `ExecutorRecord` PDAs; the array only drains during migration)
+pub const MAX_EXECUTORS: usize = 10;
+
+/// Window for executor rate limits (seconds)
+pub const RATE_LIMIT_WINDOW: i64 = 60 * 60;
+
+/// Maximum executor label length (bytes)
+pub const MAX_LABEL_LEN: usize = 32;
+
+/// Maximum proof length (tree depth)
+pub const MAX_PROOF_LENGTH: usize = 32;
+
+/// Leaf format of batches committed before leaf domain separation
+/// (the commitment itself is the leaf)
+pub const LEAF_FORMAT_RAW: u8 = 0;
+
+/// Leaf format with domain separation: leaf = keccak(0x00 || commitment)
+pub const LEAF_FORMAT_PREFIXED: u8 = 1;
+
+/// Leaf format bound to the deployment domain:
+/// leaf = keccak(0x00 || domain || commitment)
+pub const LEAF_FORMAT_DOMAIN: u8 = 2;
+
+/// Leaf node prefix of leaves that bind the intent's WOTS+ public key
+pub const WOTS_LEAF_PREFIX: u8 = 0x02;
+
+/// Leaf node binding a commitment to the WOTS+ key that signed it:
+/// keccak256(0x02 || commitment || public_key_hash)
+///
+/// The prefix keeps these leaves apart from plain leaves (0x00) and internal
+/// nodes (0x01), so they can only be settled through `settle_with_wots`.
+pub fn wots_leaf_node(commitment: &[u8; 32], public_key_hash: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&[WOTS_LEAF_PREFIX], commitment, public_key_hash]).to_bytes()
+}
+
+/// WOTS+ leaf node in a domain batch:
+/// keccak256(0x02 || domain || commitment || public_key_hash)
+pub fn wots_domain_leaf_node(
+    domain: &[u8; 32],
+    commitment: &[u8; 32],
+    public_key_hash: &[u8; 32],
+) -> [u8; 32] {
+    keccak::hashv(&[&[WOTS_LEAF_PREFIX], domain, commitment, public_key_hash]).to_bytes()
+}
+
+/// Domain tag of the message a WOTS+ key signs to burn itself
+pub const WOTS_BURN_TAG: &[u8] = b"SIP_WOTS_BURN_V1";
+
+/// Message a key signs to prove ownership before it is burned:
+/// keccak256("SIP_WOTS_BURN_V1" || public_key_hash)
+pub fn wots_burn_message(public_key_hash: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[WOTS_BURN_TAG, public_key_hash]).to_bytes()
+}
+
+/// Leaf node prefix of leaves that bind the release paid out for a commitment
+pub const RELEASE_LEAF_PREFIX: u8 = 0x03;
+
+/// Leaf node binding a commitment to its vault release:
+/// keccak256(0x03 || commitment || amount (u64 LE) || recipient || mint)
+///
+/// `mint` is the default pubkey for SOL. Like WOTS leaves, these can only be
+/// settled through the instruction that checks them, `settle_and_release`.
+pub fn release_leaf_node(
+    commitment: &[u8; 32],
+    amount: u64,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        &[RELEASE_LEAF_PREFIX],
+        commitment,
+        &amount.to_le_bytes(),
+        recipient.as_ref(),
+        mint.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// Release leaf node in a domain batch:
+/// keccak256(0x03 || domain || commitment || amount (u64 LE) || recipient || mint)
+pub fn release_domain_leaf_node(
+    domain: &[u8; 32],
+    commitment: &[u8; 32],
+    amount: u64,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        &[RELEASE_LEAF_PREFIX],
+        domain,
+        commitment,
+        &amount.to_le_bytes(),
+        recipient.as_ref(),
+        mint.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// Chains in a WOTS+ signature (w = 16)
+pub const WOTS_LEN: usize = obscura_wots::LEN;
+
+/// Trees hashed with keccak256 (EVM compatible)
+pub const HASH_FUNCTION_KECCAK: u8 = 0;
+
+/// Trees hashed with Poseidon over BN254 (ZK-circuit friendly)
+pub const HASH_FUNCTION_POSEIDON: u8 = 1;
+
+pub fn is_valid_hash_function(hash_function: u8) -> bool {
+    hash_function == HASH_FUNCTION_KECCAK || hash_function == HASH_FUNCTION_POSEIDON
+}
+
+/// Maximum approvals recorded on a root proposal (upper bound for the threshold)
+pub const MAX_ROOT_APPROVALS: usize = 16;
+
+/// Default lifetime of a root proposal (seconds)
+pub const DEFAULT_PROPOSAL_TTL: i64 = 60 * 60;
+
+/// Maximum guardians in a set (a full set of signatures must fit in one transaction)
+pub const MAX_GUARDIANS: usize = 12;
+
+/// Full configuration and role management
+pub const ROLE_ADMIN: u8 = 0;
+
+// Id 1 is not assigned: root submitters are the executor registry
+
+/// Pauses settlement in an emergency
+pub const ROLE_PAUSER: u8 = 3;
+
+/// Roles that can be assigned through role PDAs in this program
+pub fn is_valid_role(role: u8) -> bool {
+    matches!(role, ROLE_ADMIN | ROLE_PAUSER)
+}
+
+/// Settlement state account
+#[account]
+pub struct SettlementState {
+    /// Program authority (owner)
+    pub authority: Pubkey,
+    
+    /// Pending authority for two-step transfer
+    pub pending_authority: Pubkey,
+    
+    /// Current Merkle root
+    pub current_root: [u8; 32],
+    
+    /// Current batch ID
+    pub batch_id: u64,
+    
+    /// Number of executors left in the legacy array
+    pub executor_count: u8,
+    
+    /// Legacy executors not yet migrated to `ExecutorRecord`s
+    pub executors: [Pubkey; MAX_EXECUTORS],
+    
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Tree hash function for this deployment (fixed at initialization)
+    pub hash_function: u8,
+
+    /// Distinct executor approvals needed to activate a root (1 = direct update_root)
+    pub root_threshold: u8,
+
+    /// Lifetime of a root proposal (seconds)
+    pub proposal_ttl: i64,
+
+    /// Pause state for emergency (blocks root updates and settlement)
+    pub paused: bool,
+
+    /// Number of `ExecutorRecord`s
+    pub registered_executors: u32,
+
+    /// Lifetime of new batches (seconds, 0 = batches never expire)
+    pub batch_ttl: i64,
+
+    /// Domain separator of this cluster and program (zero until set)
+    pub domain: [u8; 32],
+}
+
+impl SettlementState {
+    /// Size of settlement state created before `hash_function` existed
+    pub const LEGACY_LEN: usize = 8 + // discriminator
+        32 + // authority
+        32 + // pending_authority
+        32 + // current_root
+        8 +  // batch_id
+        1 +  // executor_count
+        (32 * MAX_EXECUTORS) + // executors
+        1;   // bump
+
+    /// Size of settlement state created before the root quorum existed
+    pub const V1_LEN: usize = Self::LEGACY_LEN +
+        1;   // hash_function
+
+    /// Size of settlement state created before pausing existed
+    pub const V2_LEN: usize = Self::V1_LEN +
+        1 +  // root_threshold
+        8;   // proposal_ttl
+
+    /// Size of settlement state created before the executor registry existed
+    pub const V3_LEN: usize = Self::V2_LEN +
+        1;   // paused
+
+    /// Size of settlement state created before batch expiry existed
+    pub const V4_LEN: usize = Self::V3_LEN +
+        4;   // registered_executors
+
+    /// Size of settlement state created before domain separation existed
+    pub const V5_LEN: usize = Self::V4_LEN +
+        8;   // batch_ttl
+
+    pub const LEN: usize = Self::V5_LEN +
+        32;  // domain
+
+    pub fn has_domain(&self) -> bool {
+        self.domain != [0u8; 32]
+    }
+
+    /// Position of a key in the legacy executor array
+    pub fn legacy_executor_index(&self, pubkey: &Pubkey) -> Option<usize> {
+        self.executors[..self.executor_count as usize]
+            .iter()
+            .position(|executor| executor == pubkey)
+    }
+
+    /// Whether a root threshold can be met by the registered executors
+    pub fn is_reachable_threshold(&self, threshold: u8) -> bool {
+        threshold == 1 || threshold as u32 <= self.registered_executors
+    }
+}
+
+/// Authorized executor (PDA per key; holds the root-submitter role)
+#[account]
+pub struct ExecutorRecord {
+    /// Executor key
+    pub executor: Pubkey,
+
+    /// Operator label (UTF-8, zero-padded), e.g. region or host
+    pub label: [u8; MAX_LABEL_LEN],
+
+    /// Timestamp when added
+    pub added_at: i64,
+
+    /// Admin who added the executor
+    pub added_by: Pubkey,
+
+    /// Last batch this executor submitted, proposed or approved
+    pub last_active_batch: u64,
+
+    /// Maximum root submissions per `RATE_LIMIT_WINDOW` (0 = unlimited)
+    pub rate_limit: u16,
+
+    /// Start of the current rate limit window
+    pub window_start: i64,
+
+    /// Root submissions in the current window
+    pub window_count: u16,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ExecutorRecord {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // executor
+        MAX_LABEL_LEN + // label
+        8 +  // added_at
+        32 + // added_by
+        8 +  // last_active_batch
+        2 +  // rate_limit
+        8 +  // window_start
+        2 +  // window_count
+        1;   // bump
+
+    /// Count a root submission, returning false when over the rate limit
+    pub fn record_submission(&mut self, now: i64) -> bool {
+        if now >= self.window_start + RATE_LIMIT_WINDOW {
+            self.window_start = now;
+            self.window_count = 0;
+        }
+        if self.rate_limit != 0 && self.window_count >= self.rate_limit {
+            return false;
+        }
+        // Unlimited executors can exceed u16 within a window
+        self.window_count = self.window_count.saturating_add(1);
+        true
+    }
+}
+
+/// Used commitment account (for replay protection)
+#[account]
+pub struct UsedCommitment {
+    /// The commitment that was used
+    pub commitment: [u8; 32],
+    
+    /// Batch ID when settled
+    pub batch_id: u64,
+    
+    /// Settlement timestamp
+    pub settled_at: i64,
+    
+    /// Executor who settled
+    pub executor: Pubkey,
+    
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl UsedCommitment {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // commitment
+        8 +  // batch_id
+        8 +  // settled_at
+        32 + // executor
+        1;   // bump
+}
+
+/// Batch root historical record
+#[account]
+pub struct BatchRoot {
+    /// Batch ID
+    pub batch_id: u64,
+    
+    /// Merkle root
+    pub root: [u8; 32],
+    
+    /// Timestamp when created
+    pub created_at: i64,
+    
+    /// Executor who submitted
+    pub executor: Pubkey,
+    
+    /// Bump seed for PDA
+    pub bump: u8,
+
+    /// Tree depth (every proof against this root must have exactly this length)
+    pub depth: u8,
+
+    /// Leaf format (LEAF_FORMAT_RAW for migrated legacy batches)
+    pub leaf_format: u8,
+
+    /// No settlement against this batch after this time (0 = never expires)
+    pub expires_at: i64,
+}
+
+impl BatchRoot {
+    /// Size of batch records created before `depth` and `leaf_format` existed
+    pub const LEGACY_LEN: usize = 8 + // discriminator
+        8 +  // batch_id
+        32 + // root
+        8 +  // created_at
+        32 + // executor
+        1;   // bump
+
+    /// Size of batch records created before `expires_at` existed
+    pub const V1_LEN: usize = Self::LEGACY_LEN +
+        1 +  // depth
+        1;   // leaf_format
+
+    pub const LEN: usize = Self::V1_LEN +
+        8;   // expires_at
+
+    pub fn is_valid_depth(depth: u8) -> bool {
+        depth >= 1 && depth as usize <= MAX_PROOF_LENGTH
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now > self.expires_at
+    }
+}
+
+/// Role assignment (PDA per role and holder; exists only while granted)
+#[account]
+pub struct RoleAssignment {
+    /// Role identifier (ROLE_*)
+    pub role: u8,
+
+    /// Key holding the role
+    pub holder: Pubkey,
+
+    /// Admin who granted the role
+    pub granted_by: Pubkey,
+
+    /// Timestamp when granted
+    pub granted_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl RoleAssignment {
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // role
+        32 + // holder
+        32 + // granted_by
+        8 +  // granted_at
+        1;   // bump
+}
+
+/// Root proposal awaiting executor approvals
+#[account]
+pub struct PendingRoot {
+    /// Proposed Merkle root
+    pub root: [u8; 32],
+
+    /// Tree depth of the proposed root
+    pub depth: u8,
+
+    /// Batch ID the root becomes when executed
+    pub target_batch_id: u64,
+
+    /// Executor who proposed (receives rent on close)
+    pub proposer: Pubkey,
+
+    /// Timestamp when proposed
+    pub proposed_at: i64,
+
+    /// Proposal cannot be approved or executed after this time
+    pub expires_at: i64,
+
+    /// Number of approvals
+    pub approval_count: u8,
+
+    /// Executors who approved (proposer first)
+    pub approvals: [Pubkey; MAX_ROOT_APPROVALS],
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl PendingRoot {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // root
+        1 +  // depth
+        8 +  // target_batch_id
+        32 + // proposer
+        8 +  // proposed_at
+        8 +  // expires_at
+        1 +  // approval_count
+        (32 * MAX_ROOT_APPROVALS) + // approvals
+        1;   // bump
+
+    pub fn has_approved(&self, executor: &Pubkey) -> bool {
+        self.approvals[..self.approval_count as usize].contains(executor)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+}
+
+/// Scratch account for verifying a WOTS+ signature over several instructions
+#[account]
+pub struct WotsVerification {
+    /// Commitment (intent hash) the signature is over
+    pub commitment: [u8; 32],
+
+    /// Hash of the one-time public key committed in the leaf
+    pub public_key_hash: [u8; 32],
+
+    /// Creator (writes, verifies, settles and receives rent on close)
+    pub owner: Pubkey,
+
+    /// Signature elements written so far
+    pub written: u8,
+
+    /// Chains completed so far
+    pub completed: u8,
+
+    /// Whether the recovered public key matched `public_key_hash`
+    pub verified: bool,
+
+    /// Key tree the key was proven to belong to (default if unbound)
+    pub key_tree: Pubkey,
+
+    /// Position of the key in `key_tree`
+    pub key_index: u32,
+
+    /// Signature elements; each is replaced by its public key element once
+    /// its chain is completed
+    pub chains: [[u8; 32]; WOTS_LEN],
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl WotsVerification {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // commitment
+        32 + // public_key_hash
+        32 + // owner
+        1 +  // written
+        1 +  // completed
+        1 +  // verified
+        32 + // key_tree
+        4 +  // key_index
+        (32 * WOTS_LEN) + // chains
+        1;   // bump
+}
+
+/// A user's pre-generated WOTS+ keys, committed by the root of a key pool tree
+#[account]
+pub struct KeyTree {
+    /// User who registered the keys
+    pub owner: Pubkey,
+
+    /// Root of the key pool tree over public key hashes
+    pub root: [u8; 32],
+
+    /// Tree depth (2^depth keys)
+    pub depth: u8,
+
+    /// Timestamp when registered
+    pub registered_at: i64,
+
+    /// Keys consumed so far
+    pub consumed: u32,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl KeyTree {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // owner
+        32 + // root
+        1 +  // depth
+        8 +  // registered_at
+        4 +  // consumed
+        1;   // bump
+}
+
+/// Consumed WOTS+ public key (PDA per key hash; its existence rejects reuse)
+#[account]
+pub struct ConsumedKey {
+    /// Hash of the consumed public key
+    pub public_key_hash: [u8; 32],
+
+    /// Key tree the key belongs to (default if not registered)
+    pub key_tree: Pubkey,
+
+    /// Position of the key in `key_tree`
+    pub key_index: u32,
+
+    /// Commitment the key signed (zero when burned without settling)
+    pub commitment: [u8; 32],
+
+    /// Timestamp when consumed
+    pub consumed_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ConsumedKey {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // public_key_hash
+        32 + // key_tree
+        4 +  // key_index
+        32 + // commitment
+        8 +  // consumed_at
+        1;   // bump
+}
+
+/// Guardians attesting roots of the EVM SIPSettlement for mirroring
+#[account]
+pub struct GuardianSet {
+    /// EVM chain the roots are mirrored from
+    pub source_chain_id: u64,
+
+    /// SIPSettlement contract on the source chain
+    pub source_contract: [u8; 20],
+
+    /// Number of guardians
+    pub guardian_count: u8,
+
+    /// Guardian Ethereum addresses (secp256k1 keys)
+    pub guardians: [[u8; 20]; MAX_GUARDIANS],
+
+    /// Distinct guardian signatures needed to mirror a root
+    pub threshold: u8,
+
+    /// Last mirrored EVM batch ID (attestations must be newer)
+    pub last_source_batch_id: u64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl GuardianSet {
+    pub const LEN: usize = 8 + // discriminator
+        8 +  // source_chain_id
+        20 + // source_contract
+        1 +  // guardian_count
+        (20 * MAX_GUARDIANS) + // guardians
+        1 +  // threshold
+        8 +  // last_source_batch_id
+        1;   // bump
+
+    /// Guardians must be distinct non-zero addresses and the threshold reachable
+    pub fn is_valid_config(guardians: &[[u8; 20]], threshold: u8) -> bool {
+        if guardians.is_empty() || guardians.len() > MAX_GUARDIANS {
+            return false;
+        }
+        if threshold == 0 || threshold as usize > guardians.len() {
+            return false;
+        }
+        guardians.iter().enumerate().all(|(i, guardian)| {
+            *guardian != [0u8; 20] && !guardians[..i].contains(guardian)
+        })
+    }
+
+    pub fn set_guardians(&mut self, guardians: &[[u8; 20]], threshold: u8) {
+        self.guardians = [[0u8; 20]; MAX_GUARDIANS];
+        self.guardians[..guardians.len()].copy_from_slice(guardians);
+        self.guardian_count = guardians.len() as u8;
+        self.threshold = threshold;
+    }
+}
@@ -0,0 +1,133 @@
+//! Guardian attestation fixtures
+//!
+//! `tests/fixtures/guardian_attestations.json` holds deterministic guardian
+//! keys (secret = keccak256("guardian-<i>")) and attestations they signed, so
+//! relayers and guardian nodes can check their encoding against the program.
+
+use serde::Deserialize;
+use sip_settlement::attestation::*;
+
+#[derive(Deserialize)]
+struct Fixtures {
+    guardians: Vec<GuardianFixture>,
+    attestations: Vec<AttestationFixture>,
+}
+
+#[derive(Deserialize)]
+struct GuardianFixture {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct AttestationFixture {
+    target_domain: String,
+    source_chain_id: u64,
+    source_contract: String,
+    source_batch_id: u64,
+    root: String,
+    depth: u8,
+    digest: String,
+    signatures: Vec<SignatureFixture>,
+}
+
+#[derive(Deserialize)]
+struct SignatureFixture {
+    guardian_index: u8,
+    signature: String,
+    recovery_id: u8,
+}
+
+fn bytes<const N: usize>(value: &str) -> [u8; N] {
+    hex::decode(value).unwrap().try_into().unwrap()
+}
+
+fn fixtures() -> Fixtures {
+    serde_json::from_str(include_str!("fixtures/guardian_attestations.json")).unwrap()
+}
+
+fn signature(fixture: &SignatureFixture) -> GuardianSignature {
+    GuardianSignature {
+        guardian_index: fixture.guardian_index,
+        signature: bytes(&fixture.signature),
+        recovery_id: fixture.recovery_id,
+    }
+}
+
+fn digest(attestation: &AttestationFixture) -> [u8; 32] {
+    attestation_digest(
+        &bytes(&attestation.target_domain),
+        attestation.source_chain_id,
+        &bytes(&attestation.source_contract),
+        attestation.source_batch_id,
+        &bytes(&attestation.root),
+        attestation.depth,
+    )
+}
+
+#[test]
+fn attestation_digests_match_fixtures() {
+    for attestation in fixtures().attestations {
+        assert_eq!(digest(&attestation), bytes::<32>(&attestation.digest));
+    }
+}
+
+#[test]
+fn signatures_recover_their_guardians() {
+    let fixtures = fixtures();
+    for attestation in &fixtures.attestations {
+        let digest = digest(attestation);
+        for fixture in &attestation.signatures {
+            let guardian = &fixtures.guardians[fixture.guardian_index as usize];
+            assert_eq!(
+                recover_signer(&digest, &signature(fixture)),
+                Some(bytes::<20>(&guardian.address))
+            );
+        }
+    }
+}
+
+#[test]
+fn ethereum_recovery_ids_are_accepted() {
+    let fixtures = fixtures();
+    let attestation = &fixtures.attestations[0];
+    let fixture = &attestation.signatures[0];
+    let mut eth_signature = signature(fixture);
+    eth_signature.recovery_id += 27;
+
+    assert_eq!(
+        recover_signer(&digest(attestation), &eth_signature),
+        recover_signer(&digest(attestation), &signature(fixture))
+    );
+}
+
+#[test]
+fn tampered_attestations_do_not_recover_guardians() {
+    let fixtures = fixtures();
+    let attestation = &fixtures.attestations[0];
+    let fixture = &attestation.signatures[0];
+    let guardian = bytes::<20>(&fixtures.guardians[fixture.guardian_index as usize].address);
+
+    let domain = bytes::<32>(&attestation.target_domain);
+    let contract = bytes(&attestation.source_contract);
+    let (chain_id, batch_id, depth) = (
+        attestation.source_chain_id,
+        attestation.source_batch_id,
+        attestation.depth,
+    );
+    let root = bytes::<32>(&attestation.root);
+    let mut other_root = root;
+    other_root[0] ^= 1;
+    let other_domain = bytes::<32>(&fixtures.attestations[2].target_domain);
+
+    let tampered = [
+        attestation_digest(&other_domain, chain_id, &contract, batch_id, &root, depth),
+        attestation_digest(&domain, chain_id + 1, &contract, batch_id, &root, depth),
+        attestation_digest(&domain, chain_id, &[0x11; 20], batch_id, &root, depth),
+        attestation_digest(&domain, chain_id, &contract, batch_id + 1, &root, depth),
+        attestation_digest(&domain, chain_id, &contract, batch_id, &other_root, depth),
+        attestation_digest(&domain, chain_id, &contract, batch_id, &root, depth + 1),
+    ];
+    for digest in tampered {
+        assert_ne!(recover_signer(&digest, &signature(fixture)), Some(guardian));
+    }
+}
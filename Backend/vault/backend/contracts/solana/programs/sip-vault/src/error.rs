@@ -33,4 +33,22 @@ pub enum VaultError {
 
     #[msg("Invalid commitment")]
     InvalidCommitment,
+
+    #[msg("Invalid hash function")]
+    InvalidHashFunction,
+
+    #[msg("Vault state is not in the legacy layout")]
+    NotLegacyVaultState,
+
+    #[msg("Invalid role")]
+    InvalidRole,
+
+    #[msg("Domain already set")]
+    DomainAlreadySet,
+
+    #[msg("Invalid genesis hash")]
+    InvalidGenesisHash,
+
+    #[msg("Token account does not match the released mint")]
+    MintMismatch,
 }
@@ -0,0 +1,71 @@
+//! Vault events
+//!
+//! Typed records of every state change for indexers. Deposit and withdrawal
+//! events carry the commitment and asset but never the amount, depositor or
+//! recipient.
+
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct Deposit {
+    pub commitment: [u8; 32],
+    /// Pubkey::default() for SOL
+    pub token_mint: Pubkey,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct Withdrawal {
+    pub commitment: [u8; 32],
+    /// Pubkey::default() for SOL
+    pub token_mint: Pubkey,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct SettlementChanged {
+    pub old_settlement: Pubkey,
+    pub new_settlement: Pubkey,
+}
+
+#[event]
+pub struct DomainSet {
+    pub genesis_hash: [u8; 32],
+    pub domain: [u8; 32],
+}
+
+#[event]
+pub struct RoleGranted {
+    pub role: u8,
+    pub holder: Pubkey,
+    pub granted_by: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub role: u8,
+    pub holder: Pubkey,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferStarted {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferAccepted {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct Paused {
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct Unpaused {
+    pub admin: Pubkey,
+}
@@ -1,10 +1,15 @@
 //! Vault instructions
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::poseidon::{self, Endianness, Parameters};
 use anchor_lang::solana_program::{keccak, system_instruction};
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use obscura_merkle::{domain_field_elements, domain_separator};
 
 use crate::error::VaultError;
+use crate::events::*;
 use crate::state::*;
 
 /// Seeds for PDAs
@@ -12,6 +17,7 @@ pub const VAULT_SEED: &[u8] = b"sip_vault";
 pub const DEPOSIT_SEED: &[u8] = b"deposit";
 pub const COMMITMENT_SEED: &[u8] = b"used_commitment";
 pub const TOKEN_VAULT_SEED: &[u8] = b"token_vault";
+pub const ROLE_SEED: &[u8] = b"role";
 
 // ============ Initialize ============
 
@@ -32,16 +38,20 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+pub fn initialize(ctx: Context<Initialize>, hash_function: u8) -> Result<()> {
+    require!(is_valid_hash_function(hash_function), VaultError::InvalidHashFunction);
+
     let state = &mut ctx.accounts.vault_state;
     state.authority = ctx.accounts.authority.key();
     state.pending_authority = Pubkey::default();
-    state.settlement = ctx.accounts.authority.key(); // Initially, authority is also settlement
+    state.settlement = Pubkey::default(); // Set by an admin; the authority cannot withdraw
     state.sol_balance = 0;
     state.deposit_nonce = 0;
     state.withdrawal_nonce = 0;
     state.paused = false;
     state.bump = ctx.bumps.vault_state;
+    state.hash_function = hash_function;
+    state.domain = [0u8; 32];
 
     msg!("Vault initialized. Authority: {}", state.authority);
     Ok(())
@@ -103,12 +113,14 @@ pub fn deposit_native(ctx: Context<DepositNative>, amount: u64) -> Result<()> {
 
     // Compute commitment
     let commitment = compute_deposit_commitment(
+        state.hash_function,
+        &state.domain,
         &ctx.accounts.depositor.key(),
         amount,
         &Pubkey::default(), // SOL = default pubkey
         state.deposit_nonce,
         Clock::get()?.unix_timestamp,
-    );
+    )?;
 
     // Store deposit record
     let record = &mut ctx.accounts.deposit_record;
@@ -120,7 +132,12 @@ pub fn deposit_native(ctx: Context<DepositNative>, amount: u64) -> Result<()> {
     record.nonce = state.deposit_nonce;
     record.bump = ctx.bumps.deposit_record;
 
-    msg!("Deposited {} lamports. Commitment: {:?}", amount, commitment);
+    emit!(Deposit {
+        commitment,
+        token_mint: Pubkey::default(),
+        nonce: state.deposit_nonce,
+    });
+    msg!("Deposit recorded: nonce={}", state.deposit_nonce);
     Ok(())
 }
 
@@ -181,12 +198,14 @@ pub fn deposit_token(ctx: Context<DepositToken>, amount: u64) -> Result<()> {
     // Compute commitment
     let mint = ctx.accounts.depositor_token_account.mint;
     let commitment = compute_deposit_commitment(
+        state.hash_function,
+        &state.domain,
         &ctx.accounts.depositor.key(),
         amount,
         &mint,
         state.deposit_nonce,
         Clock::get()?.unix_timestamp,
-    );
+    )?;
 
     // Store deposit record
     let record = &mut ctx.accounts.deposit_record;
@@ -198,7 +217,12 @@ pub fn deposit_token(ctx: Context<DepositToken>, amount: u64) -> Result<()> {
     record.nonce = state.deposit_nonce;
     record.bump = ctx.bumps.deposit_record;
 
-    msg!("Deposited {} tokens. Commitment: {:?}", amount, commitment);
+    emit!(Deposit {
+        commitment,
+        token_mint: mint,
+        nonce: state.deposit_nonce,
+    });
+    msg!("Deposit recorded: nonce={}", state.deposit_nonce);
     Ok(())
 }
 
@@ -227,6 +251,13 @@ pub struct WithdrawNative<'info> {
     #[account(mut)]
     pub recipient: AccountInfo<'info>,
 
+    /// Withdrawer role of the executor (not needed for the settlement key)
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_WITHDRAWER], executor.key().as_ref()],
+        bump = withdrawer_role.bump
+    )]
+    pub withdrawer_role: Option<Account<'info, RoleAssignment>>,
+
     #[account(mut)]
     pub executor: Signer<'info>,
 
@@ -242,35 +273,26 @@ pub fn withdraw_native(
     
     // Check authorization
     require!(
-        state.is_authorized(&ctx.accounts.executor.key()),
+        state.is_authorized(
+            &ctx.accounts.executor.key(),
+            ctx.accounts.withdrawer_role.is_some()
+        ),
         VaultError::Unauthorized
     );
     require!(!state.paused, VaultError::VaultPaused);
     require!(amount > 0, VaultError::InvalidAmount);
     require!(state.sol_balance >= amount, VaultError::InsufficientBalance);
 
-    // Transfer SOL from vault PDA to recipient
-    let vault_state_info = ctx.accounts.vault_state.to_account_info();
-    **vault_state_info.try_borrow_mut_lamports()? -= amount;
-    **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
-
-    // Update state
-    let state = &mut ctx.accounts.vault_state;
-    state.sol_balance = state.sol_balance.checked_sub(amount).ok_or(VaultError::Overflow)?;
-    state.withdrawal_nonce += 1;
-
-    // Mark commitment as used
-    let used = &mut ctx.accounts.used_commitment;
-    used.commitment = commitment;
-    used.used_at = Clock::get()?.unix_timestamp;
-    used.executor = ctx.accounts.executor.key();
-    used.amount = amount;
-    used.recipient = ctx.accounts.recipient.key();
-    used.bump = ctx.bumps.used_commitment;
-
-    msg!("Withdrawn {} lamports to {}. Commitment: {:?}", 
-        amount, ctx.accounts.recipient.key(), commitment);
-    Ok(())
+    let executor = ctx.accounts.executor.key();
+    pay_out_native(
+        &mut ctx.accounts.vault_state,
+        &ctx.accounts.recipient,
+        &mut ctx.accounts.used_commitment,
+        commitment,
+        amount,
+        executor,
+        ctx.bumps.used_commitment,
+    )
 }
 
 // ============ Withdraw SPL Token ============
@@ -300,6 +322,13 @@ pub struct WithdrawToken<'info> {
     #[account(mut)]
     pub recipient_token_account: Account<'info, TokenAccount>,
 
+    /// Withdrawer role of the executor (not needed for the settlement key)
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_WITHDRAWER], executor.key().as_ref()],
+        bump = withdrawer_role.bump
+    )]
+    pub withdrawer_role: Option<Account<'info, RoleAssignment>>,
+
     #[account(mut)]
     pub executor: Signer<'info>,
 
@@ -315,40 +344,161 @@ pub fn withdraw_token(
     let state = &ctx.accounts.vault_state;
     
     require!(
-        state.is_authorized(&ctx.accounts.executor.key()),
+        state.is_authorized(
+            &ctx.accounts.executor.key(),
+            ctx.accounts.withdrawer_role.is_some()
+        ),
         VaultError::Unauthorized
     );
     require!(!state.paused, VaultError::VaultPaused);
     require!(amount > 0, VaultError::InvalidAmount);
 
-    // Transfer tokens from vault to recipient
-    let seeds = &[VAULT_SEED, &[state.bump]];
-    let signer = &[&seeds[..]];
-    
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.vault_token_account.to_account_info(),
-        to: ctx.accounts.recipient_token_account.to_account_info(),
-        authority: ctx.accounts.vault_state.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-    token::transfer(cpi_ctx, amount)?;
+    let executor = ctx.accounts.executor.key();
+    pay_out_token(
+        &mut ctx.accounts.vault_state,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.recipient_token_account,
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.used_commitment,
+        commitment,
+        amount,
+        executor,
+        ctx.bumps.used_commitment,
+    )
+}
 
-    // Update state
-    let state = &mut ctx.accounts.vault_state;
-    state.withdrawal_nonce += 1;
+// ============ Release (settlement CPI) ============
 
-    // Mark commitment as used
-    let used = &mut ctx.accounts.used_commitment;
-    used.commitment = commitment;
-    used.used_at = Clock::get()?.unix_timestamp;
-    used.executor = ctx.accounts.executor.key();
-    used.amount = amount;
-    used.recipient = ctx.accounts.recipient_token_account.key();
-    used.bump = ctx.bumps.used_commitment;
+/// Release SOL for a commitment settled by the settlement program
+///
+/// Called by `sip_settlement::settle_and_release` with its state PDA as
+/// `settlement` signer, so settlement and release are one transaction. The
+/// amount and recipient are the ones its settled leaf commits to.
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32], amount: u64)]
+pub struct ReleaseNative<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED],
+        bump = vault_state.bump,
+        has_one = settlement
+    )]
+    pub vault_state: Account<'info, VaultState>,
 
-    msg!("Withdrawn {} tokens. Commitment: {:?}", amount, commitment);
-    Ok(())
+    #[account(
+        init,
+        payer = payer,
+        space = UsedCommitment::LEN,
+        seeds = [COMMITMENT_SEED, &commitment],
+        bump
+    )]
+    pub used_commitment: Account<'info, UsedCommitment>,
+
+    /// CHECK: Recipient receives SOL
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    /// Settlement key (the settlement program's state PDA)
+    pub settlement: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn release_native(
+    ctx: Context<ReleaseNative>,
+    commitment: [u8; 32],
+    amount: u64,
+    recipient: Pubkey,
+) -> Result<()> {
+    let state = &ctx.accounts.vault_state;
+    require!(!state.paused, VaultError::VaultPaused);
+    require!(amount > 0, VaultError::InvalidAmount);
+    require_keys_eq!(ctx.accounts.recipient.key(), recipient, VaultError::InvalidRecipient);
+    require!(state.sol_balance >= amount, VaultError::InsufficientBalance);
+
+    let settlement = ctx.accounts.settlement.key();
+    pay_out_native(
+        &mut ctx.accounts.vault_state,
+        &ctx.accounts.recipient,
+        &mut ctx.accounts.used_commitment,
+        commitment,
+        amount,
+        settlement,
+        ctx.bumps.used_commitment,
+    )
+}
+
+/// Release SPL tokens for a commitment settled by the settlement program
+///
+/// `recipient` owns the receiving token account; both token accounts must
+/// hold `mint`.
+#[derive(Accounts)]
+#[instruction(commitment: [u8; 32], amount: u64)]
+pub struct ReleaseToken<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED],
+        bump = vault_state.bump,
+        has_one = settlement
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = UsedCommitment::LEN,
+        seeds = [COMMITMENT_SEED, &commitment],
+        bump
+    )]
+    pub used_commitment: Account<'info, UsedCommitment>,
+
+    #[account(mut)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Settlement key (the settlement program's state PDA)
+    pub settlement: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn release_token(
+    ctx: Context<ReleaseToken>,
+    commitment: [u8; 32],
+    amount: u64,
+    recipient: Pubkey,
+    mint: Pubkey,
+) -> Result<()> {
+    let state = &ctx.accounts.vault_state;
+    require!(!state.paused, VaultError::VaultPaused);
+    require!(amount > 0, VaultError::InvalidAmount);
+
+    let recipient_token_account = &ctx.accounts.recipient_token_account;
+    require_keys_eq!(recipient_token_account.owner, recipient, VaultError::InvalidRecipient);
+    require_keys_eq!(recipient_token_account.mint, mint, VaultError::MintMismatch);
+    require_keys_eq!(ctx.accounts.vault_token_account.mint, mint, VaultError::MintMismatch);
+
+    let settlement = ctx.accounts.settlement.key();
+    pay_out_token(
+        &mut ctx.accounts.vault_state,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.recipient_token_account,
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.used_commitment,
+        commitment,
+        amount,
+        settlement,
+        ctx.bumps.used_commitment,
+    )
 }
 
 // ============ Admin Functions ============
@@ -358,12 +508,17 @@ pub struct SetSettlement<'info> {
     #[account(
         mut,
         seeds = [VAULT_SEED],
-        bump = vault_state.bump,
-        has_one = authority
+        bump = vault_state.bump
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    pub authority: Signer<'info>,
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
 }
 
 pub fn set_settlement(ctx: Context<SetSettlement>, settlement: Pubkey) -> Result<()> {
@@ -371,21 +526,65 @@ pub fn set_settlement(ctx: Context<SetSettlement>, settlement: Pubkey) -> Result
     let old_settlement = state.settlement;
     state.settlement = settlement;
     
+    emit!(SettlementChanged {
+        old_settlement,
+        new_settlement: settlement,
+    });
     msg!("Settlement updated: {} -> {}", old_settlement, settlement);
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct SetDomain<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+/// The genesis hash is supplied by the admin (`solana genesis-hash`);
+/// deposits made before the domain is set keep their commitments.
+pub fn set_domain(ctx: Context<SetDomain>, genesis_hash: [u8; 32]) -> Result<()> {
+    require!(genesis_hash != [0u8; 32], VaultError::InvalidGenesisHash);
+
+    let state = &mut ctx.accounts.vault_state;
+    require!(!state.has_domain(), VaultError::DomainAlreadySet);
+    state.domain = domain_separator(&genesis_hash, &crate::ID.to_bytes());
+
+    emit!(DomainSet {
+        genesis_hash,
+        domain: state.domain,
+    });
+    msg!("Domain set");
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct TransferAuthority<'info> {
     #[account(
         mut,
         seeds = [VAULT_SEED],
-        bump = vault_state.bump,
-        has_one = authority
+        bump = vault_state.bump
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    pub authority: Signer<'info>,
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
 }
 
 pub fn transfer_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
@@ -394,6 +593,10 @@ pub fn transfer_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey
     let state = &mut ctx.accounts.vault_state;
     state.pending_authority = new_authority;
     
+    emit!(AuthorityTransferStarted {
+        authority: state.authority,
+        pending_authority: new_authority,
+    });
     msg!("Authority transfer initiated to: {}", new_authority);
     Ok(())
 }
@@ -426,6 +629,10 @@ pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
     state.authority = state.pending_authority;
     state.pending_authority = Pubkey::default();
 
+    emit!(AuthorityTransferAccepted {
+        old_authority,
+        new_authority: state.authority,
+    });
     msg!("Authority transferred: {} -> {}", old_authority, state.authority);
     Ok(())
 }
@@ -435,56 +642,475 @@ pub struct Pause<'info> {
     #[account(
         mut,
         seeds = [VAULT_SEED],
-        bump = vault_state.bump,
-        has_one = authority
+        bump = vault_state.bump
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    pub authority: Signer<'info>,
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_PAUSER], pauser.key().as_ref()],
+        bump = pauser_role.bump
+    )]
+    pub pauser_role: Account<'info, RoleAssignment>,
+
+    pub pauser: Signer<'info>,
 }
 
 pub fn pause(ctx: Context<Pause>) -> Result<()> {
     let state = &mut ctx.accounts.vault_state;
     state.paused = true;
+    emit!(Paused {
+        pauser: ctx.accounts.pauser.key(),
+    });
     msg!("Vault paused");
     Ok(())
 }
 
+/// Unpausing needs an admin so a compromised pauser key cannot undo a pause
 #[derive(Accounts)]
 pub struct Unpause<'info> {
     #[account(
         mut,
         seeds = [VAULT_SEED],
-        bump = vault_state.bump,
-        has_one = authority
+        bump = vault_state.bump
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    pub authority: Signer<'info>,
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
 }
 
 pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
     let state = &mut ctx.accounts.vault_state;
     state.paused = false;
+    emit!(Unpaused {
+        admin: ctx.accounts.admin.key(),
+    });
     msg!("Vault unpaused");
     Ok(())
 }
 
+// ============ Roles ============
+
+#[derive(Accounts)]
+#[instruction(role: u8, holder: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [VAULT_SEED],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = RoleAssignment::LEN,
+        seeds = [ROLE_SEED, &[role], holder.as_ref()],
+        bump
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    /// Admin role of the signer (not needed for the authority)
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Option<Account<'info, RoleAssignment>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn grant_role(ctx: Context<GrantRole>, role: u8, holder: Pubkey) -> Result<()> {
+    let state = &ctx.accounts.vault_state;
+    let admin = ctx.accounts.admin.key();
+
+    // The authority can always manage roles so admins can be recovered
+    require!(
+        admin == state.authority || ctx.accounts.admin_role.is_some(),
+        VaultError::Unauthorized
+    );
+    require!(is_valid_role(role), VaultError::InvalidRole);
+    require!(holder != Pubkey::default(), VaultError::InvalidRole);
+
+    let assignment = &mut ctx.accounts.role_assignment;
+    assignment.role = role;
+    assignment.holder = holder;
+    assignment.granted_by = admin;
+    assignment.granted_at = Clock::get()?.unix_timestamp;
+    assignment.bump = ctx.bumps.role_assignment;
+
+    emit!(RoleGranted {
+        role,
+        holder,
+        granted_by: admin,
+    });
+    msg!("Role {} granted to {}", role, holder);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(role: u8, holder: Pubkey)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [VAULT_SEED],
+        bump = vault_state.bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        mut,
+        seeds = [ROLE_SEED, &[role], holder.as_ref()],
+        bump = role_assignment.bump,
+        close = admin
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    /// Admin role of the signer (not needed for the authority)
+    #[account(
+        seeds = [ROLE_SEED, &[ROLE_ADMIN], admin.key().as_ref()],
+        bump = admin_role.bump
+    )]
+    pub admin_role: Option<Account<'info, RoleAssignment>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn revoke_role(ctx: Context<RevokeRole>, role: u8, holder: Pubkey) -> Result<()> {
+    let state = &ctx.accounts.vault_state;
+    let admin = ctx.accounts.admin.key();
+
+    require!(
+        admin == state.authority || ctx.accounts.admin_role.is_some(),
+        VaultError::Unauthorized
+    );
+
+    emit!(RoleRevoked {
+        role,
+        holder,
+        revoked_by: admin,
+    });
+    msg!("Role {} revoked from {}", role, holder);
+    Ok(())
+}
+
+// ============ Migrations ============
+
+/// Signed by the authority: role PDAs can only be granted once the state
+/// has the current layout.
+#[derive(Accounts)]
+pub struct MigrateVaultState<'info> {
+    /// CHECK: Legacy layout cannot be deserialized as VaultState; owner,
+    /// discriminator, size and authority are checked in the handler
+    #[account(
+        mut,
+        seeds = [VAULT_SEED],
+        bump
+    )]
+    pub vault_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_vault_state(ctx: Context<MigrateVaultState>) -> Result<()> {
+    let state_info = ctx.accounts.vault_state.to_account_info();
+    let old_len = state_info.data_len();
+    require_keys_eq!(*state_info.owner, crate::ID, VaultError::NotLegacyVaultState);
+    require!(
+        old_len == VaultState::LEGACY_LEN || old_len == VaultState::V1_LEN,
+        VaultError::NotLegacyVaultState
+    );
+    {
+        let data = state_info.try_borrow_data()?;
+        require!(
+            data[..8] == VaultState::DISCRIMINATOR,
+            VaultError::NotLegacyVaultState
+        );
+        // authority is the first field
+        require!(
+            data[8..40] == ctx.accounts.authority.key().to_bytes(),
+            VaultError::Unauthorized
+        );
+    }
+
+    // The vault PDA holds deposits, so only the rent difference is added
+    let old_rent = Rent::get()?.minimum_balance(old_len);
+    let new_rent = Rent::get()?.minimum_balance(VaultState::LEN);
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: state_info.clone(),
+            },
+        ),
+        new_rent.saturating_sub(old_rent),
+    )?;
+
+    state_info.realloc(VaultState::LEN, false)?;
+    let mut data = state_info.try_borrow_mut_data()?;
+
+    // Existing deployments keep keccak commitments
+    if old_len == VaultState::LEGACY_LEN {
+        data[VaultState::LEGACY_LEN] = HASH_FUNCTION_KECCAK;
+    }
+
+    // No domain until the authority sets one
+    data[VaultState::V1_LEN..VaultState::LEN].fill(0);
+
+    msg!("Vault state migrated from {} bytes", old_len);
+    Ok(())
+}
+
 // ============ Helper Functions ============
 
+/// Move SOL from the vault PDA to the recipient and mark the commitment used
+fn pay_out_native<'info>(
+    vault_state: &mut Account<'info, VaultState>,
+    recipient: &AccountInfo<'info>,
+    used: &mut Account<'info, UsedCommitment>,
+    commitment: [u8; 32],
+    amount: u64,
+    executor: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    // Transfer SOL from vault PDA to recipient
+    let vault_state_info = vault_state.to_account_info();
+    **vault_state_info.try_borrow_mut_lamports()? -= amount;
+    **recipient.try_borrow_mut_lamports()? += amount;
+
+    // Update state
+    vault_state.sol_balance = vault_state
+        .sol_balance
+        .checked_sub(amount)
+        .ok_or(VaultError::Overflow)?;
+    vault_state.withdrawal_nonce += 1;
+
+    mark_used(used, commitment, executor, amount, recipient.key(), bump)?;
+
+    emit!(Withdrawal {
+        commitment,
+        token_mint: Pubkey::default(),
+        nonce: vault_state.withdrawal_nonce,
+    });
+    msg!("Withdrawal executed: nonce={}", vault_state.withdrawal_nonce);
+    Ok(())
+}
+
+/// Transfer tokens signed by the vault PDA and mark the commitment used
+#[allow(clippy::too_many_arguments)]
+fn pay_out_token<'info>(
+    vault_state: &mut Account<'info, VaultState>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    recipient_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    used: &mut Account<'info, UsedCommitment>,
+    commitment: [u8; 32],
+    amount: u64,
+    executor: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    // Transfer tokens from vault to recipient
+    let seeds = &[VAULT_SEED, &[vault_state.bump]];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: vault_token_account.to_account_info(),
+        to: recipient_token_account.to_account_info(),
+        authority: vault_state.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)?;
+
+    // Update state
+    vault_state.withdrawal_nonce += 1;
+
+    mark_used(used, commitment, executor, amount, recipient_token_account.key(), bump)?;
+
+    emit!(Withdrawal {
+        commitment,
+        token_mint: vault_token_account.mint,
+        nonce: vault_state.withdrawal_nonce,
+    });
+    msg!("Withdrawal executed: nonce={}", vault_state.withdrawal_nonce);
+    Ok(())
+}
+
+/// Record a used commitment (the account creation acts as replay protection)
+fn mark_used(
+    used: &mut UsedCommitment,
+    commitment: [u8; 32],
+    executor: Pubkey,
+    amount: u64,
+    recipient: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    used.commitment = commitment;
+    used.used_at = Clock::get()?.unix_timestamp;
+    used.executor = executor;
+    used.amount = amount;
+    used.recipient = recipient;
+    used.bump = bump;
+    Ok(())
+}
+
+/// Domain tag for deposit commitments
+const DEPOSIT_DOMAIN: &[u8] = b"SIP_DEPOSIT";
+
+/// keccak256("SIP_DEPOSIT" || [domain] || depositor || amount || mint || nonce || timestamp)
+///
+/// The domain separator is included once set (non-zero).
 fn compute_deposit_commitment(
+    hash_function: u8,
+    domain: &[u8; 32],
     depositor: &Pubkey,
     amount: u64,
     token_mint: &Pubkey,
     nonce: u64,
     timestamp: i64,
-) -> [u8; 32] {
-    let mut data = Vec::with_capacity(128);
-    data.extend_from_slice(b"SIP_DEPOSIT");
+) -> Result<[u8; 32]> {
+    let domain = (*domain != [0u8; 32]).then_some(domain);
+    if hash_function == HASH_FUNCTION_POSEIDON {
+        return poseidon_deposit_commitment(domain, depositor, amount, token_mint, nonce, timestamp);
+    }
+
+    let mut data = Vec::with_capacity(160);
+    data.extend_from_slice(DEPOSIT_DOMAIN);
+    if let Some(domain) = domain {
+        data.extend_from_slice(domain);
+    }
     data.extend_from_slice(depositor.as_ref());
     data.extend_from_slice(&amount.to_le_bytes());
     data.extend_from_slice(token_mint.as_ref());
     data.extend_from_slice(&nonce.to_le_bytes());
     data.extend_from_slice(&timestamp.to_le_bytes());
-    keccak::hash(&data).to_bytes()
+    Ok(keccak::hash(&data).to_bytes())
+}
+
+/// Poseidon(tag, [domain_hi, domain_lo,] depositor_hi, depositor_lo, amount,
+/// mint_hi, mint_lo, nonce, timestamp)
+///
+/// Pubkeys and the domain are split into 16-byte halves so every input is
+/// below the BN254 modulus. Inputs are 32-byte big-endian field elements.
+fn poseidon_deposit_commitment(
+    domain: Option<&[u8; 32]>,
+    depositor: &Pubkey,
+    amount: u64,
+    token_mint: &Pubkey,
+    nonce: u64,
+    timestamp: i64,
+) -> Result<[u8; 32]> {
+    let depositor = depositor.to_bytes();
+    let mint = token_mint.to_bytes();
+    let mut inputs = vec![field_element(DEPOSIT_DOMAIN)];
+    if let Some(domain) = domain {
+        inputs.extend(domain_field_elements(domain));
+    }
+    inputs.extend([
+        field_element(&depositor[..16]),
+        field_element(&depositor[16..]),
+        field_element(&amount.to_be_bytes()),
+        field_element(&mint[..16]),
+        field_element(&mint[16..]),
+        field_element(&nonce.to_be_bytes()),
+        field_element(&(timestamp as u64).to_be_bytes()),
+    ]);
+    let refs: Vec<&[u8]> = inputs.iter().map(|input| input.as_slice()).collect();
+
+    let hash = poseidon::hashv(Parameters::Bn254X5, Endianness::BigEndian, &refs)
+        .map_err(|_| VaultError::InvalidCommitment)?;
+    Ok(hash.to_bytes())
+}
+
+/// Right-align up to 31 bytes into a big-endian field element
+fn field_element(bytes: &[u8]) -> [u8; 32] {
+    let mut element = [0u8; 32];
+    element[32 - bytes.len()..].copy_from_slice(bytes);
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: [u8; 32]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// (hash_function, depositor, amount, mint, nonce, timestamp, commitment)
+    type Vector = (u8, [u8; 32], u64, [u8; 32], u64, i64, &'static str);
+
+    const VECTORS: &[Vector] = &[
+        (HASH_FUNCTION_KECCAK, [7u8; 32], 1_000_000_000, [0u8; 32], 1, 1_700_000_000,
+            "780cca7ef2df3a8dbfa9a5bef4654313ae8666a0cd4004abd68bb60ed4fdad7c"),
+        (HASH_FUNCTION_KECCAK, [7u8; 32], 250, [0xfe; 32], 42, 1_700_000_123,
+            "f898d69acded6e29bb4011e8170408a8acdc96bb272841b5f53f607f9637e106"),
+        (HASH_FUNCTION_POSEIDON, [7u8; 32], 1_000_000_000, [0u8; 32], 1, 1_700_000_000,
+            "2a8ea05b827d32a3d9f1d241480626a70c23dfc0f11de4f9a62ae6fcd715deaf"),
+        (HASH_FUNCTION_POSEIDON, [7u8; 32], 250, [0xfe; 32], 42, 1_700_000_123,
+            "2012f99dd9baacd16a813448979f79040adfb74dfc9bf9799ed47d80483db56b"),
+    ];
+
+    #[test]
+    fn deposit_commitment_vectors() {
+        for (hash_function, depositor, amount, mint, nonce, timestamp, expected) in VECTORS {
+            let commitment = compute_deposit_commitment(
+                *hash_function,
+                &[0u8; 32],
+                &Pubkey::new_from_array(*depositor),
+                *amount,
+                &Pubkey::new_from_array(*mint),
+                *nonce,
+                *timestamp,
+            )
+            .unwrap();
+            assert_eq!(hex(commitment), *expected);
+        }
+    }
+
+    #[test]
+    fn field_elements_fit_bn254() {
+        // A pubkey of 0xff bytes would exceed the modulus if not split
+        let max = Pubkey::new_from_array([0xff; 32]);
+        assert!(
+            compute_deposit_commitment(HASH_FUNCTION_POSEIDON, &[0xff; 32], &max, u64::MAX, &max, u64::MAX, i64::MAX)
+                .is_ok()
+        );
+    }
+
+    /// (hash_function, domain separator, commitment) for the first vector's inputs
+    const DOMAIN_VECTORS: &[(u8, [u8; 32], &str)] = &[
+        (HASH_FUNCTION_KECCAK, [0x3a; 32], "86497777d0515cd415e6410ca2275dc228000d48dad4cd2b43f6824d1aa2bbdb"),
+        (HASH_FUNCTION_POSEIDON, [0x3a; 32], "150a92b05ee427480564737171bbc6e8361671d6ff97b9b80cb7f88ddbc13fce"),
+    ];
+
+    #[test]
+    fn deposit_commitments_bind_the_domain() {
+        let (_, depositor, amount, mint, nonce, timestamp, legacy) = VECTORS[0];
+        for (hash_function, domain, expected) in DOMAIN_VECTORS {
+            let commitment = compute_deposit_commitment(
+                *hash_function,
+                domain,
+                &Pubkey::new_from_array(depositor),
+                amount,
+                &Pubkey::new_from_array(mint),
+                nonce,
+                timestamp,
+            )
+            .unwrap();
+            assert_eq!(hex(commitment), *expected);
+            assert_ne!(hex(commitment), legacy);
+        }
+    }
 }
@@ -9,11 +9,9 @@
 //! Key security properties:
 //! - Assets can only be released with valid authorization
 //! - Commitments can only be used once (replay protection)
-//! - Settlement contract is the only authority for releases
+//! - Only the settlement key or a withdrawer role holder can release assets
 
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::keccak;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 #[cfg(not(feature = "no-entrypoint"))]
 use solana_security_txt::security_txt;
@@ -28,9 +26,10 @@ security_txt! {
     auditors: "None"
 }
 
-declare_id!("VauLt1111111111111111111111111111111111111");
+declare_id!("VauLt11111111111111111111111111111111111111");
 
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
@@ -40,9 +39,9 @@ use instructions::*;
 pub mod sip_vault {
     use super::*;
 
-    /// Initialize the vault state
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        instructions::initialize(ctx)
+    /// Initialize the vault state with the deployment's commitment hash function
+    pub fn initialize(ctx: Context<Initialize>, hash_function: u8) -> Result<()> {
+        instructions::initialize(ctx, hash_function)
     }
 
     /// Deposit native SOL to vault
@@ -73,11 +72,47 @@ pub mod sip_vault {
         instructions::withdraw_token(ctx, commitment, amount)
     }
 
+    /// Grant a role to a key (admin or authority)
+    pub fn grant_role(ctx: Context<GrantRole>, role: u8, holder: Pubkey) -> Result<()> {
+        instructions::grant_role(ctx, role, holder)
+    }
+
+    /// Revoke a role from a key (admin or authority)
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: u8, holder: Pubkey) -> Result<()> {
+        instructions::revoke_role(ctx, role, holder)
+    }
+
+    /// Release SOL for a commitment (settlement program CPI)
+    pub fn release_native(
+        ctx: Context<ReleaseNative>,
+        commitment: [u8; 32],
+        amount: u64,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        instructions::release_native(ctx, commitment, amount, recipient)
+    }
+
+    /// Release SPL tokens for a commitment (settlement program CPI)
+    pub fn release_token(
+        ctx: Context<ReleaseToken>,
+        commitment: [u8; 32],
+        amount: u64,
+        recipient: Pubkey,
+        mint: Pubkey,
+    ) -> Result<()> {
+        instructions::release_token(ctx, commitment, amount, recipient, mint)
+    }
+
     /// Set settlement authority
     pub fn set_settlement(ctx: Context<SetSettlement>, settlement: Pubkey) -> Result<()> {
         instructions::set_settlement(ctx, settlement)
     }
 
+    /// Bind new deposit commitments to this cluster and program (once)
+    pub fn set_domain(ctx: Context<SetDomain>, genesis_hash: [u8; 32]) -> Result<()> {
+        instructions::set_domain(ctx, genesis_hash)
+    }
+
     /// Transfer authority (two-step pattern)
     pub fn transfer_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
         instructions::transfer_authority(ctx, new_authority)
@@ -97,4 +132,9 @@ pub mod sip_vault {
     pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
         instructions::unpause(ctx)
     }
+
+    /// Resize vault state created with an older layout
+    pub fn migrate_vault_state(ctx: Context<MigrateVaultState>) -> Result<()> {
+        instructions::migrate_vault_state(ctx)
+    }
 }
@@ -2,6 +2,32 @@
 
 use anchor_lang::prelude::*;
 
+/// Deposit commitments hashed with keccak256
+pub const HASH_FUNCTION_KECCAK: u8 = 0;
+
+/// Deposit commitments hashed with Poseidon over BN254
+pub const HASH_FUNCTION_POSEIDON: u8 = 1;
+
+pub fn is_valid_hash_function(hash_function: u8) -> bool {
+    hash_function == HASH_FUNCTION_KECCAK || hash_function == HASH_FUNCTION_POSEIDON
+}
+
+/// Full configuration and role management
+pub const ROLE_ADMIN: u8 = 0;
+
+// Id 1 is not assigned: root submitters are sip_settlement executors
+
+/// Releases vault funds for settled commitments
+pub const ROLE_WITHDRAWER: u8 = 2;
+
+/// Pauses the vault in an emergency
+pub const ROLE_PAUSER: u8 = 3;
+
+/// Roles that can be assigned in this program
+pub fn is_valid_role(role: u8) -> bool {
+    matches!(role, ROLE_ADMIN | ROLE_WITHDRAWER | ROLE_PAUSER)
+}
+
 /// Vault state account
 #[account]
 pub struct VaultState {
@@ -28,10 +54,17 @@ pub struct VaultState {
     
     /// Bump seed for PDA
     pub bump: u8,
+
+    /// Commitment hash function (must match the settlement deployment)
+    pub hash_function: u8,
+
+    /// Domain separator of this cluster and program (zero until set)
+    pub domain: [u8; 32],
 }
 
 impl VaultState {
-    pub const LEN: usize = 8 + // discriminator
+    /// Size of vault state created before `hash_function` existed
+    pub const LEGACY_LEN: usize = 8 + // discriminator
         32 + // authority
         32 + // pending_authority
         32 + // settlement
@@ -41,9 +74,49 @@ impl VaultState {
         1 +  // paused
         1;   // bump
 
-    pub fn is_authorized(&self, pubkey: &Pubkey) -> bool {
-        *pubkey == self.authority || *pubkey == self.settlement
+    /// Size of vault state created before domain separation existed
+    pub const V1_LEN: usize = Self::LEGACY_LEN +
+        1;   // hash_function
+
+    pub const LEN: usize = Self::V1_LEN +
+        32;  // domain
+
+    pub fn has_domain(&self) -> bool {
+        self.domain != [0u8; 32]
     }
+
+    /// Withdrawals are released by the settlement key or a withdrawer role holder
+    pub fn is_authorized(&self, pubkey: &Pubkey, has_withdrawer_role: bool) -> bool {
+        has_withdrawer_role || (*pubkey == self.settlement && self.settlement != Pubkey::default())
+    }
+}
+
+/// Role assignment (PDA per role and holder; exists only while granted)
+#[account]
+pub struct RoleAssignment {
+    /// Role identifier (ROLE_*)
+    pub role: u8,
+
+    /// Key holding the role
+    pub holder: Pubkey,
+
+    /// Admin who granted the role
+    pub granted_by: Pubkey,
+
+    /// Timestamp when granted
+    pub granted_at: i64,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl RoleAssignment {
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // role
+        32 + // holder
+        32 + // granted_by
+        8 +  // granted_at
+        1;   // bump
 }
 
 /// Used commitment account (for replay protection)
@@ -0,0 +1,179 @@
+//! obscura-merkle CLI
+//!
+//! Builds batch trees and proofs off-chain with the same code the programs
+//! verify with.
+//!
+//! Usage:
+//!   obscura-merkle root   --depth D [--hasher keccak|poseidon] [--raw] LEAF...
+//!   obscura-merkle proof  --depth D --index I [--hasher ..] [--raw] LEAF...
+//!   obscura-merkle verify --depth D --index I --root ROOT [--hasher ..] [--raw] LEAF SIBLING...
+//!   obscura-merkle domain --genesis HASH --program ID
+//!
+//! Leaves are 32-byte hex commitments hashed with the leaf domain, or leaf
+//! nodes as-is with `--raw` (legacy batches). `--domain SEPARATOR` builds
+//! leaves bound to a deployment domain; `domain` prints the separator for a
+//! cluster genesis hash and program id (base58, as printed by `solana`).
+
+use std::process::ExitCode;
+
+use obscura_merkle::{domain_separator, verify_proof, Hash, Hasher, Keccak, MerkleTree, Poseidon};
+
+struct Args {
+    command: String,
+    depth: u8,
+    index: Option<u64>,
+    root: Option<Hash>,
+    hasher: String,
+    raw: bool,
+    domain: Option<Hash>,
+    genesis: Option<Hash>,
+    program: Option<Hash>,
+    values: Vec<Hash>,
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1).collect()).and_then(|args| {
+        match args.hasher.as_str() {
+            "keccak" => run::<Keccak>(&args),
+            "poseidon" => run::<Poseidon>(&args),
+            other => Err(format!("unknown hasher: {}", other)),
+        }
+    }) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run<H: Hasher>(args: &Args) -> Result<(), String> {
+    match args.command.as_str() {
+        "root" => {
+            let tree = build_tree::<H>(args, &args.values)?;
+            println!("{}", hex::encode(tree.root()));
+        }
+        "proof" => {
+            let index = args.index.ok_or("--index is required")?;
+            let tree = build_tree::<H>(args, &args.values)?;
+            let proof = tree.proof(index).map_err(|e| e.to_string())?;
+            println!("root {}", hex::encode(tree.root()));
+            println!("leaf {}", hex::encode(tree.leaf_node(index).map_err(|e| e.to_string())?));
+            for sibling in &proof.siblings {
+                println!("sibling {}", hex::encode(sibling));
+            }
+        }
+        "verify" => {
+            let index = args.index.ok_or("--index is required")?;
+            let root = args.root.ok_or("--root is required")?;
+            let (leaf, siblings) = args.values.split_first().ok_or("missing leaf")?;
+            let leaf_node = leaf_node::<H>(args, leaf)?;
+            let valid = verify_proof::<H>(&leaf_node, siblings, index, args.depth, &root)
+                .map_err(|e| e.to_string())?;
+            println!("{}", if valid { "valid" } else { "invalid" });
+            if !valid {
+                return Err("proof does not match root".into());
+            }
+        }
+        "domain" => {
+            let genesis = args.genesis.ok_or("--genesis is required")?;
+            let program = args.program.ok_or("--program is required")?;
+            println!("{}", hex::encode(domain_separator(&genesis, &program)));
+        }
+        other => return Err(format!("unknown command: {}", other)),
+    }
+    Ok(())
+}
+
+fn build_tree<H: Hasher>(args: &Args, values: &[Hash]) -> Result<MerkleTree<H>, String> {
+    let nodes = values
+        .iter()
+        .map(|value| leaf_node::<H>(args, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    MerkleTree::<H>::from_leaf_nodes(args.depth, nodes).map_err(|e| e.to_string())
+}
+
+fn leaf_node<H: Hasher>(args: &Args, value: &Hash) -> Result<Hash, String> {
+    if args.raw {
+        Ok(*value)
+    } else if let Some(domain) = &args.domain {
+        H::hash_domain_leaf(domain, value).map_err(|e| e.to_string())
+    } else {
+        H::hash_leaf(value).map_err(|e| e.to_string())
+    }
+}
+
+fn parse_args(raw: Vec<String>) -> Result<Args, String> {
+    let mut iter = raw.into_iter();
+    let command = iter.next().ok_or("missing command (root, proof, verify, domain)")?;
+    let mut args = Args {
+        command,
+        depth: 0,
+        index: None,
+        root: None,
+        hasher: "keccak".into(),
+        raw: false,
+        domain: None,
+        genesis: None,
+        program: None,
+        values: Vec::new(),
+    };
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--depth" => args.depth = next_value(&mut iter, "--depth")?,
+            "--index" => args.index = Some(next_value(&mut iter, "--index")?),
+            "--root" => {
+                let value: String = next_value(&mut iter, "--root")?;
+                args.root = Some(parse_hash(&value)?);
+            }
+            "--hasher" => args.hasher = next_value(&mut iter, "--hasher")?,
+            "--raw" => args.raw = true,
+            "--domain" => {
+                let value: String = next_value(&mut iter, "--domain")?;
+                args.domain = Some(parse_hash(&value)?);
+            }
+            "--genesis" => {
+                let value: String = next_value(&mut iter, "--genesis")?;
+                args.genesis = Some(parse_base58(&value)?);
+            }
+            "--program" => {
+                let value: String = next_value(&mut iter, "--program")?;
+                args.program = Some(parse_base58(&value)?);
+            }
+            value => args.values.push(parse_hash(value)?),
+        }
+    }
+
+    if args.depth == 0 && args.command != "domain" {
+        return Err("--depth is required".into());
+    }
+    Ok(args)
+}
+
+fn next_value<T: std::str::FromStr>(
+    iter: &mut impl Iterator<Item = String>,
+    flag: &str,
+) -> Result<T, String> {
+    iter.next()
+        .ok_or_else(|| format!("{} needs a value", flag))?
+        .parse()
+        .map_err(|_| format!("invalid value for {}", flag))
+}
+
+fn parse_hash(value: &str) -> Result<Hash, String> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .map_err(|_| format!("invalid hex: {}", value))?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("expected 32 bytes: {}", value))
+}
+
+fn parse_base58(value: &str) -> Result<Hash, String> {
+    let bytes = bs58::decode(value)
+        .into_vec()
+        .map_err(|_| format!("invalid base58: {}", value))?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("expected 32 bytes: {}", value))
+}
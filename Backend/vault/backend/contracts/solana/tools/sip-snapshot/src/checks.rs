@@ -0,0 +1,367 @@
+//! Invariants over a snapshot
+//!
+//! Each check lists what it found wrong in `details`; informational notes
+//! (e.g. batches that were closed after expiring) do not fail a check.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::rent::Rent;
+use serde::Serialize;
+use sip_settlement::instructions as settlement_seeds;
+use sip_settlement::state::MAX_EXECUTORS;
+use sip_vault::instructions as vault_seeds;
+
+use crate::snapshot::{Decoded, Snapshot};
+
+/// Outcome of one invariant
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    pub details: Vec<String>,
+}
+
+impl Check {
+    fn new(name: &str) -> Self {
+        Check {
+            name: name.into(),
+            passed: true,
+            details: Vec::new(),
+        }
+    }
+
+    fn fail(&mut self, detail: String) {
+        self.passed = false;
+        self.details.push(detail);
+    }
+
+    fn note(&mut self, detail: String) {
+        self.details.push(detail);
+    }
+}
+
+/// Run every check, in a fixed order
+pub fn run_checks(snapshot: &Snapshot) -> Vec<Check> {
+    vec![
+        check_decodable(snapshot),
+        check_singletons(snapshot),
+        check_addresses(snapshot),
+        check_executor_count(snapshot),
+        check_batch_continuity(snapshot),
+        check_used_commitments(snapshot),
+        check_consumed_keys(snapshot),
+        check_vault_balance(snapshot),
+        check_vault_nonces(snapshot),
+        check_releases_settled(snapshot),
+    ]
+}
+
+fn check_decodable(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("accounts_decodable");
+    for (pubkey, reason) in &snapshot.undecodable {
+        check.fail(format!("{}: {}", pubkey, reason));
+    }
+    check
+}
+
+fn check_singletons(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("state_accounts");
+    for (name, count) in [
+        ("SettlementState", snapshot.settlement_states.len()),
+        ("VaultState", snapshot.vault_states.len()),
+    ] {
+        if count != 1 {
+            check.fail(format!("expected one {}, found {}", name, count));
+        }
+    }
+    if snapshot.guardian_sets.len() > 1 {
+        check.fail(format!("expected at most one GuardianSet, found {}", snapshot.guardian_sets.len()));
+    }
+    check
+}
+
+/// Every account sits at the PDA derived from its own fields and bump
+fn check_addresses(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("account_addresses");
+    let settlement = sip_settlement::ID;
+    let vault = sip_vault::ID;
+
+    for state in &snapshot.settlement_states {
+        let bump = [state.account.bump];
+        expect_pda(&mut check, state, &[settlement_seeds::SETTLEMENT_SEED, &bump], &settlement);
+    }
+    for batch in &snapshot.batch_roots {
+        let (id, bump) = (batch.account.batch_id.to_le_bytes(), [batch.account.bump]);
+        expect_pda(&mut check, batch, &[settlement_seeds::BATCH_SEED, &id, &bump], &settlement);
+    }
+    for used in &snapshot.used_commitments {
+        let bump = [used.account.bump];
+        let seeds: &[&[u8]] = &[settlement_seeds::COMMITMENT_SEED, &used.account.commitment, &bump];
+        expect_pda(&mut check, used, seeds, &settlement);
+    }
+    for pending in &snapshot.pending_roots {
+        let bump = [pending.account.bump];
+        let seeds: &[&[u8]] = &[settlement_seeds::PENDING_ROOT_SEED, &pending.account.root, &bump];
+        expect_pda(&mut check, pending, seeds, &settlement);
+    }
+    for role in &snapshot.settlement_roles {
+        let (role_id, bump) = ([role.account.role], [role.account.bump]);
+        let seeds: &[&[u8]] = &[settlement_seeds::ROLE_SEED, &role_id, role.account.holder.as_ref(), &bump];
+        expect_pda(&mut check, role, seeds, &settlement);
+    }
+    for record in &snapshot.executor_records {
+        let bump = [record.account.bump];
+        let seeds: &[&[u8]] = &[settlement_seeds::EXECUTOR_SEED, record.account.executor.as_ref(), &bump];
+        expect_pda(&mut check, record, seeds, &settlement);
+    }
+    for set in &snapshot.guardian_sets {
+        let bump = [set.account.bump];
+        expect_pda(&mut check, set, &[settlement_seeds::GUARDIAN_SET_SEED, &bump], &settlement);
+    }
+    for verification in &snapshot.wots_verifications {
+        let account = &verification.account;
+        let bump = [account.bump];
+        let seeds: &[&[u8]] = &[settlement_seeds::WOTS_SEED, account.owner.as_ref(), &account.commitment, &bump];
+        expect_pda(&mut check, verification, seeds, &settlement);
+    }
+    for tree in &snapshot.key_trees {
+        let bump = [tree.account.bump];
+        let seeds: &[&[u8]] = &[settlement_seeds::KEY_TREE_SEED, tree.account.owner.as_ref(), &tree.account.root, &bump];
+        expect_pda(&mut check, tree, seeds, &settlement);
+    }
+    for key in &snapshot.consumed_keys {
+        let bump = [key.account.bump];
+        let seeds: &[&[u8]] = &[settlement_seeds::CONSUMED_KEY_SEED, &key.account.public_key_hash, &bump];
+        expect_pda(&mut check, key, seeds, &settlement);
+    }
+
+    for state in &snapshot.vault_states {
+        let bump = [state.account.bump];
+        expect_pda(&mut check, state, &[vault_seeds::VAULT_SEED, &bump], &vault);
+    }
+    for role in &snapshot.vault_roles {
+        let (role_id, bump) = ([role.account.role], [role.account.bump]);
+        let seeds: &[&[u8]] = &[vault_seeds::ROLE_SEED, &role_id, role.account.holder.as_ref(), &bump];
+        expect_pda(&mut check, role, seeds, &vault);
+    }
+    for used in &snapshot.vault_used_commitments {
+        let bump = [used.account.bump];
+        let seeds: &[&[u8]] = &[vault_seeds::COMMITMENT_SEED, &used.account.commitment, &bump];
+        expect_pda(&mut check, used, seeds, &vault);
+    }
+    for record in &snapshot.deposit_records {
+        let (nonce, bump) = (record.account.nonce.to_le_bytes(), [record.account.bump]);
+        expect_pda(&mut check, record, &[vault_seeds::DEPOSIT_SEED, &nonce, &bump], &vault);
+    }
+    check
+}
+
+fn expect_pda<T>(check: &mut Check, decoded: &Decoded<T>, seeds: &[&[u8]], program_id: &Pubkey) {
+    match Pubkey::create_program_address(seeds, program_id) {
+        Ok(address) if address == decoded.pubkey => {}
+        _ => check.fail(format!("{}: not at the PDA of its own fields", decoded.pubkey)),
+    }
+}
+
+fn check_executor_count(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("executor_count");
+    let Some(state) = snapshot.settlement_states.first() else {
+        return check;
+    };
+    let state = &state.account;
+
+    let records = snapshot.executor_records.len();
+    if state.registered_executors as usize != records {
+        check.fail(format!(
+            "registered_executors = {}, but {} ExecutorRecord accounts",
+            state.registered_executors, records
+        ));
+    }
+
+    // Legacy inline executors awaiting migrate_executor
+    let count = state.executor_count as usize;
+    if count > MAX_EXECUTORS {
+        check.fail(format!("executor_count = {} exceeds {}", count, MAX_EXECUTORS));
+    } else {
+        let legacy = &state.executors[..count];
+        let unique: BTreeSet<_> = legacy.iter().collect();
+        if unique.len() != count || legacy.contains(&Pubkey::default()) {
+            check.fail("legacy executor array has duplicate or empty entries".into());
+        }
+        if count > 0 {
+            check.note(format!("{} legacy executors not yet migrated", count));
+        }
+    }
+    check
+}
+
+fn check_batch_continuity(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("batch_continuity");
+    let Some(state) = snapshot.settlement_states.first() else {
+        return check;
+    };
+    let state = &state.account;
+
+    let mut batches = BTreeMap::new();
+    for batch in &snapshot.batch_roots {
+        let id = batch.account.batch_id;
+        if id == 0 || id > state.batch_id {
+            check.fail(format!("batch {} outside 1..={}", id, state.batch_id));
+        }
+        if batches.insert(id, &batch.account).is_some() {
+            check.fail(format!("batch {} recorded twice", id));
+        }
+    }
+
+    let mut previous: Option<(u64, i64)> = None;
+    for (&id, batch) in &batches {
+        if let Some((previous_id, created_at)) = previous {
+            if batch.created_at < created_at {
+                check.fail(format!("batch {} created before batch {}", id, previous_id));
+            }
+        }
+        previous = Some((id, batch.created_at));
+    }
+
+    if state.batch_id > 0 {
+        match batches.get(&state.batch_id) {
+            Some(latest) if latest.root == state.current_root => {}
+            Some(_) => check.fail(format!("current_root differs from batch {}", state.batch_id)),
+            None => check.fail(format!("latest batch {} is missing", state.batch_id)),
+        }
+    }
+
+    // Expired batches may be closed; list the gaps without failing
+    let missing: Vec<String> = (1..=state.batch_id)
+        .filter(|id| !batches.contains_key(id))
+        .map(|id| id.to_string())
+        .collect();
+    if !missing.is_empty() {
+        check.note(format!("closed batches: {}", missing.join(", ")));
+    }
+    check
+}
+
+fn check_used_commitments(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("commitments_used_once");
+    let batch_id = snapshot.settlement_states.first().map(|state| state.account.batch_id);
+
+    let mut settled = BTreeSet::new();
+    for used in &snapshot.used_commitments {
+        if !settled.insert(used.account.commitment) {
+            check.fail(format!("commitment {} settled twice", hex::encode(used.account.commitment)));
+        }
+        if batch_id.is_some_and(|batch_id| used.account.batch_id > batch_id) {
+            check.fail(format!("{}: settled in future batch {}", used.pubkey, used.account.batch_id));
+        }
+    }
+
+    let mut released = BTreeSet::new();
+    for used in &snapshot.vault_used_commitments {
+        if !released.insert(used.account.commitment) {
+            check.fail(format!("commitment {} released twice", hex::encode(used.account.commitment)));
+        }
+    }
+    check
+}
+
+fn check_consumed_keys(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("wots_keys_consumed_once");
+
+    let mut keys = BTreeSet::new();
+    let mut per_tree: BTreeMap<Pubkey, u32> = BTreeMap::new();
+    for key in &snapshot.consumed_keys {
+        if !keys.insert(key.account.public_key_hash) {
+            check.fail(format!("key {} consumed twice", hex::encode(key.account.public_key_hash)));
+        }
+        if key.account.key_tree != Pubkey::default() {
+            *per_tree.entry(key.account.key_tree).or_default() += 1;
+        }
+    }
+
+    for tree in &snapshot.key_trees {
+        let records = per_tree.get(&tree.pubkey).copied().unwrap_or(0);
+        if tree.account.consumed != records {
+            check.fail(format!(
+                "{}: consumed = {}, but {} ConsumedKey records",
+                tree.pubkey, tree.account.consumed, records
+            ));
+        }
+    }
+    check
+}
+
+/// The vault PDA holds deposited SOL on top of its own rent
+fn check_vault_balance(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("vault_balance");
+    let Some(state) = snapshot.vault_states.first() else {
+        return check;
+    };
+
+    let rent = Rent::default().minimum_balance(state.data_len);
+    let required = state.account.sol_balance.saturating_add(rent);
+    if state.lamports < required {
+        check.fail(format!(
+            "lamports {} below sol_balance {} + rent {}",
+            state.lamports, state.account.sol_balance, rent
+        ));
+    } else if state.lamports > required {
+        check.note(format!("{} lamports above sol_balance + rent", state.lamports - required));
+    }
+    check
+}
+
+fn check_vault_nonces(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("vault_nonces");
+    let Some(state) = snapshot.vault_states.first() else {
+        return check;
+    };
+    let state = &state.account;
+
+    let mut nonces = BTreeSet::new();
+    for record in &snapshot.deposit_records {
+        let nonce = record.account.nonce;
+        if nonce == 0 || nonce > state.deposit_nonce {
+            check.fail(format!("deposit nonce {} outside 1..={}", nonce, state.deposit_nonce));
+        }
+        if !nonces.insert(nonce) {
+            check.fail(format!("deposit nonce {} recorded twice", nonce));
+        }
+    }
+
+    // Every release creates one UsedCommitment and bumps the nonce
+    let releases = snapshot.vault_used_commitments.len() as u64;
+    if state.withdrawal_nonce != releases {
+        check.fail(format!(
+            "withdrawal_nonce = {}, but {} UsedCommitment accounts",
+            state.withdrawal_nonce, releases
+        ));
+    }
+    check
+}
+
+/// Funds released by the settlement program belong to settled commitments
+fn check_releases_settled(snapshot: &Snapshot) -> Check {
+    let mut check = Check::new("releases_settled");
+    let Some(state) = snapshot.vault_states.first() else {
+        return check;
+    };
+
+    let (settlement_pda, _) =
+        Pubkey::find_program_address(&[settlement_seeds::SETTLEMENT_SEED], &sip_settlement::ID);
+    if state.account.settlement != settlement_pda {
+        check.note(format!("vault settlement key {} is not the settlement PDA", state.account.settlement));
+    }
+
+    let settled: BTreeSet<_> = snapshot.used_commitments.iter().map(|used| used.account.commitment).collect();
+    for used in &snapshot.vault_used_commitments {
+        if used.account.executor == settlement_pda && !settled.contains(&used.account.commitment) {
+            check.fail(format!(
+                "commitment {} released by settlement but never settled",
+                hex::encode(used.account.commitment)
+            ));
+        }
+    }
+    check
+}
@@ -0,0 +1,94 @@
+//! Account dump files
+//!
+//! Accepts the `getProgramAccounts` result with base64 encoding, either as a
+//! bare array or wrapped with the slot it was taken at:
+//!
+//! ```text
+//! [{ "pubkey": "..", "account": { "lamports": 1, "owner": "..", "data": ["<base64>", "base64"], .. } }]
+//! { "slot": 1, "accounts": [..] }
+//! ```
+//!
+//! Dumps of both programs can be concatenated into one array.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use base64::Engine;
+use serde::Deserialize;
+
+/// Parsed dump, sorted by account address
+pub struct Dump {
+    pub slot: Option<u64>,
+    pub accounts: Vec<RawAccount>,
+}
+
+/// One account as exported
+pub struct RawAccount {
+    pub pubkey: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DumpFile {
+    Accounts(Vec<DumpEntry>),
+    Snapshot { slot: Option<u64>, accounts: Vec<DumpEntry> },
+}
+
+#[derive(Deserialize)]
+struct DumpEntry {
+    pubkey: String,
+    account: DumpAccount,
+}
+
+#[derive(Deserialize)]
+struct DumpAccount {
+    lamports: u64,
+    owner: String,
+    data: (String, String),
+}
+
+/// Parse a dump file
+pub fn parse_dump(bytes: &[u8]) -> Result<Dump, String> {
+    let file: DumpFile =
+        serde_json::from_slice(bytes).map_err(|e| format!("invalid dump file: {}", e))?;
+    let (slot, entries) = match file {
+        DumpFile::Accounts(entries) => (None, entries),
+        DumpFile::Snapshot { slot, accounts } => (slot, accounts),
+    };
+
+    let mut seen = BTreeSet::new();
+    let mut accounts = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let pubkey = parse_pubkey(&entry.pubkey)?;
+        if !seen.insert(pubkey) {
+            return Err(format!("duplicate account in dump: {}", pubkey));
+        }
+
+        let (data, encoding) = &entry.account.data;
+        if encoding != "base64" {
+            return Err(format!("{}: unsupported data encoding {}", pubkey, encoding));
+        }
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|_| format!("{}: invalid base64 data", pubkey))?;
+
+        accounts.push(RawAccount {
+            pubkey,
+            owner: parse_pubkey(&entry.account.owner)?,
+            lamports: entry.account.lamports,
+            data,
+        });
+    }
+
+    // Report order must not depend on export order
+    accounts.sort_by_key(|account| account.pubkey);
+    Ok(Dump { slot, accounts })
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(value).map_err(|_| format!("invalid pubkey: {}", value))
+}
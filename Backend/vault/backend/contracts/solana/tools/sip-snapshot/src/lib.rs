@@ -0,0 +1,19 @@
+//! Offline snapshots of the SIP programs
+//!
+//! Reads an exported account dump (no RPC), decodes every sip-settlement and
+//! sip-vault account, checks the invariants the programs are meant to keep
+//! and produces a deterministic report that can be signed:
+//! - `dump` — dump file parsing
+//! - `snapshot` — accounts decoded by discriminator
+//! - `checks` — invariants over a snapshot
+//! - `report` — report building, signing and verification
+
+pub mod checks;
+pub mod dump;
+pub mod report;
+pub mod snapshot;
+
+pub use checks::{run_checks, Check};
+pub use dump::{parse_dump, Dump, RawAccount};
+pub use report::{build_report, sign_report, verify_report, Report, SignedReport};
+pub use snapshot::{Decoded, Snapshot};
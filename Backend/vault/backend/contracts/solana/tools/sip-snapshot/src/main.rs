@@ -0,0 +1,82 @@
+//! sip-snapshot CLI
+//!
+//! Usage:
+//!   sip-snapshot check  --dump FILE --keypair KEYPAIR [--out FILE]
+//!   sip-snapshot verify --report FILE [--signer PUBKEY]
+//!
+//! `check` reads an account dump (see `dump`), writes the signed report to
+//! `--out` or stdout and exits non-zero when an invariant fails. KEYPAIR is a
+//! Solana CLI keypair file. `verify` checks a report's signature, and its
+//! signer when `--signer` is given.
+
+use std::process::ExitCode;
+
+use ed25519_dalek::Keypair;
+use sip_snapshot::{build_report, parse_dump, sign_report, verify_report, SignedReport, Snapshot};
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(raw: Vec<String>) -> Result<bool, String> {
+    let mut iter = raw.into_iter();
+    let command = iter.next().ok_or("missing command (check, verify)")?;
+    let mut flags = std::collections::BTreeMap::new();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        flags.insert(flag, value);
+    }
+    let flag = |name: &str| flags.get(name).map(String::as_str);
+
+    match command.as_str() {
+        "check" => {
+            let dump_path = flag("--dump").ok_or("--dump is required")?;
+            let keypair = read_keypair(flag("--keypair").ok_or("--keypair is required")?)?;
+
+            let dump = std::fs::read(dump_path).map_err(|e| format!("{}: {}", dump_path, e))?;
+            let snapshot = Snapshot::from_dump(&parse_dump(&dump)?);
+            let report = build_report(&dump, &snapshot);
+            let signed = sign_report(&report, &keypair)?;
+
+            let json = serde_json::to_string_pretty(&signed).map_err(|e| e.to_string())?;
+            match flag("--out") {
+                Some(path) => std::fs::write(path, json + "\n").map_err(|e| format!("{}: {}", path, e))?,
+                None => println!("{}", json),
+            }
+            for check in report.checks.iter().filter(|check| !check.passed) {
+                eprintln!("FAILED {}: {}", check.name, check.details.join("; "));
+            }
+            Ok(report.passed)
+        }
+        "verify" => {
+            let path = flag("--report").ok_or("--report is required")?;
+            let file = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+            let signed: SignedReport =
+                serde_json::from_slice(&file).map_err(|e| format!("invalid report: {}", e))?;
+
+            let signer = verify_report(&signed)?;
+            if let Some(expected) = flag("--signer") {
+                if signer != expected {
+                    return Err(format!("signed by {}, expected {}", signer, expected));
+                }
+            }
+            println!("valid signature by {}", signer);
+            Ok(true)
+        }
+        other => Err(format!("unknown command: {}", other)),
+    }
+}
+
+/// Solana CLI keypair file: JSON array of the 64 keypair bytes
+fn read_keypair(path: &str) -> Result<Keypair, String> {
+    let file = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    let bytes: Vec<u8> = serde_json::from_slice(&file).map_err(|_| format!("{}: not a keypair file", path))?;
+    Keypair::from_bytes(&bytes).map_err(|_| format!("{}: invalid keypair", path))
+}
@@ -0,0 +1,165 @@
+//! Signed snapshot reports
+//!
+//! The report is plain JSON with a fixed field order and no wall-clock time,
+//! so the same dump always yields the same bytes. The signer's ed25519 key
+//! signs `REPORT_DOMAIN || report` over the exact bytes embedded in the
+//! signed file.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::Pubkey;
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use sha2::{Digest, Sha256};
+
+use crate::checks::{run_checks, Check};
+use crate::snapshot::Snapshot;
+
+/// Report format version
+pub const REPORT_VERSION: u8 = 1;
+
+/// Prefix of signed report messages
+pub const REPORT_DOMAIN: &[u8] = b"SIP_SNAPSHOT_REPORT_V1";
+
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub version: u8,
+    pub dump_sha256: String,
+    pub slot: Option<u64>,
+    pub settlement_program: String,
+    pub vault_program: String,
+    pub accounts: BTreeMap<&'static str, usize>,
+    pub settlement: Option<SettlementSummary>,
+    pub vault: Option<VaultSummary>,
+    pub checks: Vec<Check>,
+    pub passed: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SettlementSummary {
+    pub address: String,
+    pub authority: String,
+    pub batch_id: u64,
+    pub current_root: String,
+    pub registered_executors: u32,
+    pub root_threshold: u8,
+    pub paused: bool,
+    pub hash_function: u8,
+    pub domain: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct VaultSummary {
+    pub address: String,
+    pub authority: String,
+    pub settlement: String,
+    pub lamports: u64,
+    pub sol_balance: u64,
+    pub deposit_nonce: u64,
+    pub withdrawal_nonce: u64,
+    pub paused: bool,
+    pub domain: String,
+}
+
+/// Report with the signer's key and signature (both base58)
+#[derive(Serialize, Deserialize)]
+pub struct SignedReport {
+    pub report: Box<RawValue>,
+    pub signer: String,
+    pub signature: String,
+}
+
+/// Check a snapshot and summarize it
+pub fn build_report(dump: &[u8], snapshot: &Snapshot) -> Report {
+    let checks = run_checks(snapshot);
+    let passed = checks.iter().all(|check| check.passed);
+
+    let accounts = BTreeMap::from([
+        ("settlement_state", snapshot.settlement_states.len()),
+        ("batch_root", snapshot.batch_roots.len()),
+        ("used_commitment", snapshot.used_commitments.len()),
+        ("pending_root", snapshot.pending_roots.len()),
+        ("settlement_role", snapshot.settlement_roles.len()),
+        ("executor_record", snapshot.executor_records.len()),
+        ("guardian_set", snapshot.guardian_sets.len()),
+        ("wots_verification", snapshot.wots_verifications.len()),
+        ("key_tree", snapshot.key_trees.len()),
+        ("consumed_key", snapshot.consumed_keys.len()),
+        ("vault_state", snapshot.vault_states.len()),
+        ("vault_role", snapshot.vault_roles.len()),
+        ("vault_used_commitment", snapshot.vault_used_commitments.len()),
+        ("deposit_record", snapshot.deposit_records.len()),
+        ("token_vault", snapshot.token_vaults.len()),
+        ("undecodable", snapshot.undecodable.len()),
+        ("foreign", snapshot.foreign),
+    ]);
+
+    let settlement = snapshot.settlement_states.first().map(|state| SettlementSummary {
+        address: state.pubkey.to_string(),
+        authority: state.account.authority.to_string(),
+        batch_id: state.account.batch_id,
+        current_root: hex::encode(state.account.current_root),
+        registered_executors: state.account.registered_executors,
+        root_threshold: state.account.root_threshold,
+        paused: state.account.paused,
+        hash_function: state.account.hash_function,
+        domain: hex::encode(state.account.domain),
+    });
+    let vault = snapshot.vault_states.first().map(|state| VaultSummary {
+        address: state.pubkey.to_string(),
+        authority: state.account.authority.to_string(),
+        settlement: state.account.settlement.to_string(),
+        lamports: state.lamports,
+        sol_balance: state.account.sol_balance,
+        deposit_nonce: state.account.deposit_nonce,
+        withdrawal_nonce: state.account.withdrawal_nonce,
+        paused: state.account.paused,
+        domain: hex::encode(state.account.domain),
+    });
+
+    Report {
+        version: REPORT_VERSION,
+        dump_sha256: hex::encode(Sha256::digest(dump)),
+        slot: snapshot.slot,
+        settlement_program: sip_settlement::ID.to_string(),
+        vault_program: sip_vault::ID.to_string(),
+        accounts,
+        settlement,
+        vault,
+        checks,
+        passed,
+    }
+}
+
+/// Sign a report
+pub fn sign_report(report: &Report, keypair: &Keypair) -> Result<SignedReport, String> {
+    let json = serde_json::to_string(report).map_err(|e| e.to_string())?;
+    let signature = keypair.sign(&message(json.as_bytes()));
+    Ok(SignedReport {
+        report: RawValue::from_string(json).map_err(|e| e.to_string())?,
+        signer: Pubkey::new_from_array(keypair.public.to_bytes()).to_string(),
+        signature: bs58::encode(signature.to_bytes()).into_string(),
+    })
+}
+
+/// Verify a signed report, returning the signer
+pub fn verify_report(signed: &SignedReport) -> Result<String, String> {
+    let signer = bs58::decode(&signed.signer)
+        .into_vec()
+        .map_err(|_| "invalid signer".to_string())?;
+    let public_key = PublicKey::from_bytes(&signer).map_err(|_| "invalid signer".to_string())?;
+    let signature = bs58::decode(&signed.signature)
+        .into_vec()
+        .map_err(|_| "invalid signature".to_string())?;
+    let signature = Signature::from_bytes(&signature).map_err(|_| "invalid signature".to_string())?;
+
+    public_key
+        .verify(&message(signed.report.get().as_bytes()), &signature)
+        .map_err(|_| "signature does not match the report".to_string())?;
+    Ok(signed.signer.clone())
+}
+
+fn message(report: &[u8]) -> Vec<u8> {
+    [REPORT_DOMAIN, report].concat()
+}
@@ -0,0 +1,165 @@
+//! Accounts decoded from a dump
+//!
+//! Accounts are matched by owner, then by Anchor discriminator. Accounts
+//! that carry a known discriminator but do not deserialize (usually an
+//! older layout awaiting migration) are listed in `undecodable`.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use sip_settlement::state as settlement;
+use sip_vault::state as vault;
+
+use crate::dump::{Dump, RawAccount};
+
+/// A decoded account with its address and balance
+pub struct Decoded<T> {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+    pub data_len: usize,
+    pub account: T,
+}
+
+/// Every account of both programs found in a dump
+#[derive(Default)]
+pub struct Snapshot {
+    pub slot: Option<u64>,
+
+    // sip-settlement
+    pub settlement_states: Vec<Decoded<settlement::SettlementState>>,
+    pub batch_roots: Vec<Decoded<settlement::BatchRoot>>,
+    pub used_commitments: Vec<Decoded<settlement::UsedCommitment>>,
+    pub pending_roots: Vec<Decoded<settlement::PendingRoot>>,
+    pub settlement_roles: Vec<Decoded<settlement::RoleAssignment>>,
+    pub executor_records: Vec<Decoded<settlement::ExecutorRecord>>,
+    pub guardian_sets: Vec<Decoded<settlement::GuardianSet>>,
+    pub wots_verifications: Vec<Decoded<settlement::WotsVerification>>,
+    pub key_trees: Vec<Decoded<settlement::KeyTree>>,
+    pub consumed_keys: Vec<Decoded<settlement::ConsumedKey>>,
+
+    // sip-vault
+    pub vault_states: Vec<Decoded<vault::VaultState>>,
+    pub vault_roles: Vec<Decoded<vault::RoleAssignment>>,
+    pub vault_used_commitments: Vec<Decoded<vault::UsedCommitment>>,
+    pub deposit_records: Vec<Decoded<vault::DepositRecord>>,
+    pub token_vaults: Vec<Decoded<vault::TokenVault>>,
+
+    /// Accounts of either program that could not be decoded, with the reason
+    pub undecodable: Vec<(Pubkey, String)>,
+
+    /// Accounts owned by other programs (ignored)
+    pub foreign: usize,
+}
+
+impl Snapshot {
+    pub fn from_dump(dump: &Dump) -> Self {
+        let mut snapshot = Snapshot {
+            slot: dump.slot,
+            ..Default::default()
+        };
+        for account in &dump.accounts {
+            if account.owner == sip_settlement::ID {
+                snapshot.add_settlement_account(account);
+            } else if account.owner == sip_vault::ID {
+                snapshot.add_vault_account(account);
+            } else {
+                snapshot.foreign += 1;
+            }
+        }
+        snapshot
+    }
+
+    fn add_settlement_account(&mut self, account: &RawAccount) {
+        let undecodable = &mut self.undecodable;
+        let Some(discriminator) = discriminator(account, undecodable) else {
+            return;
+        };
+        match discriminator {
+            d if d == settlement::SettlementState::DISCRIMINATOR => {
+                decode(account, &mut self.settlement_states, undecodable)
+            }
+            d if d == settlement::BatchRoot::DISCRIMINATOR => {
+                decode(account, &mut self.batch_roots, undecodable)
+            }
+            d if d == settlement::UsedCommitment::DISCRIMINATOR => {
+                decode(account, &mut self.used_commitments, undecodable)
+            }
+            d if d == settlement::PendingRoot::DISCRIMINATOR => {
+                decode(account, &mut self.pending_roots, undecodable)
+            }
+            d if d == settlement::RoleAssignment::DISCRIMINATOR => {
+                decode(account, &mut self.settlement_roles, undecodable)
+            }
+            d if d == settlement::ExecutorRecord::DISCRIMINATOR => {
+                decode(account, &mut self.executor_records, undecodable)
+            }
+            d if d == settlement::GuardianSet::DISCRIMINATOR => {
+                decode(account, &mut self.guardian_sets, undecodable)
+            }
+            d if d == settlement::WotsVerification::DISCRIMINATOR => {
+                decode(account, &mut self.wots_verifications, undecodable)
+            }
+            d if d == settlement::KeyTree::DISCRIMINATOR => {
+                decode(account, &mut self.key_trees, undecodable)
+            }
+            d if d == settlement::ConsumedKey::DISCRIMINATOR => {
+                decode(account, &mut self.consumed_keys, undecodable)
+            }
+            _ => undecodable.push((account.pubkey, "unknown sip-settlement account".into())),
+        }
+    }
+
+    fn add_vault_account(&mut self, account: &RawAccount) {
+        let undecodable = &mut self.undecodable;
+        let Some(discriminator) = discriminator(account, undecodable) else {
+            return;
+        };
+        match discriminator {
+            d if d == vault::VaultState::DISCRIMINATOR => {
+                decode(account, &mut self.vault_states, undecodable)
+            }
+            d if d == vault::RoleAssignment::DISCRIMINATOR => {
+                decode(account, &mut self.vault_roles, undecodable)
+            }
+            d if d == vault::UsedCommitment::DISCRIMINATOR => {
+                decode(account, &mut self.vault_used_commitments, undecodable)
+            }
+            d if d == vault::DepositRecord::DISCRIMINATOR => {
+                decode(account, &mut self.deposit_records, undecodable)
+            }
+            d if d == vault::TokenVault::DISCRIMINATOR => {
+                decode(account, &mut self.token_vaults, undecodable)
+            }
+            _ => undecodable.push((account.pubkey, "unknown sip-vault account".into())),
+        }
+    }
+}
+
+fn discriminator(account: &RawAccount, undecodable: &mut Vec<(Pubkey, String)>) -> Option<[u8; 8]> {
+    match account.data.get(..8) {
+        Some(bytes) => bytes.try_into().ok(),
+        None => {
+            undecodable.push((account.pubkey, "shorter than a discriminator".into()));
+            None
+        }
+    }
+}
+
+fn decode<T: AccountDeserialize>(
+    account: &RawAccount,
+    into: &mut Vec<Decoded<T>>,
+    undecodable: &mut Vec<(Pubkey, String)>,
+) {
+    let name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+    match T::try_deserialize(&mut account.data.as_slice()) {
+        Ok(decoded) => into.push(Decoded {
+            pubkey: account.pubkey,
+            lamports: account.lamports,
+            data_len: account.data.len(),
+            account: decoded,
+        }),
+        Err(_) => undecodable.push((
+            account.pubkey,
+            format!("{} does not decode ({} bytes; older layout?)", name, account.data.len()),
+        )),
+    }
+}
@@ -0,0 +1,306 @@
+//! Snapshot checks over dumps built from serialized program accounts
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::AccountSerialize;
+use base64::Engine;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+use serde_json::{json, Value};
+use sip_settlement::instructions as settlement_seeds;
+use sip_settlement::state as settlement;
+use sip_snapshot::*;
+use sip_vault::instructions as vault_seeds;
+use sip_vault::state as vault;
+
+const SOL: u64 = 1_000_000_000;
+const COMMITMENT: [u8; 32] = [0xc0; 32];
+
+fn pda(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, program_id)
+}
+
+fn entry<T: AccountSerialize>(pubkey: Pubkey, owner: Pubkey, account: &T, lamports: Option<u64>) -> Value {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    json!({
+        "pubkey": pubkey.to_string(),
+        "account": {
+            "lamports": lamports.unwrap_or_else(|| Rent::default().minimum_balance(data.len())),
+            "owner": owner.to_string(),
+            "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"],
+            "executable": false,
+            "rentEpoch": 0
+        }
+    })
+}
+
+/// A consistent deployment: two batches, one executor, one deposit settled and released
+struct Fixture {
+    settlement_state: settlement::SettlementState,
+    vault_state: vault::VaultState,
+    vault_lamports: u64,
+    release_executor: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let settlement_id = sip_settlement::ID;
+        let (settlement_pda, settlement_bump) = pda(&[settlement_seeds::SETTLEMENT_SEED], &settlement_id);
+        let (_, vault_bump) = pda(&[vault_seeds::VAULT_SEED], &sip_vault::ID);
+
+        let settlement_state = settlement::SettlementState {
+            authority: Pubkey::new_from_array([1; 32]),
+            pending_authority: Pubkey::default(),
+            current_root: [2; 32],
+            batch_id: 2,
+            executor_count: 0,
+            executors: [Pubkey::default(); settlement::MAX_EXECUTORS],
+            bump: settlement_bump,
+            hash_function: settlement::HASH_FUNCTION_KECCAK,
+            root_threshold: 1,
+            proposal_ttl: 3600,
+            paused: false,
+            registered_executors: 1,
+            batch_ttl: 0,
+            domain: [0; 32],
+        };
+        let vault_state = vault::VaultState {
+            authority: Pubkey::new_from_array([1; 32]),
+            pending_authority: Pubkey::default(),
+            settlement: settlement_pda,
+            sol_balance: 4 * SOL,
+            deposit_nonce: 1,
+            withdrawal_nonce: 1,
+            paused: false,
+            bump: vault_bump,
+            hash_function: vault::HASH_FUNCTION_KECCAK,
+            domain: [0; 32],
+        };
+        Fixture {
+            settlement_state,
+            vault_state,
+            vault_lamports: 4 * SOL + Rent::default().minimum_balance(vault::VaultState::LEN),
+            release_executor: settlement_pda,
+        }
+    }
+
+    fn accounts(&self) -> Vec<Value> {
+        let settlement_id = sip_settlement::ID;
+        let vault_id = sip_vault::ID;
+        let executor = Pubkey::new_from_array([9; 32]);
+        let mut accounts = Vec::new();
+
+        let (address, _) = pda(&[settlement_seeds::SETTLEMENT_SEED], &settlement_id);
+        accounts.push(entry(address, settlement_id, &self.settlement_state, None));
+
+        let (address, bump) = pda(&[settlement_seeds::EXECUTOR_SEED, executor.as_ref()], &settlement_id);
+        let record = settlement::ExecutorRecord {
+            executor,
+            label: [0; settlement::MAX_LABEL_LEN],
+            added_at: 1_700_000_000,
+            added_by: Pubkey::new_from_array([1; 32]),
+            last_active_batch: 2,
+            rate_limit: 0,
+            window_start: 0,
+            window_count: 0,
+            bump,
+        };
+        accounts.push(entry(address, settlement_id, &record, None));
+
+        for (batch_id, root) in [(1u64, [1u8; 32]), (2, [2; 32])] {
+            let (address, bump) = pda(&[settlement_seeds::BATCH_SEED, &batch_id.to_le_bytes()], &settlement_id);
+            let batch = settlement::BatchRoot {
+                batch_id,
+                root,
+                created_at: 1_700_000_000 + batch_id as i64,
+                executor,
+                bump,
+                depth: 3,
+                leaf_format: settlement::LEAF_FORMAT_PREFIXED,
+                expires_at: 0,
+            };
+            accounts.push(entry(address, settlement_id, &batch, None));
+        }
+
+        let (address, bump) = pda(&[settlement_seeds::COMMITMENT_SEED, &COMMITMENT], &settlement_id);
+        let used = settlement::UsedCommitment {
+            commitment: COMMITMENT,
+            batch_id: 2,
+            settled_at: 1_700_000_100,
+            executor,
+            bump,
+        };
+        accounts.push(entry(address, settlement_id, &used, None));
+
+        let (address, _) = pda(&[vault_seeds::VAULT_SEED], &vault_id);
+        accounts.push(entry(address, vault_id, &self.vault_state, Some(self.vault_lamports)));
+
+        let (address, bump) = pda(&[vault_seeds::DEPOSIT_SEED, &1u64.to_le_bytes()], &vault_id);
+        let deposit = vault::DepositRecord {
+            commitment: [0xd0; 32],
+            depositor: Pubkey::new_from_array([5; 32]),
+            amount: 5 * SOL,
+            token_mint: Pubkey::default(),
+            deposited_at: 1_700_000_000,
+            nonce: 1,
+            bump,
+        };
+        accounts.push(entry(address, vault_id, &deposit, None));
+
+        let (address, bump) = pda(&[vault_seeds::COMMITMENT_SEED, &COMMITMENT], &vault_id);
+        let released = vault::UsedCommitment {
+            commitment: COMMITMENT,
+            used_at: 1_700_000_100,
+            executor: self.release_executor,
+            amount: SOL,
+            recipient: Pubkey::new_from_array([6; 32]),
+            bump,
+        };
+        accounts.push(entry(address, vault_id, &released, None));
+
+        accounts
+    }
+
+    fn dump(&self) -> Vec<u8> {
+        serde_json::to_vec(&json!({ "slot": 250_000_000u64, "accounts": self.accounts() })).unwrap()
+    }
+}
+
+fn report(dump: &[u8]) -> Report {
+    build_report(dump, &Snapshot::from_dump(&parse_dump(dump).unwrap()))
+}
+
+fn failed(report: &Report) -> Vec<&str> {
+    report
+        .checks
+        .iter()
+        .filter(|check| !check.passed)
+        .map(|check| check.name.as_str())
+        .collect()
+}
+
+fn keypair() -> Keypair {
+    let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+#[test]
+fn consistent_snapshot_passes() {
+    let report = report(&Fixture::new().dump());
+    assert_eq!(failed(&report), Vec::<&str>::new());
+    assert!(report.passed);
+    assert_eq!(report.slot, Some(250_000_000));
+    assert_eq!(report.accounts["batch_root"], 2);
+    assert_eq!(report.accounts["vault_used_commitment"], 1);
+}
+
+#[test]
+fn report_does_not_depend_on_account_order() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.accounts();
+    accounts.reverse();
+    let reversed = serde_json::to_vec(&accounts).unwrap();
+    let in_order = serde_json::to_vec(&fixture.accounts()).unwrap();
+
+    let checks = |dump: &[u8]| serde_json::to_string(&report(dump).checks).unwrap();
+    assert_eq!(checks(&reversed), checks(&in_order));
+}
+
+#[test]
+fn detects_broken_invariants() {
+    let mut fixture = Fixture::new();
+    fixture.settlement_state.registered_executors = 2;
+    assert_eq!(failed(&report(&fixture.dump())), ["executor_count"]);
+
+    let mut fixture = Fixture::new();
+    fixture.settlement_state.current_root = [3; 32];
+    assert_eq!(failed(&report(&fixture.dump())), ["batch_continuity"]);
+
+    let mut fixture = Fixture::new();
+    fixture.vault_lamports -= 1;
+    assert_eq!(failed(&report(&fixture.dump())), ["vault_balance"]);
+
+    let mut fixture = Fixture::new();
+    fixture.vault_state.withdrawal_nonce = 2;
+    assert_eq!(failed(&report(&fixture.dump())), ["vault_nonces"]);
+}
+
+#[test]
+fn release_without_settlement_fails() {
+    let fixture = Fixture::new();
+    let accounts: Vec<Value> = fixture
+        .accounts()
+        .into_iter()
+        .filter(|account| {
+            // Drop the settlement UsedCommitment, keep the vault release
+            let (address, _) = pda(&[settlement_seeds::COMMITMENT_SEED, &COMMITMENT], &sip_settlement::ID);
+            account["pubkey"] != address.to_string()
+        })
+        .collect();
+    assert_eq!(
+        failed(&report(&serde_json::to_vec(&accounts).unwrap())),
+        ["releases_settled"]
+    );
+
+    // Releases by a withdrawer do not need a settlement record
+    let mut fixture = Fixture::new();
+    fixture.release_executor = Pubkey::new_from_array([8; 32]);
+    assert!(report(&fixture.dump()).passed);
+}
+
+#[test]
+fn legacy_layouts_are_reported() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.accounts();
+    let (address, bump) = pda(&[settlement_seeds::BATCH_SEED, &3u64.to_le_bytes()], &sip_settlement::ID);
+    let mut data = Vec::new();
+    settlement::BatchRoot {
+        batch_id: 3,
+        root: [3; 32],
+        created_at: 0,
+        executor: Pubkey::default(),
+        bump,
+        depth: 0,
+        leaf_format: 0,
+        expires_at: 0,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    data.truncate(settlement::BatchRoot::LEGACY_LEN);
+    accounts.push(json!({
+        "pubkey": address.to_string(),
+        "account": {
+            "lamports": SOL,
+            "owner": sip_settlement::ID.to_string(),
+            "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"]
+        }
+    }));
+
+    let report = report(&serde_json::to_vec(&accounts).unwrap());
+    assert_eq!(failed(&report), ["accounts_decodable"]);
+    assert!(report.checks[0].details[0].contains("BatchRoot"));
+}
+
+#[test]
+fn signed_reports_verify() {
+    let dump = Fixture::new().dump();
+    let keypair = keypair();
+    let signed = sign_report(&report(&dump), &keypair).unwrap();
+    let signer = Pubkey::new_from_array(keypair.public.to_bytes()).to_string();
+
+    // Round trip through the file format
+    let file = serde_json::to_string_pretty(&signed).unwrap();
+    let parsed: SignedReport = serde_json::from_str(&file).unwrap();
+    assert_eq!(verify_report(&parsed), Ok(signer));
+
+    // Same dump, same report bytes
+    let again = sign_report(&report(&dump), &keypair).unwrap();
+    assert_eq!(again.report.get(), signed.report.get());
+    assert_eq!(again.signature, signed.signature);
+
+    let tampered = file.replacen("\"passed\":true", "\"passed\":false", 1);
+    assert_ne!(tampered, file);
+    let tampered: SignedReport = serde_json::from_str(&tampered).unwrap();
+    assert!(verify_report(&tampered).is_err());
+}
@@ -24,6 +24,7 @@ pub struct FuzzSettle {
     pub proof_len: u8,
     pub leaf_index: u64,
     pub proof_data: Vec<[u8; 32]>,
+    pub batch_depth: u8,
 }
 
 /// Fuzz data for executor management
@@ -31,7 +32,7 @@ pub struct FuzzSettle {
 pub struct FuzzExecutorManagement {
     pub executor_bytes: [u8; 32],
     pub is_add: bool,
-    pub executor_count: u8,
+    pub executor_count: u32,
 }
 
 /// Fuzz data for authority transfer
@@ -41,6 +42,95 @@ pub struct FuzzAuthorityTransfer {
     pub is_zero: bool,
 }
 
+/// Fuzz data for execute_root (quorum root updates)
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzRootQuorum {
+    pub threshold: u8,
+    pub approvals: Vec<[u8; 32]>,
+    pub is_expired: bool,
+}
+
+/// Fuzz data for grant_role / revoke_role
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzRoleChange {
+    pub role: u8,
+    pub holder_bytes: [u8; 32],
+    pub is_authority: bool,
+    pub has_admin_role: bool,
+}
+
+/// Fuzz data for settle_and_release
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzSettleAndRelease {
+    pub settle: FuzzSettle,
+    pub is_executor: bool,
+    pub has_vault_token_account: bool,
+    pub has_recipient_token_account: bool,
+    pub has_token_program: bool,
+    /// Amount and recipient the settled leaf commits to
+    pub leaf_amount: u64,
+    pub leaf_recipient: [u8; 32],
+    /// Amount and recipient passed to the vault
+    pub amount: u64,
+    pub recipient: [u8; 32],
+}
+
+/// Fuzz data for mirror_root (guardian-attested EVM roots)
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzMirrorRoot {
+    pub threshold: u8,
+    pub guardian_indices: Vec<u8>,
+    pub source_batch_id: u64,
+    pub last_source_batch_id: u64,
+    /// Domain the guardians signed for and the receiving deployment's domain
+    pub attested_domain: [u8; 32],
+    pub deployment_domain: [u8; 32],
+}
+
+/// Fuzz data for the WOTS+ verification flow
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzWotsVerification {
+    pub written: u8,
+    pub completed: u8,
+    pub verified: bool,
+    pub is_keccak: bool,
+    pub is_prefixed_batch: bool,
+}
+
+/// Fuzz data for consuming a WOTS+ key
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzConsumeKey {
+    pub public_key_hash: [u8; 32],
+    pub in_key_tree: bool,
+    /// The key signed its burn message (proves the caller holds it)
+    pub signed_burn: bool,
+}
+
+/// Fuzz data for settling against or closing a batch with an expiry
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzBatchExpiry {
+    pub expires_at: i64,
+    pub now: i64,
+    pub is_close: bool,
+}
+
+/// Fuzz data for settling a leaf built for one deployment domain on another
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzDomainSettle {
+    pub leaf_domain: [u8; 32],
+    pub deployment_domain: [u8; 32],
+}
+
+/// Fuzz data for executing a governance upgrade proposal
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzGovernanceExecute {
+    pub approvals: u8,
+    pub threshold: u8,
+    pub eta: i64,
+    pub now: i64,
+    pub stale: bool,
+}
+
 /// Combined fuzz input
 #[derive(Arbitrary, Debug, Clone)]
 pub enum FuzzInstruction {
@@ -49,6 +139,15 @@ pub enum FuzzInstruction {
     AddExecutor(FuzzExecutorManagement),
     RemoveExecutor(FuzzExecutorManagement),
     TransferAuthority(FuzzAuthorityTransfer),
+    ExecuteRoot(FuzzRootQuorum),
+    GrantRole(FuzzRoleChange),
+    SettleAndRelease(FuzzSettleAndRelease),
+    MirrorRoot(FuzzMirrorRoot),
+    SettleWithWots(FuzzWotsVerification),
+    ConsumeWotsKey(FuzzConsumeKey),
+    BatchExpiry(FuzzBatchExpiry),
+    DomainSettle(FuzzDomainSettle),
+    GovernanceExecute(FuzzGovernanceExecute),
     AcceptAuthority,
     CancelAuthorityTransfer,
 }
@@ -92,18 +191,39 @@ fn invariant_proof_length_bounded(proof_len: u8, proof_data_len: usize) -> bool
     true
 }
 
-/// Invariant: Executor count must not exceed maximum
-fn invariant_executor_count_bounded(count: u8, is_add: bool) -> bool {
-    const MAX_EXECUTORS: u8 = 10;
+/// Invariant: Proof length must equal the batch tree depth
+fn invariant_proof_matches_depth(proof_data_len: usize, batch_depth: u8) -> bool {
+    if proof_data_len != batch_depth as usize {
+        // Mismatched proof should trigger ProofLengthMismatch error
+        // (otherwise an internal node could be passed as a leaf)
+        return false;
+    }
+    true
+}
+
+/// Invariant: Leaf index must fit in the batch tree
+fn invariant_leaf_index_in_range(leaf_index: u64, batch_depth: u8) -> bool {
+    if batch_depth < 64 && leaf_index >= (1u64 << batch_depth) {
+        // Out-of-range index should trigger LeafIndexOutOfRange error
+        // (otherwise the same leaf verifies at many indices)
+        return false;
+    }
+    true
+}
+
+/// Invariant: Executor registry counter must not overflow
+fn invariant_executor_count_bounded(count: u32, is_add: bool) -> bool {
+    // Executors are ExecutorRecord PDAs; only the u32 counter bounds them
+    const MAX_EXECUTORS: u32 = u32::MAX;
     if count >= MAX_EXECUTORS && is_add {
-        // Adding when at max should trigger MaxExecutorsReached
+        // Adding when the counter is full should trigger MaxExecutorsReached
         return false;
     }
     true
 }
 
 /// Invariant: Cannot remove from empty executor list
-fn invariant_remove_requires_executors(count: u8, is_add: bool) -> bool {
+fn invariant_remove_requires_executors(count: u32, is_add: bool) -> bool {
     if !is_add && count == 0 {
         // Removing from empty should trigger ExecutorNotFound
         return false;
@@ -120,6 +240,84 @@ fn invariant_valid_pending_authority(bytes: &[u8; 32]) -> bool {
     true
 }
 
+/// Invariant: A quorum root needs `threshold` distinct approvals
+fn invariant_quorum_met(threshold: u8, approvals: &[[u8; 32]]) -> bool {
+    let mut distinct: Vec<&[u8; 32]> = Vec::new();
+    for approver in approvals {
+        if !distinct.contains(&approver) {
+            distinct.push(approver);
+        }
+    }
+    // Duplicate approvals must not count twice (AlreadyApproved)
+    threshold >= 1 && distinct.len() >= threshold as usize
+}
+
+/// Invariant: Only the authority or an admin role holder can change roles
+fn invariant_role_manager_required(is_authority: bool, has_admin_role: bool) -> bool {
+    is_authority || has_admin_role
+}
+
+/// Invariant: Only admin and pauser are assignable (root submitters are executors)
+fn invariant_assignable_role(role: u8) -> bool {
+    const ROLE_ADMIN: u8 = 0;
+    const ROLE_PAUSER: u8 = 3;
+    role == ROLE_ADMIN || role == ROLE_PAUSER
+}
+
+/// Invariant: Token accounts for a release are all present or all absent
+fn invariant_release_accounts_consistent(vault_token: bool, recipient_token: bool, program: bool) -> bool {
+    vault_token == recipient_token && recipient_token == program
+}
+
+/// Invariant: A release pays out exactly what its leaf commits to
+fn invariant_release_matches_leaf(
+    leaf_amount: u64,
+    leaf_recipient: &[u8; 32],
+    amount: u64,
+    recipient: &[u8; 32],
+) -> bool {
+    leaf_amount == amount && leaf_recipient == recipient
+}
+
+/// Invariant: Guardian signatures are strictly increasing by index and meet the threshold
+fn invariant_guardian_quorum(threshold: u8, guardian_indices: &[u8]) -> bool {
+    if threshold == 0 {
+        return false;
+    }
+    let sorted = guardian_indices.windows(2).all(|pair| pair[0] < pair[1]);
+    sorted && guardian_indices.len() >= threshold as usize
+}
+
+/// Invariant: A WOTS signature is verified only after all 67 chains are written and completed
+fn invariant_wots_complete(written: u8, completed: u8, verified: bool) -> bool {
+    const WOTS_LEN: u8 = 67;
+    !verified || (written == WOTS_LEN && completed == WOTS_LEN)
+}
+
+/// Invariant: A WOTS+ key must never be consumed twice
+fn invariant_no_key_reuse(public_key_hash: &[u8; 32], consumed_keys: &[[u8; 32]]) -> bool {
+    !consumed_keys.contains(public_key_hash)
+}
+
+/// Invariant: A batch without an expiry (0) or before its expiry is live
+fn invariant_batch_live(expires_at: i64, now: i64) -> bool {
+    expires_at == 0 || now <= expires_at
+}
+
+/// Invariant: A domain leaf only verifies in the deployment it was built for
+fn invariant_same_domain(leaf_domain: &[u8; 32], deployment_domain: &[u8; 32]) -> bool {
+    leaf_domain == deployment_domain
+}
+
+/// Invariant: A proposal executes only once approved, past its timelock and current
+fn invariant_proposal_executable(data: &FuzzGovernanceExecute) -> bool {
+    data.threshold >= 1
+        && data.approvals >= data.threshold
+        && data.eta != 0
+        && data.now >= data.eta
+        && !data.stale
+}
+
 /// Invariant: Commitment replay must be prevented
 fn invariant_no_commitment_replay(commitment: &[u8; 32], used_commitments: &[[u8; 32]]) -> bool {
     // Same commitment should not be settleable twice
@@ -149,6 +347,12 @@ fn check_settle(data: &FuzzSettle) -> Result<(), &'static str> {
     if !invariant_proof_length_bounded(data.proof_len, data.proof_data.len()) {
         return Err("VULNERABILITY: Oversized proof accepted - ProofTooLong check missing");
     }
+    if !invariant_proof_matches_depth(data.proof_data.len(), data.batch_depth) {
+        return Err("VULNERABILITY: Proof depth mismatch accepted - ProofLengthMismatch check missing");
+    }
+    if !invariant_leaf_index_in_range(data.leaf_index, data.batch_depth) {
+        return Err("VULNERABILITY: Out-of-range leaf index accepted - LeafIndexOutOfRange check missing");
+    }
     Ok(())
 }
 
@@ -163,6 +367,120 @@ fn check_executor_management(data: &FuzzExecutorManagement) -> Result<(), &'stat
     Ok(())
 }
 
+/// Validate all invariants for execute_root
+fn check_root_quorum(data: &FuzzRootQuorum) -> Result<(), &'static str> {
+    if data.is_expired {
+        return Err("VULNERABILITY: Expired proposal executed - ProposalExpired check missing");
+    }
+    if !invariant_quorum_met(data.threshold, &data.approvals) {
+        return Err("VULNERABILITY: Root executed below quorum - ThresholdNotMet check missing");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for role changes
+fn check_role_change(data: &FuzzRoleChange) -> Result<(), &'static str> {
+    if !invariant_role_manager_required(data.is_authority, data.has_admin_role) {
+        return Err("VULNERABILITY: Role changed without admin - access control bypass");
+    }
+    if !invariant_assignable_role(data.role) {
+        return Err("VULNERABILITY: Unknown role assigned - InvalidRole check missing");
+    }
+    if data.holder_bytes == [0u8; 32] {
+        return Err("VULNERABILITY: Role granted to zero key - InvalidRole check missing");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for settle_and_release
+fn check_settle_and_release(data: &FuzzSettleAndRelease) -> Result<(), &'static str> {
+    check_settle(&data.settle)?;
+    if !invariant_authorization_required(data.is_executor) {
+        return Err("VULNERABILITY: Release by non-executor - access control bypass");
+    }
+    if !invariant_release_accounts_consistent(
+        data.has_vault_token_account,
+        data.has_recipient_token_account,
+        data.has_token_program,
+    ) {
+        return Err("VULNERABILITY: Partial token accounts accepted - MissingTokenAccounts check missing");
+    }
+    if !invariant_release_matches_leaf(data.leaf_amount, &data.leaf_recipient, data.amount, &data.recipient) {
+        return Err("VULNERABILITY: Release differs from the settled leaf - executor chose amount or recipient");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for mirror_root
+fn check_mirror_root(data: &FuzzMirrorRoot) -> Result<(), &'static str> {
+    if data.source_batch_id <= data.last_source_batch_id {
+        return Err("VULNERABILITY: Attestation replayed - StaleAttestation check missing");
+    }
+    if !invariant_same_domain(&data.attested_domain, &data.deployment_domain) {
+        return Err("VULNERABILITY: Attestation for another deployment accepted - domain not signed");
+    }
+    if !invariant_guardian_quorum(data.threshold, &data.guardian_indices) {
+        return Err("VULNERABILITY: Root mirrored below guardian threshold");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for settle_with_wots
+fn check_settle_with_wots(data: &FuzzWotsVerification) -> Result<(), &'static str> {
+    if !data.verified {
+        return Err("VULNERABILITY: Settled without WOTS verification - WotsNotVerified check missing");
+    }
+    if !invariant_wots_complete(data.written, data.completed, data.verified) {
+        return Err("VULNERABILITY: WOTS verified with missing chains");
+    }
+    if !data.is_keccak || !data.is_prefixed_batch {
+        return Err("VULNERABILITY: WOTS leaf accepted outside keccak prefixed batches");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for consume_wots_key
+fn check_consume_key(data: &FuzzConsumeKey, consumed_keys: &[[u8; 32]]) -> Result<(), &'static str> {
+    if !data.in_key_tree || data.public_key_hash == [0u8; 32] {
+        return Err("VULNERABILITY: Unregistered key consumed - WotsKeyNotInTree check missing");
+    }
+    if !data.signed_burn {
+        return Err("VULNERABILITY: Key burned without its signature - another user's key can be blocked");
+    }
+    if !invariant_no_key_reuse(&data.public_key_hash, consumed_keys) {
+        return Err("VULNERABILITY: WOTS key reused - ConsumedKey PDA must already exist");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for settling against or closing a batch
+fn check_batch_expiry(data: &FuzzBatchExpiry) -> Result<(), &'static str> {
+    let live = invariant_batch_live(data.expires_at, data.now);
+    if data.is_close && live {
+        return Err("VULNERABILITY: Live batch closed - BatchNotExpired check missing");
+    }
+    if !data.is_close && !live {
+        return Err("VULNERABILITY: Expired batch settled - BatchExpired check missing");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for settling a domain leaf
+fn check_domain_settle(data: &FuzzDomainSettle) -> Result<(), &'static str> {
+    if !invariant_same_domain(&data.leaf_domain, &data.deployment_domain) {
+        return Err("VULNERABILITY: Leaf from another cluster or program accepted - domain not bound");
+    }
+    Ok(())
+}
+
+/// Validate all invariants for a governance execution that succeeded
+fn check_governance_execute(data: &FuzzGovernanceExecute) -> Result<(), &'static str> {
+    if !invariant_proposal_executable(data) {
+        return Err("VULNERABILITY: Upgrade executed early or without approvals - timelock or threshold bypassed");
+    }
+    Ok(())
+}
+
 /// Validate all invariants for authority transfer
 fn check_authority_transfer(data: &FuzzAuthorityTransfer) -> Result<(), &'static str> {
     if data.is_zero || !invariant_valid_pending_authority(&data.new_authority_bytes) {
@@ -178,7 +496,8 @@ fn check_authority_transfer(data: &FuzzAuthorityTransfer) -> Result<(), &'static
 fn main() {
     // Track state for stateful fuzzing
     let mut used_commitments: Vec<[u8; 32]> = Vec::new();
-    let mut executor_count: u8 = 0;
+    let mut executor_count: u32 = 0;
+    let mut consumed_keys: Vec<[u8; 32]> = Vec::new();
     
     loop {
         fuzz!(|instruction: FuzzInstruction| {
@@ -213,7 +532,7 @@ fn main() {
                     if let Err(vuln) = check_executor_management(&data) {
                         let _ = vuln;
                     }
-                    if executor_count < 10 {
+                    if executor_count < u32::MAX {
                         executor_count += 1;
                     }
                 }
@@ -235,6 +554,60 @@ fn main() {
                     }
                 }
                 
+                FuzzInstruction::ExecuteRoot(data) => {
+                    if let Err(vuln) = check_root_quorum(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
+                FuzzInstruction::GrantRole(data) => {
+                    if let Err(vuln) = check_role_change(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
+                FuzzInstruction::SettleAndRelease(data) => {
+                    if let Err(vuln) = check_settle_and_release(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
+                FuzzInstruction::MirrorRoot(data) => {
+                    if let Err(vuln) = check_mirror_root(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
+                FuzzInstruction::SettleWithWots(data) => {
+                    if let Err(vuln) = check_settle_with_wots(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
+                FuzzInstruction::ConsumeWotsKey(data) => {
+                    if check_consume_key(&data, &consumed_keys).is_ok() {
+                        consumed_keys.push(data.public_key_hash);
+                    }
+                }
+                
+                FuzzInstruction::BatchExpiry(data) => {
+                    if let Err(vuln) = check_batch_expiry(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
+                FuzzInstruction::DomainSettle(data) => {
+                    if let Err(vuln) = check_domain_settle(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
+                FuzzInstruction::GovernanceExecute(data) => {
+                    if let Err(vuln) = check_governance_execute(&data) {
+                        let _ = vuln;
+                    }
+                }
+                
                 FuzzInstruction::AcceptAuthority => {
                     // Invariant: Only pending authority can accept
                     // This requires stateful tracking of pending_authority
@@ -293,11 +666,26 @@ mod invariant_tests {
         assert!(invariant_proof_length_bounded(10, 10));
     }
 
+    #[test]
+    fn test_proof_depth_invariant() {
+        assert!(!invariant_proof_matches_depth(4, 5));
+        assert!(!invariant_proof_matches_depth(6, 5));
+        assert!(invariant_proof_matches_depth(5, 5));
+    }
+
+    #[test]
+    fn test_leaf_index_invariant() {
+        assert!(!invariant_leaf_index_in_range(32, 5));
+        assert!(!invariant_leaf_index_in_range(u64::MAX, 32));
+        assert!(invariant_leaf_index_in_range(31, 5));
+        assert!(invariant_leaf_index_in_range(0, 1));
+    }
+
     #[test]
     fn test_executor_count_invariant() {
-        assert!(!invariant_executor_count_bounded(10, true));
-        assert!(invariant_executor_count_bounded(9, true));
-        assert!(invariant_executor_count_bounded(10, false));
+        assert!(!invariant_executor_count_bounded(u32::MAX, true));
+        assert!(invariant_executor_count_bounded(10, true));
+        assert!(invariant_executor_count_bounded(u32::MAX, false));
     }
 
     #[test]
@@ -351,6 +739,7 @@ mod invariant_tests {
             proof_len: 5,
             leaf_index: 0,
             proof_data: vec![[0u8; 32]; 5],
+            batch_depth: 5,
         };
         assert!(check_settle(&valid).is_ok());
         
@@ -359,6 +748,7 @@ mod invariant_tests {
             proof_len: 0,
             leaf_index: 0,
             proof_data: vec![],
+            batch_depth: 5,
         };
         assert!(check_settle(&empty_proof).is_err());
         
@@ -367,8 +757,27 @@ mod invariant_tests {
             proof_len: 33,
             leaf_index: 0,
             proof_data: vec![[0u8; 32]; 33],
+            batch_depth: 32,
         };
         assert!(check_settle(&long_proof).is_err());
+
+        let shallow_proof = FuzzSettle {
+            commitment: [1u8; 32],
+            proof_len: 4,
+            leaf_index: 0,
+            proof_data: vec![[0u8; 32]; 4],
+            batch_depth: 5,
+        };
+        assert!(check_settle(&shallow_proof).is_err());
+
+        let aliased_index = FuzzSettle {
+            commitment: [1u8; 32],
+            proof_len: 5,
+            leaf_index: 32,
+            proof_data: vec![[0u8; 32]; 5],
+            batch_depth: 5,
+        };
+        assert!(check_settle(&aliased_index).is_err());
     }
 
     #[test]
@@ -383,7 +792,7 @@ mod invariant_tests {
         let overflow_add = FuzzExecutorManagement {
             executor_bytes: [1u8; 32],
             is_add: true,
-            executor_count: 10,
+            executor_count: u32::MAX,
         };
         assert!(check_executor_management(&overflow_add).is_err());
         
@@ -402,6 +811,265 @@ mod invariant_tests {
         assert!(check_executor_management(&empty_remove).is_err());
     }
 
+    #[test]
+    fn test_root_quorum_check() {
+        let met = FuzzRootQuorum {
+            threshold: 2,
+            approvals: vec![[1u8; 32], [2u8; 32]],
+            is_expired: false,
+        };
+        assert!(check_root_quorum(&met).is_ok());
+
+        let duplicate = FuzzRootQuorum {
+            threshold: 2,
+            approvals: vec![[1u8; 32], [1u8; 32]],
+            is_expired: false,
+        };
+        assert!(check_root_quorum(&duplicate).is_err());
+
+        let expired = FuzzRootQuorum {
+            threshold: 1,
+            approvals: vec![[1u8; 32]],
+            is_expired: true,
+        };
+        assert!(check_root_quorum(&expired).is_err());
+    }
+
+    #[test]
+    fn test_role_change_check() {
+        let admin_grant = FuzzRoleChange {
+            role: 3,
+            holder_bytes: [1u8; 32],
+            is_authority: false,
+            has_admin_role: true,
+        };
+        assert!(check_role_change(&admin_grant).is_ok());
+
+        let no_admin = FuzzRoleChange {
+            role: 3,
+            holder_bytes: [1u8; 32],
+            is_authority: false,
+            has_admin_role: false,
+        };
+        assert!(check_role_change(&no_admin).is_err());
+
+        let root_submitter = FuzzRoleChange {
+            role: 1,
+            holder_bytes: [1u8; 32],
+            is_authority: true,
+            has_admin_role: false,
+        };
+        assert!(check_role_change(&root_submitter).is_err());
+    }
+
+    #[test]
+    fn test_settle_and_release_check() {
+        let settle = FuzzSettle {
+            commitment: [1u8; 32],
+            proof_len: 5,
+            leaf_index: 3,
+            proof_data: vec![[0u8; 32]; 5],
+            batch_depth: 5,
+        };
+        let native = FuzzSettleAndRelease {
+            settle: settle.clone(),
+            is_executor: true,
+            has_vault_token_account: false,
+            has_recipient_token_account: false,
+            has_token_program: false,
+            leaf_amount: 1_000,
+            leaf_recipient: [7u8; 32],
+            amount: 1_000,
+            recipient: [7u8; 32],
+        };
+        assert!(check_settle_and_release(&native).is_ok());
+
+        let other_amount = FuzzSettleAndRelease {
+            amount: 2_000,
+            ..native.clone()
+        };
+        assert!(check_settle_and_release(&other_amount).is_err());
+
+        let other_recipient = FuzzSettleAndRelease {
+            recipient: [8u8; 32],
+            ..native.clone()
+        };
+        assert!(check_settle_and_release(&other_recipient).is_err());
+
+        let partial_token = FuzzSettleAndRelease {
+            has_vault_token_account: true,
+            ..native.clone()
+        };
+        assert!(check_settle_and_release(&partial_token).is_err());
+
+        let not_executor = FuzzSettleAndRelease {
+            is_executor: false,
+            ..native
+        };
+        assert!(check_settle_and_release(&not_executor).is_err());
+    }
+
+    #[test]
+    fn test_mirror_root_check() {
+        let attested = FuzzMirrorRoot {
+            threshold: 2,
+            guardian_indices: vec![0, 2],
+            source_batch_id: 8,
+            last_source_batch_id: 7,
+            attested_domain: [5u8; 32],
+            deployment_domain: [5u8; 32],
+        };
+        assert!(check_mirror_root(&attested).is_ok());
+
+        let other_deployment = FuzzMirrorRoot {
+            deployment_domain: [6u8; 32],
+            ..attested.clone()
+        };
+        assert!(check_mirror_root(&other_deployment).is_err());
+
+        let duplicate = FuzzMirrorRoot {
+            guardian_indices: vec![2, 2],
+            ..attested.clone()
+        };
+        assert!(check_mirror_root(&duplicate).is_err());
+
+        let below_threshold = FuzzMirrorRoot {
+            guardian_indices: vec![1],
+            ..attested.clone()
+        };
+        assert!(check_mirror_root(&below_threshold).is_err());
+
+        let replayed = FuzzMirrorRoot {
+            source_batch_id: 7,
+            ..attested
+        };
+        assert!(check_mirror_root(&replayed).is_err());
+    }
+
+    #[test]
+    fn test_settle_with_wots_check() {
+        let verified = FuzzWotsVerification {
+            written: 67,
+            completed: 67,
+            verified: true,
+            is_keccak: true,
+            is_prefixed_batch: true,
+        };
+        assert!(check_settle_with_wots(&verified).is_ok());
+
+        let partial = FuzzWotsVerification {
+            completed: 40,
+            ..verified.clone()
+        };
+        assert!(check_settle_with_wots(&partial).is_err());
+
+        let unverified = FuzzWotsVerification {
+            verified: false,
+            ..verified.clone()
+        };
+        assert!(check_settle_with_wots(&unverified).is_err());
+
+        let raw_batch = FuzzWotsVerification {
+            is_prefixed_batch: false,
+            ..verified
+        };
+        assert!(check_settle_with_wots(&raw_batch).is_err());
+    }
+
+    #[test]
+    fn test_consume_key_check() {
+        let key = FuzzConsumeKey {
+            public_key_hash: [5u8; 32],
+            in_key_tree: true,
+            signed_burn: true,
+        };
+        assert!(check_consume_key(&key, &[]).is_ok());
+        assert!(check_consume_key(&key, &[[5u8; 32]]).is_err());
+
+        let unsigned = FuzzConsumeKey {
+            signed_burn: false,
+            ..key.clone()
+        };
+        assert!(check_consume_key(&unsigned, &[]).is_err());
+
+        let unregistered = FuzzConsumeKey {
+            in_key_tree: false,
+            ..key.clone()
+        };
+        assert!(check_consume_key(&unregistered, &[]).is_err());
+
+        let padding = FuzzConsumeKey {
+            public_key_hash: [0u8; 32],
+            ..key
+        };
+        assert!(check_consume_key(&padding, &[]).is_err());
+    }
+
+    #[test]
+    fn test_batch_expiry_check() {
+        let settle = FuzzBatchExpiry {
+            expires_at: 1_000,
+            now: 1_000,
+            is_close: false,
+        };
+        assert!(check_batch_expiry(&settle).is_ok());
+        assert!(check_batch_expiry(&FuzzBatchExpiry { now: 1_001, ..settle.clone() }).is_err());
+
+        let never_expires = FuzzBatchExpiry {
+            expires_at: 0,
+            now: i64::MAX,
+            ..settle.clone()
+        };
+        assert!(check_batch_expiry(&never_expires).is_ok());
+        assert!(check_batch_expiry(&FuzzBatchExpiry { is_close: true, ..never_expires }).is_err());
+
+        let close = FuzzBatchExpiry {
+            now: 1_001,
+            is_close: true,
+            ..settle
+        };
+        assert!(check_batch_expiry(&close).is_ok());
+        assert!(check_batch_expiry(&FuzzBatchExpiry { now: 1_000, ..close }).is_err());
+    }
+
+    #[test]
+    fn test_domain_settle_check() {
+        let same = FuzzDomainSettle {
+            leaf_domain: [3u8; 32],
+            deployment_domain: [3u8; 32],
+        };
+        assert!(check_domain_settle(&same).is_ok());
+
+        let replayed = FuzzDomainSettle {
+            deployment_domain: [4u8; 32],
+            ..same
+        };
+        assert!(check_domain_settle(&replayed).is_err());
+    }
+
+    #[test]
+    fn test_governance_execute_check() {
+        let ready = FuzzGovernanceExecute {
+            approvals: 2,
+            threshold: 2,
+            eta: 3_600,
+            now: 3_600,
+            stale: false,
+        };
+        assert!(check_governance_execute(&ready).is_ok());
+        assert!(check_governance_execute(&FuzzGovernanceExecute { approvals: 1, ..ready.clone() }).is_err());
+        assert!(check_governance_execute(&FuzzGovernanceExecute { now: 3_599, ..ready.clone() }).is_err());
+        assert!(check_governance_execute(&FuzzGovernanceExecute { stale: true, ..ready.clone() }).is_err());
+
+        // Never queued
+        let unqueued = FuzzGovernanceExecute {
+            eta: 0,
+            now: i64::MAX,
+            ..ready
+        };
+        assert!(check_governance_execute(&unqueued).is_err());
+    }
+
     #[test]
     fn test_authority_transfer_check() {
         let valid = FuzzAuthorityTransfer {
@@ -0,0 +1,48 @@
+/**
+ * Deployment domains
+ *
+ * Mirrors `obscura_merkle::domain`: a separator ties leaves and commitments
+ * to one cluster (genesis hash) and one program, so they do not verify on
+ * another cluster, a fork or a redeployed program.
+ *
+ *   separator = keccak256(DOMAIN_TAG || genesis_hash || program_id)
+ *   leaf      = keccak256(0x00 || separator || commitment)
+ */
+
+import { keccak256 } from '../hash.js';
+import type { Hash } from '../types.js';
+
+/** Version of the domain separator format */
+export const DOMAIN_VERSION = 1;
+
+/** Domain tag shared with the Solana programs */
+export const DOMAIN_TAG = 'OBSCURA_SIP_DOMAIN_V1';
+
+/**
+ * Domain separator of a program deployed on a cluster
+ *
+ * @param genesisHash - Cluster genesis hash (32 bytes)
+ * @param programId - Program id (32 bytes)
+ */
+export function computeDomainSeparator(genesisHash: Uint8Array, programId: Uint8Array): Hash {
+  if (genesisHash.length !== 32 || programId.length !== 32) {
+    throw new Error('Genesis hash and program id must be 32 bytes');
+  }
+  const tag = new TextEncoder().encode(DOMAIN_TAG);
+  const data = new Uint8Array(tag.length + 64);
+  data.set(tag, 0);
+  data.set(genesisHash, tag.length);
+  data.set(programId, tag.length + 32);
+  return keccak256(data);
+}
+
+/**
+ * Keccak leaf bound to a deployment domain (LEAF_FORMAT_DOMAIN batches)
+ */
+export function computeDomainLeafHash(separator: Uint8Array, commitment: Uint8Array): Hash {
+  const data = new Uint8Array(1 + separator.length + commitment.length);
+  data[0] = 0x00;
+  data.set(separator, 1);
+  data.set(commitment, 1 + separator.length);
+  return keccak256(data);
+}
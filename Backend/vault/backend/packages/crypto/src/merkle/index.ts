@@ -3,5 +3,11 @@
  */
 
 export { MerkleTree } from './tree.js';
-export { verifyMerkleProof, computeMerkleRoot } from './verify.js';
+export { verifyMerkleProof, computeMerkleRoot, computeLeafHash } from './verify.js';
+export {
+  DOMAIN_TAG,
+  DOMAIN_VERSION,
+  computeDomainSeparator,
+  computeDomainLeafHash,
+} from './domain.js';
 export type { MerkleProof } from '../types.js';
@@ -5,7 +5,7 @@
  */
 
 import { hashConcat, hash as hashFn, bytesEqual } from '../hash.js';
-import type { Hash, MerkleProof } from '../types.js';
+import type { Hash, HashFunction, MerkleProof } from '../types.js';
 
 /**
  * Merkle Tree class
@@ -25,8 +25,12 @@ export class MerkleTree {
   /** Root hash */
   readonly root: Hash;
 
-  private constructor(levels: Hash[][]) {
+  /** Hash function for internal nodes */
+  private readonly hashFn: HashFunction;
+
+  private constructor(levels: Hash[][], hashFn: HashFunction) {
     this.levels = levels;
+    this.hashFn = hashFn;
     this.leafCount = levels[0].length;
     this.depth = levels.length - 1;
     this.root = levels[levels.length - 1][0];
@@ -36,8 +40,9 @@ export class MerkleTree {
    * Build a Merkle tree from leaf hashes
    * 
    * @param leaves - Array of leaf hashes (will be padded to power of 2)
+   * @param nodeHash - Hash function for internal nodes (keccak256 for on-chain batches)
    */
-  static fromLeaves(leaves: Hash[]): MerkleTree {
+  static fromLeaves(leaves: Hash[], nodeHash: HashFunction = hashFn): MerkleTree {
     if (leaves.length === 0) {
       throw new Error('Cannot create Merkle tree with no leaves');
     }
@@ -59,13 +64,13 @@ export class MerkleTree {
       for (let i = 0; i < currentLevel.length; i += 2) {
         const left = currentLevel[i];
         const right = currentLevel[i + 1];
-        nextLevel.push(hashNodes(left, right));
+        nextLevel.push(hashNodes(left, right, nodeHash));
       }
       levels.push(nextLevel);
       currentLevel = nextLevel;
     }
 
-    return new MerkleTree(levels);
+    return new MerkleTree(levels, nodeHash);
   }
 
   /**
@@ -98,7 +103,7 @@ export class MerkleTree {
    */
   verifyProof(proof: MerkleProof, leaf: Hash, expectedRoot?: Hash): boolean {
     const root = expectedRoot ?? this.root;
-    const computedRoot = computeRootFromProof(leaf, proof);
+    const computedRoot = computeRootFromProof(leaf, proof, this.hashFn);
     return bytesEqual(computedRoot, root);
   }
 
@@ -131,7 +136,7 @@ export class MerkleTree {
 
     const newLeaves = [...this.levels[0]];
     newLeaves[index] = newLeaf;
-    return MerkleTree.fromLeaves(newLeaves);
+    return MerkleTree.fromLeaves(newLeaves, this.hashFn);
   }
 
   /**
@@ -151,7 +156,11 @@ export class MerkleTree {
 /**
  * Compute root from leaf and proof
  */
-export function computeRootFromProof(leaf: Hash, proof: MerkleProof): Hash {
+export function computeRootFromProof(
+  leaf: Hash,
+  proof: MerkleProof,
+  nodeHash: HashFunction = hashFn
+): Hash {
   let current = leaf;
   
   for (let i = 0; i < proof.siblings.length; i++) {
@@ -160,10 +169,10 @@ export function computeRootFromProof(leaf: Hash, proof: MerkleProof): Hash {
     
     if (isRight) {
       // Current is on the right, sibling is on the left
-      current = hashNodes(sibling, current);
+      current = hashNodes(sibling, current, nodeHash);
     } else {
       // Current is on the left, sibling is on the right
-      current = hashNodes(current, sibling);
+      current = hashNodes(current, sibling, nodeHash);
     }
   }
   
@@ -175,13 +184,13 @@ export function computeRootFromProof(leaf: Hash, proof: MerkleProof): Hash {
  * 
  * Uses domain separation to prevent second preimage attacks
  */
-function hashNodes(left: Hash, right: Hash): Hash {
+function hashNodes(left: Hash, right: Hash, nodeHash: HashFunction): Hash {
   // Prefix with 0x01 to distinguish from leaf hashes
   const combined = new Uint8Array(1 + left.length + right.length);
   combined[0] = 0x01;
   combined.set(left, 1);
   combined.set(right, 1 + left.length);
-  return hashFn(combined);
+  return nodeHash(combined);
 }
 
 /**
@@ -6,7 +6,7 @@
  */
 
 import { hash as hashFn, bytesEqual } from '../hash.js';
-import type { Hash, MerkleProof } from '../types.js';
+import type { Hash, HashFunction, MerkleProof } from '../types.js';
 
 /**
  * Verify a Merkle proof
@@ -14,14 +14,16 @@ import type { Hash, MerkleProof } from '../types.js';
  * @param proof - The Merkle proof
  * @param leaf - The leaf hash to verify
  * @param root - The expected root hash
+ * @param nodeHash - Hash function for internal nodes
  * @returns true if the proof is valid
  */
 export function verifyMerkleProof(
   proof: MerkleProof,
   leaf: Hash,
-  root: Hash
+  root: Hash,
+  nodeHash: HashFunction = hashFn
 ): boolean {
-  const computedRoot = computeMerkleRoot(leaf, proof);
+  const computedRoot = computeMerkleRoot(leaf, proof, nodeHash);
   return bytesEqual(computedRoot, root);
 }
 
@@ -30,9 +32,14 @@ export function verifyMerkleProof(
  * 
  * @param leaf - The leaf hash
  * @param proof - The Merkle proof containing siblings and path
+ * @param nodeHash - Hash function for internal nodes
  * @returns The computed root hash
  */
-export function computeMerkleRoot(leaf: Hash, proof: MerkleProof): Hash {
+export function computeMerkleRoot(
+  leaf: Hash,
+  proof: MerkleProof,
+  nodeHash: HashFunction = hashFn
+): Hash {
   let current = leaf;
   
   for (let i = 0; i < proof.siblings.length; i++) {
@@ -41,10 +48,10 @@ export function computeMerkleRoot(leaf: Hash, proof: MerkleProof): Hash {
     
     if (isRight) {
       // Current is on the right, sibling is on the left
-      current = hashNodes(sibling, current);
+      current = hashNodes(sibling, current, nodeHash);
     } else {
       // Current is on the left, sibling is on the right
-      current = hashNodes(current, sibling);
+      current = hashNodes(current, sibling, nodeHash);
     }
   }
   
@@ -55,14 +62,15 @@ export function computeMerkleRoot(leaf: Hash, proof: MerkleProof): Hash {
  * Compute leaf hash from data (with domain separation)
  * 
  * @param data - Raw data to hash as a leaf
+ * @param leafHash - Hash function (keccak256 for on-chain batches)
  * @returns Leaf hash
  */
-export function computeLeafHash(data: Uint8Array): Hash {
+export function computeLeafHash(data: Uint8Array, leafHash: HashFunction = hashFn): Hash {
   // Prefix with 0x00 to distinguish from internal nodes
   const prefixed = new Uint8Array(1 + data.length);
   prefixed[0] = 0x00;
   prefixed.set(data, 1);
-  return hashFn(prefixed);
+  return leafHash(prefixed);
 }
 
 /**
@@ -85,13 +93,13 @@ export function verifyMerkleProofBatch(
 /**
  * Hash two sibling nodes together
  */
-function hashNodes(left: Hash, right: Hash): Hash {
+function hashNodes(left: Hash, right: Hash, nodeHash: HashFunction): Hash {
   // Prefix with 0x01 to distinguish from leaf hashes
   const combined = new Uint8Array(1 + left.length + right.length);
   combined[0] = 0x01;
   combined.set(left, 1);
   combined.set(right, 1 + left.length);
-  return hashFn(combined);
+  return nodeHash(combined);
 }
 
 /**
@@ -8,6 +8,9 @@ export type Hash = Uint8Array;
 /** Hex-encoded string representation of a hash */
 export type HexString = string;
 
+/** Hash function used for tree nodes (SHA-256 by default, keccak256 on-chain) */
+export type HashFunction = (data: Uint8Array) => Hash;
+
 /** WOTS signature - array of hash chains */
 export type WOTSSignature = Uint8Array[];
 
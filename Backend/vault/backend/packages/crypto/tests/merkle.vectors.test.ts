@@ -0,0 +1,142 @@
+/**
+ * Cross-implementation Merkle vectors
+ *
+ * The vectors are generated by the Rust obscura-merkle crate, which the
+ * Solana programs verify with. Any drift between the two trees fails here.
+ */
+
+import { describe, it, expect } from 'vitest';
+import { readFileSync } from 'node:fs';
+import {
+  MerkleTree,
+  verifyMerkleProof,
+  computeLeafHash,
+  computeDomainSeparator,
+  computeDomainLeafHash,
+  DOMAIN_TAG,
+  DOMAIN_VERSION,
+} from '../src/merkle/index.js';
+import { keccak256, toHex, fromHex } from '../src/hash.js';
+
+interface TreeVector {
+  depth: number;
+  leaves: string[];
+  leaf_nodes: string[];
+  root: string;
+  proofs: Array<{ index: number; siblings: string[] }>;
+}
+
+const vectors: { zero_hashes: string[]; trees: TreeVector[] } = JSON.parse(
+  readFileSync(
+    new URL(
+      '../../../contracts/solana/crates/obscura-merkle/tests/vectors/keccak.json',
+      import.meta.url
+    ),
+    'utf8'
+  )
+);
+
+/** Build a keccak tree padded to exactly 2^depth leaves */
+function buildTree(vector: TreeVector): MerkleTree {
+  const leafNodes = vector.leaves.map((leaf) => computeLeafHash(fromHex(leaf), keccak256));
+  while (leafNodes.length < 2 ** vector.depth) {
+    leafNodes.push(new Uint8Array(32));
+  }
+  return MerkleTree.fromLeaves(leafNodes, keccak256);
+}
+
+describe('Merkle cross-implementation vectors (keccak)', () => {
+  it('should match zero subtree hashes', () => {
+    const zeros = vectors.zero_hashes.length - 1;
+    const tree = MerkleTree.fromLeaves(
+      Array.from({ length: 2 ** zeros }, () => new Uint8Array(32)),
+      keccak256
+    );
+    expect(toHex(tree.root)).toBe(vectors.zero_hashes[zeros]);
+  });
+
+  for (const vector of vectors.trees) {
+    describe(`depth ${vector.depth}, ${vector.leaves.length} leaves`, () => {
+      it('should match leaf nodes', () => {
+        const leafNodes = vector.leaves.map((leaf) =>
+          toHex(computeLeafHash(fromHex(leaf), keccak256))
+        );
+        expect(leafNodes).toEqual(vector.leaf_nodes);
+      });
+
+      it('should match root', () => {
+        const tree = buildTree(vector);
+        expect(tree.depth).toBe(vector.depth);
+        expect(toHex(tree.root)).toBe(vector.root);
+      });
+
+      it('should match proofs', () => {
+        const tree = buildTree(vector);
+        for (const expected of vector.proofs) {
+          const proof = tree.getProof(expected.index);
+          expect(proof.siblings.map(toHex)).toEqual(expected.siblings);
+          expect(
+            verifyMerkleProof(
+              proof,
+              fromHex(vector.leaf_nodes[expected.index]),
+              fromHex(vector.root),
+              keccak256
+            )
+          ).toBe(true);
+        }
+      });
+    });
+  }
+});
+
+interface DomainVector {
+  cluster: string;
+  genesis_hash: string;
+  program_id: string;
+  separator: string;
+  leaves: string[];
+  keccak_leaf_nodes: string[];
+  keccak_root: string;
+}
+
+const domainVectors: { tag: string; version: number; domains: DomainVector[] } = JSON.parse(
+  readFileSync(
+    new URL(
+      '../../../contracts/solana/crates/obscura-merkle/tests/vectors/domain.json',
+      import.meta.url
+    ),
+    'utf8'
+  )
+);
+
+describe('Domain separation vectors', () => {
+  it('should share the domain tag and version', () => {
+    expect(domainVectors.tag).toBe(DOMAIN_TAG);
+    expect(domainVectors.version).toBe(DOMAIN_VERSION);
+  });
+
+  for (const vector of domainVectors.domains) {
+    describe(vector.cluster, () => {
+      it('should match the separator', () => {
+        const separator = computeDomainSeparator(
+          fromHex(vector.genesis_hash),
+          fromHex(vector.program_id)
+        );
+        expect(toHex(separator)).toBe(vector.separator);
+      });
+
+      it('should match leaf nodes and root', () => {
+        const separator = fromHex(vector.separator);
+        const leafNodes = vector.leaves.map((leaf) =>
+          computeDomainLeafHash(separator, fromHex(leaf))
+        );
+        expect(leafNodes.map(toHex)).toEqual(vector.keccak_leaf_nodes);
+
+        while (leafNodes.length < 4) {
+          leafNodes.push(new Uint8Array(32));
+        }
+        expect(toHex(MerkleTree.fromLeaves(leafNodes, keccak256).root)).toBe(vector.keccak_root);
+      });
+    });
+  }
+});